@@ -0,0 +1,484 @@
+//! Generic graph algorithms over index-based adjacency lists.
+//!
+//! The analyses in `handlers` project the code graph onto small, purpose-built
+//! graphs (files, directories, classes) whose nodes are plain `usize` indices.
+//! The algorithms here work on that representation so they can be shared.
+
+use std::collections::{BTreeSet, HashSet, VecDeque};
+
+/// Compute the strongly connected components of a directed graph.
+///
+/// `adjacency[i]` lists the successors of node `i`. Uses an iterative version of
+/// Tarjan's algorithm so deep graphs cannot overflow the stack. Components are
+/// returned in reverse topological order; every node appears in exactly one.
+pub fn strongly_connected_components(adjacency: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    let n = adjacency.len();
+    let mut index_of = vec![usize::MAX; n];
+    let mut low_link = vec![0usize; n];
+    let mut on_stack = vec![false; n];
+    let mut stack = Vec::new();
+    let mut components = Vec::new();
+    let mut next_index = 0;
+
+    for root in 0..n {
+        if index_of[root] != usize::MAX {
+            continue;
+        }
+
+        // Each frame is (node, position of the next successor to visit).
+        let mut call_stack = vec![(root, 0usize)];
+        index_of[root] = next_index;
+        low_link[root] = next_index;
+        next_index += 1;
+        stack.push(root);
+        on_stack[root] = true;
+
+        while let Some(&mut (node, ref mut next_child)) = call_stack.last_mut() {
+            if let Some(&succ) = adjacency[node].get(*next_child) {
+                *next_child += 1;
+                if succ >= n {
+                    continue;
+                }
+                if index_of[succ] == usize::MAX {
+                    index_of[succ] = next_index;
+                    low_link[succ] = next_index;
+                    next_index += 1;
+                    stack.push(succ);
+                    on_stack[succ] = true;
+                    call_stack.push((succ, 0));
+                } else if on_stack[succ] {
+                    low_link[node] = low_link[node].min(index_of[succ]);
+                }
+                continue;
+            }
+
+            // All successors visited: close the frame.
+            call_stack.pop();
+            if let Some(&(parent, _)) = call_stack.last() {
+                low_link[parent] = low_link[parent].min(low_link[node]);
+            }
+
+            if low_link[node] == index_of[node] {
+                let mut component = Vec::new();
+                while let Some(member) = stack.pop() {
+                    on_stack[member] = false;
+                    component.push(member);
+                    if member == node {
+                        break;
+                    }
+                }
+                component.sort_unstable();
+                components.push(component);
+            }
+        }
+    }
+
+    components
+}
+
+/// Return only the components that form a cycle: more than one member, or a
+/// single member with a self-loop.
+pub fn cyclic_components(adjacency: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    strongly_connected_components(adjacency)
+        .into_iter()
+        .filter(|c| c.len() > 1 || adjacency[c[0]].contains(&c[0]))
+        .collect()
+}
+
+/// Mark every node reachable from `roots` along successor edges.
+pub fn reachable_from(adjacency: &[Vec<usize>], roots: &[usize]) -> Vec<bool> {
+    let n = adjacency.len();
+    let mut reached = vec![false; n];
+    let mut stack: Vec<usize> = roots.iter().copied().filter(|&r| r < n).collect();
+    for &root in &stack {
+        reached[root] = true;
+    }
+    while let Some(node) = stack.pop() {
+        for &succ in &adjacency[node] {
+            if succ < n && !reached[succ] {
+                reached[succ] = true;
+                stack.push(succ);
+            }
+        }
+    }
+    reached
+}
+
+/// Weakly connected components of the subgraph induced by the nodes for
+/// which `include` is true, ignoring edge direction. Members are sorted.
+pub fn weakly_connected_components(adjacency: &[Vec<usize>], include: &[bool]) -> Vec<Vec<usize>> {
+    let n = adjacency.len();
+    let mut undirected = vec![Vec::new(); n];
+    for (node, successors) in adjacency.iter().enumerate() {
+        for &succ in successors {
+            if succ < n && include[node] && include[succ] {
+                undirected[node].push(succ);
+                undirected[succ].push(node);
+            }
+        }
+    }
+
+    let mut seen = vec![false; n];
+    let mut components = Vec::new();
+    for start in (0..n).filter(|&i| include[i]) {
+        if seen[start] {
+            continue;
+        }
+        seen[start] = true;
+        let mut stack = vec![start];
+        let mut component = Vec::new();
+        while let Some(node) = stack.pop() {
+            component.push(node);
+            for &next in &undirected[node] {
+                if !seen[next] {
+                    seen[next] = true;
+                    stack.push(next);
+                }
+            }
+        }
+        component.sort_unstable();
+        components.push(component);
+    }
+    components
+}
+
+/// Shortest path from `source` to `target` with at most `max_length` edges,
+/// by BFS, skipping `blocked` nodes and `removed` edges.
+fn shortest_path(
+    adjacency: &[Vec<usize>],
+    source: usize,
+    target: usize,
+    max_length: usize,
+    blocked: &[bool],
+    removed: &HashSet<(usize, usize)>,
+) -> Option<Vec<usize>> {
+    let n = adjacency.len();
+    let mut parent = vec![usize::MAX; n];
+    let mut depth = vec![usize::MAX; n];
+    depth[source] = 0;
+    let mut queue = VecDeque::from([source]);
+    while let Some(node) = queue.pop_front() {
+        if node == target {
+            let mut path = vec![target];
+            while let Some(&last) = path.last() {
+                if last == source {
+                    break;
+                }
+                path.push(parent[last]);
+            }
+            path.reverse();
+            return Some(path);
+        }
+        if depth[node] == max_length {
+            continue;
+        }
+        for &succ in &adjacency[node] {
+            if succ < n
+                && depth[succ] == usize::MAX
+                && !blocked[succ]
+                && !removed.contains(&(node, succ))
+            {
+                depth[succ] = depth[node] + 1;
+                parent[succ] = node;
+                queue.push_back(succ);
+            }
+        }
+    }
+    None
+}
+
+/// Up to `k` shortest simple paths from `source` to `target` with at most
+/// `max_length` edges, shortest first (Yen's algorithm over BFS).
+pub fn k_shortest_paths(
+    adjacency: &[Vec<usize>],
+    source: usize,
+    target: usize,
+    k: usize,
+    max_length: usize,
+) -> Vec<Vec<usize>> {
+    let n = adjacency.len();
+    if k == 0 || source >= n || target >= n {
+        return Vec::new();
+    }
+    let Some(first) = shortest_path(
+        adjacency,
+        source,
+        target,
+        max_length,
+        &vec![false; n],
+        &HashSet::new(),
+    ) else {
+        return Vec::new();
+    };
+
+    let mut paths = vec![first];
+    let mut candidates: BTreeSet<(usize, Vec<usize>)> = BTreeSet::new();
+    while paths.len() < k {
+        let previous = paths.last().cloned().unwrap_or_default();
+        for i in 0..previous.len().saturating_sub(1) {
+            let spur = previous[i];
+            let root = &previous[..=i];
+
+            let mut removed = HashSet::new();
+            for path in &paths {
+                if path.len() > i + 1 && &path[..=i] == root {
+                    removed.insert((path[i], path[i + 1]));
+                }
+            }
+            let mut blocked = vec![false; n];
+            for &node in &root[..i] {
+                blocked[node] = true;
+            }
+
+            if let Some(spur_path) =
+                shortest_path(adjacency, spur, target, max_length - i, &blocked, &removed)
+            {
+                let mut path = root[..i].to_vec();
+                path.extend(spur_path);
+                if !paths.contains(&path) {
+                    candidates.insert((path.len(), path));
+                }
+            }
+        }
+        match candidates.pop_first() {
+            Some((_, path)) => paths.push(path),
+            None => break,
+        }
+    }
+    paths
+}
+
+/// PageRank of every node, summing to 1, and the number of iterations run.
+///
+/// Mass of nodes without successors is spread evenly over all nodes. Power
+/// iteration starts from `initial` when it has one score per node (a previous
+/// result, so small graph changes converge in a few rounds) and from the
+/// uniform distribution otherwise; it stops once the L1 change drops below
+/// `tolerance`.
+pub fn pagerank(
+    adjacency: &[Vec<usize>],
+    damping: f64,
+    initial: Option<&[f64]>,
+    max_iterations: usize,
+    tolerance: f64,
+) -> (Vec<f64>, usize) {
+    let n = adjacency.len();
+    if n == 0 {
+        return (Vec::new(), 0);
+    }
+    let uniform = 1.0 / n as f64;
+    let mut ranks = match initial {
+        Some(initial) if initial.len() == n && initial.iter().sum::<f64>() > 0.0 => {
+            let total: f64 = initial.iter().sum();
+            initial.iter().map(|r| r / total).collect()
+        }
+        _ => vec![uniform; n],
+    };
+
+    let mut iterations = 0;
+    while iterations < max_iterations {
+        iterations += 1;
+        let mut next = vec![0.0; n];
+        let mut dangling = 0.0;
+        for (node, successors) in adjacency.iter().enumerate() {
+            let successors: Vec<usize> = successors.iter().copied().filter(|&s| s < n).collect();
+            if successors.is_empty() {
+                dangling += ranks[node];
+                continue;
+            }
+            let share = ranks[node] / successors.len() as f64;
+            for succ in successors {
+                next[succ] += share;
+            }
+        }
+        let base = (1.0 - damping) * uniform + damping * dangling * uniform;
+        for rank in &mut next {
+            *rank = base + damping * *rank;
+        }
+        let change: f64 = next.iter().zip(&ranks).map(|(a, b)| (a - b).abs()).sum();
+        ranks = next;
+        if change < tolerance {
+            break;
+        }
+    }
+    (ranks, iterations)
+}
+
+/// Betweenness centrality of every node in a directed, unweighted graph
+/// (Brandes' algorithm).
+///
+/// With more than `max_sources` nodes, only that many evenly spaced source
+/// nodes are used and the result is scaled up, so large graphs get a
+/// deterministic estimate in bounded time.
+pub fn betweenness_centrality(adjacency: &[Vec<usize>], max_sources: usize) -> Vec<f64> {
+    let n = adjacency.len();
+    let mut centrality = vec![0.0; n];
+    if n == 0 || max_sources == 0 {
+        return centrality;
+    }
+    let sources: Vec<usize> = if n <= max_sources {
+        (0..n).collect()
+    } else {
+        (0..max_sources).map(|i| i * n / max_sources).collect()
+    };
+
+    let mut sigma = vec![0.0f64; n];
+    let mut distance = vec![usize::MAX; n];
+    let mut delta = vec![0.0f64; n];
+    let mut predecessors: Vec<Vec<usize>> = vec![Vec::new(); n];
+    for &source in &sources {
+        sigma.iter_mut().for_each(|s| *s = 0.0);
+        distance.iter_mut().for_each(|d| *d = usize::MAX);
+        delta.iter_mut().for_each(|d| *d = 0.0);
+        predecessors.iter_mut().for_each(Vec::clear);
+
+        sigma[source] = 1.0;
+        distance[source] = 0;
+        let mut order = Vec::new();
+        let mut queue = VecDeque::from([source]);
+        while let Some(node) = queue.pop_front() {
+            order.push(node);
+            for &succ in &adjacency[node] {
+                if succ >= n {
+                    continue;
+                }
+                if distance[succ] == usize::MAX {
+                    distance[succ] = distance[node] + 1;
+                    queue.push_back(succ);
+                }
+                if distance[succ] == distance[node] + 1 {
+                    sigma[succ] += sigma[node];
+                    predecessors[succ].push(node);
+                }
+            }
+        }
+        for &node in order.iter().rev() {
+            for &pred in &predecessors[node] {
+                delta[pred] += sigma[pred] / sigma[node] * (1.0 + delta[node]);
+            }
+            if node != source {
+                centrality[node] += delta[node];
+            }
+        }
+    }
+
+    let scale = n as f64 / sources.len() as f64;
+    if scale != 1.0 {
+        centrality.iter_mut().for_each(|c| *c *= scale);
+    }
+    centrality
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(mut components: Vec<Vec<usize>>) -> Vec<Vec<usize>> {
+        components.sort();
+        components
+    }
+
+    #[test]
+    fn test_scc_empty_graph() {
+        assert!(strongly_connected_components(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_scc_acyclic_graph() {
+        let adjacency = vec![vec![1], vec![2], vec![]];
+        let components = sorted(strongly_connected_components(&adjacency));
+        assert_eq!(components, vec![vec![0], vec![1], vec![2]]);
+        assert!(cyclic_components(&adjacency).is_empty());
+    }
+
+    #[test]
+    fn test_scc_finds_cycles() {
+        // 0 -> 1 -> 2 -> 0, 2 -> 3, 3 -> 4 -> 3
+        let adjacency = vec![vec![1], vec![2], vec![0, 3], vec![4], vec![3]];
+        let components = sorted(cyclic_components(&adjacency));
+        assert_eq!(components, vec![vec![0, 1, 2], vec![3, 4]]);
+    }
+
+    #[test]
+    fn test_scc_self_loop() {
+        let adjacency = vec![vec![0], vec![0]];
+        assert_eq!(cyclic_components(&adjacency), vec![vec![0]]);
+    }
+
+    #[test]
+    fn test_scc_ignores_out_of_range_successors() {
+        let adjacency = vec![vec![7], vec![]];
+        assert_eq!(strongly_connected_components(&adjacency).len(), 2);
+    }
+
+    #[test]
+    fn test_scc_deep_chain_does_not_overflow() {
+        let n = 100_000;
+        let mut adjacency: Vec<Vec<usize>> = (0..n).map(|i| vec![i + 1]).collect();
+        adjacency[n - 1] = vec![0];
+        let components = cyclic_components(&adjacency);
+        assert_eq!(components.len(), 1);
+        assert_eq!(components[0].len(), n);
+    }
+
+    #[test]
+    fn test_reachable_from_roots() {
+        // 0 -> 1 -> 2, 3 <-> 4 (dead cycle), 5 -> 2
+        let adjacency = vec![vec![1], vec![2], vec![], vec![4], vec![3], vec![2]];
+        let reached = reachable_from(&adjacency, &[0]);
+        assert_eq!(reached, vec![true, true, true, false, false, false]);
+    }
+
+    #[test]
+    fn test_k_shortest_paths() {
+        // 0 -> 1 -> 3, 0 -> 2 -> 3, 0 -> 1 -> 2, 3 -> 0
+        let adjacency = vec![vec![1, 2], vec![3, 2], vec![3], vec![0]];
+        let paths = k_shortest_paths(&adjacency, 0, 3, 5, 10);
+        assert_eq!(paths, vec![vec![0, 1, 3], vec![0, 2, 3], vec![0, 1, 2, 3]]);
+
+        assert_eq!(
+            k_shortest_paths(&adjacency, 0, 3, 5, 1),
+            Vec::<Vec<usize>>::new()
+        );
+        assert_eq!(k_shortest_paths(&adjacency, 0, 3, 1, 2).len(), 1);
+        assert_eq!(k_shortest_paths(&adjacency, 2, 2, 3, 4), vec![vec![2]]);
+    }
+
+    #[test]
+    fn test_weakly_connected_components_of_subset() {
+        let adjacency = vec![vec![1], vec![2], vec![], vec![4], vec![3], vec![2]];
+        let dead = vec![false, false, false, true, true, true];
+        let components = sorted(weakly_connected_components(&adjacency, &dead));
+        // 5 only connects to a live node, so it is alone.
+        assert_eq!(components, vec![vec![3, 4], vec![5]]);
+    }
+
+    #[test]
+    fn test_pagerank() {
+        // 1, 2 and 3 all point at 0; 0 points back at 1.
+        let adjacency = vec![vec![1], vec![0], vec![0], vec![0]];
+        let (ranks, iterations) = pagerank(&adjacency, 0.85, None, 100, 1e-9);
+        assert!((ranks.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        assert!(ranks[0] > ranks[1] && ranks[1] > ranks[2]);
+        assert!((ranks[2] - ranks[3]).abs() < 1e-12);
+
+        // Warm-starting from the result converges immediately.
+        let (warm, warm_iterations) = pagerank(&adjacency, 0.85, Some(&ranks), 100, 1e-9);
+        assert!(warm_iterations < iterations);
+        assert!((warm[0] - ranks[0]).abs() < 1e-6);
+        assert_eq!(pagerank(&[], 0.85, None, 10, 1e-9), (Vec::new(), 0));
+    }
+
+    #[test]
+    fn test_betweenness_centrality() {
+        // 0 -> 1 -> 2 -> 3, 0 -> 4 -> 3: 1, 2 and 4 lie on shortest paths.
+        let adjacency = vec![vec![1, 4], vec![2], vec![3], vec![], vec![3]];
+        let centrality = betweenness_centrality(&adjacency, 10);
+        // 1: 0->2; 2: 1->3; 4: 0->3.
+        assert_eq!(centrality, vec![0.0, 1.0, 1.0, 0.0, 1.0]);
+
+        let sampled = betweenness_centrality(&adjacency, 2);
+        assert_eq!(sampled.len(), 5);
+        assert_eq!(sampled[0], 0.0);
+    }
+}
@@ -4,20 +4,33 @@
 
 use crate::cache::QueryCache;
 use crate::error::{LspError, LspResult};
-use crate::index::SymbolIndex;
+use crate::handlers::diagnostics::{DiagnosticsConfig, DiagnosticsScope, WorkspaceFindings};
+use crate::handlers::key_symbols::SymbolRankCache;
+use crate::index::{self, SymbolIndex};
+use crate::lexer::{self, Language};
+use crate::occurrences::{self, OccurrenceIndex, OccurrenceKind, ResolvedOccurrence};
 use crate::parser_registry::ParserRegistry;
 use crate::watcher::FileWatcher;
-use codegraph::{CodeGraph, Direction, EdgeType, NodeId, NodeType};
+use codegraph::{CodeGraph, Direction, EdgeType, NodeId, NodeType, PropertyMap};
 use codegraph_parser_api::FileInfo;
 use dashmap::DashMap;
-use std::path::PathBuf;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::{Mutex, RwLock};
 use tower_lsp::jsonrpc::Result;
 use tower_lsp::lsp_types::*;
 use tower_lsp::{Client, LanguageServer};
 
+/// Read an integer property, accepting the numeric strings some parsers store.
+pub(crate) fn int_property(properties: &PropertyMap, key: &str) -> Option<i64> {
+    properties
+        .get_int(key)
+        .or_else(|| properties.get_string(key)?.trim().parse().ok())
+}
+
 /// CodeGraph Language Server backend.
+#[derive(Clone)]
 pub struct CodeGraphBackend {
     /// LSP client for sending notifications.
     pub client: Client,
@@ -37,8 +50,26 @@ pub struct CodeGraphBackend {
     /// Symbol index for fast lookups.
     pub symbol_index: Arc<SymbolIndex>,
 
+    /// Identifier occurrences per file, for navigation inside function bodies.
+    pub occurrence_index: Arc<OccurrenceIndex>,
+
+    /// Text of documents currently open in the editor.
+    pub open_documents: Arc<DashMap<Url, String>>,
+
     /// Workspace folders
-    workspace_folders: Arc<RwLock<Vec<std::path::PathBuf>>>,
+    pub(crate) workspace_folders: Arc<RwLock<Vec<std::path::PathBuf>>>,
+
+    /// Diagnostics settings from the client.
+    pub(crate) diagnostics_config: Arc<RwLock<DiagnosticsConfig>>,
+
+    /// Diagnostics last published per document, so they can be cleared.
+    pub(crate) published_diagnostics: Arc<DashMap<Url, Vec<Diagnostic>>>,
+
+    /// Import and liveness analyses shared by diagnostics passes.
+    pub(crate) workspace_findings: Arc<Mutex<WorkspaceFindings>>,
+
+    /// PageRank and betweenness of symbols, recomputed after graph changes.
+    pub(crate) rank_cache: Arc<SymbolRankCache>,
 
     /// File system watcher for incremental updates.
     file_watcher: Arc<Mutex<Option<FileWatcher>>>,
@@ -56,51 +87,149 @@ impl CodeGraphBackend {
             file_cache: Arc::new(DashMap::new()),
             query_cache: Arc::new(QueryCache::new(1000)),
             symbol_index: Arc::new(SymbolIndex::new()),
+            occurrence_index: Arc::new(OccurrenceIndex::new()),
+            open_documents: Arc::new(DashMap::new()),
             workspace_folders: Arc::new(RwLock::new(Vec::new())),
+            diagnostics_config: Arc::new(RwLock::new(DiagnosticsConfig::default())),
+            published_diagnostics: Arc::new(DashMap::new()),
+            workspace_findings: Arc::new(Mutex::new(WorkspaceFindings::default())),
+            rank_cache: Arc::new(SymbolRankCache::default()),
             file_watcher: Arc::new(Mutex::new(None)),
         }
     }
 
-    /// Start the file watcher for the given workspace folders.
+    /// Watch the given workspace folders, creating the file watcher on first use.
     pub async fn start_file_watcher(&self, folders: &[PathBuf]) {
-        // Create the file watcher
-        match FileWatcher::new(
-            Arc::clone(&self.graph),
-            Arc::clone(&self.parsers),
-            self.client.clone(),
-        ) {
-            Ok(mut watcher) => {
-                // Start watching each folder
-                for folder in folders {
-                    if let Err(e) = watcher.watch(folder) {
-                        self.client
-                            .log_message(
-                                MessageType::WARNING,
-                                format!("Failed to watch {}: {}", folder.display(), e),
-                            )
-                            .await;
-                    } else {
-                        self.client
-                            .log_message(
-                                MessageType::INFO,
-                                format!("Watching folder: {}", folder.display()),
-                            )
-                            .await;
-                    }
+        let mut file_watcher = self.file_watcher.lock().await;
+
+        if file_watcher.is_none() {
+            match FileWatcher::new(
+                Arc::clone(&self.graph),
+                Arc::clone(&self.parsers),
+                self.client.clone(),
+            ) {
+                Ok(watcher) => *file_watcher = Some(watcher),
+                Err(e) => {
+                    self.client
+                        .log_message(
+                            MessageType::ERROR,
+                            format!("Failed to create file watcher: {e}"),
+                        )
+                        .await;
+                    return;
                 }
-
-                // Store the watcher
-                *self.file_watcher.lock().await = Some(watcher);
             }
-            Err(e) => {
+        }
+
+        let Some(watcher) = file_watcher.as_mut() else {
+            return;
+        };
+        for folder in folders {
+            if let Err(e) = watcher.watch(folder) {
                 self.client
                     .log_message(
-                        MessageType::ERROR,
-                        format!("Failed to create file watcher: {e}"),
+                        MessageType::WARNING,
+                        format!("Failed to watch {}: {}", folder.display(), e),
                     )
                     .await;
+            } else {
+                self.client
+                    .log_message(
+                        MessageType::INFO,
+                        format!("Watching folder: {}", folder.display()),
+                    )
+                    .await;
+            }
+        }
+    }
+
+    /// Stop watching a workspace folder.
+    async fn stop_watching(&self, folder: &std::path::Path) {
+        if let Some(watcher) = self.file_watcher.lock().await.as_mut() {
+            if let Err(e) = watcher.unwatch(folder) {
+                tracing::warn!("Failed to unwatch {}: {}", folder.display(), e);
+            }
+        }
+    }
+
+    /// Index and watch a folder added to the workspace.
+    async fn add_workspace_folder(&self, folder: PathBuf) {
+        {
+            let mut folders = self.workspace_folders.write().await;
+            if folders.contains(&folder) {
+                return;
+            }
+            folders.push(folder.clone());
+        }
+
+        // A folder nested in one already open is indexed and watched already.
+        let covered = self
+            .workspace_folders
+            .read()
+            .await
+            .iter()
+            .any(|f| f != &folder && folder.starts_with(f));
+        if covered {
+            return;
+        }
+
+        let count = self.index_directory(&folder).await;
+        self.client
+            .log_message(
+                MessageType::INFO,
+                format!("Indexed {} files from {}", count, folder.display()),
+            )
+            .await;
+        self.start_file_watcher(std::slice::from_ref(&folder)).await;
+    }
+
+    /// Stop watching a folder removed from the workspace and purge its files,
+    /// except those still covered by another workspace folder.
+    async fn remove_workspace_folder(&self, folder: &std::path::Path) {
+        let remaining = {
+            let mut folders = self.workspace_folders.write().await;
+            folders.retain(|f| f != folder);
+            folders.clone()
+        };
+        if remaining.iter().any(|f| folder.starts_with(f)) {
+            return;
+        }
+
+        self.stop_watching(folder).await;
+        // Folders nested in the removed one lost their recursive watch.
+        let nested: Vec<PathBuf> = remaining
+            .iter()
+            .filter(|f| f.starts_with(folder))
+            .cloned()
+            .collect();
+        if !nested.is_empty() {
+            self.start_file_watcher(&nested).await;
+        }
+
+        let mut files: HashSet<PathBuf> = self.symbol_index.files().into_iter().collect();
+        files.extend(
+            self.file_cache
+                .iter()
+                .filter_map(|entry| entry.key().to_file_path().ok()),
+        );
+        let purged: Vec<PathBuf> = files
+            .into_iter()
+            .filter(|p| p.starts_with(folder) && !remaining.iter().any(|f| p.starts_with(f)))
+            .collect();
+
+        for path in &purged {
+            self.remove_file_from_graph(path).await;
+            if let Ok(uri) = Url::from_file_path(path) {
+                self.file_cache.remove(&uri);
             }
         }
+
+        self.client
+            .log_message(
+                MessageType::INFO,
+                format!("Removed {} files from {}", purged.len(), folder.display()),
+            )
+            .await;
     }
 
     /// Remove all nodes associated with a file from the graph.
@@ -115,13 +244,20 @@ impl CodeGraphBackend {
             }
         }
 
+        // Symbol nodes carry no path property; the symbol index knows them
+        for node_id in self.symbol_index.get_file_symbols(path) {
+            let _ = graph.delete_node(node_id);
+        }
+
         // Invalidate caches
         self.query_cache.invalidate_file(&path.to_path_buf());
         self.symbol_index.remove_file(path);
+        self.occurrence_index.remove_file(path);
+        self.schedule_rank_refresh();
     }
 
     /// Index all supported files in a directory
-    fn index_directory<'a>(
+    pub(crate) fn index_directory<'a>(
         &'a self,
         dir: &'a std::path::Path,
     ) -> std::pin::Pin<Box<dyn std::future::Future<Output = usize> + Send + 'a>> {
@@ -174,6 +310,9 @@ impl CodeGraphBackend {
                                                 &file_info,
                                                 &graph,
                                             );
+                                            if let Ok(text) = fs::read_to_string(&path) {
+                                                self.occurrence_index.index_file(&path, &text);
+                                            }
                                             self.file_cache.insert(
                                                 Url::from_file_path(&path).unwrap(),
                                                 file_info,
@@ -536,6 +675,134 @@ impl CodeGraphBackend {
         Ok(None)
     }
 
+    /// Resolve the identifier under the cursor through the occurrence index.
+    pub fn resolve_occurrence_at(
+        &self,
+        graph: &CodeGraph,
+        path: &Path,
+        position: Position,
+    ) -> Option<ResolvedOccurrence> {
+        let occurrence = self.occurrence_index.find_at(path, position)?;
+        let target = occurrences::resolve(graph, &self.symbol_index, path, &occurrence)?;
+        Some(ResolvedOccurrence {
+            path: path.to_path_buf(),
+            occurrence,
+            target,
+        })
+    }
+
+    /// All occurrences in the workspace that resolve to `target`.
+    pub fn find_target_occurrences(
+        &self,
+        graph: &CodeGraph,
+        target: NodeId,
+    ) -> Vec<ResolvedOccurrence> {
+        let Some(name) = graph
+            .get_node(target)
+            .ok()
+            .and_then(|n| n.properties.get_string("name"))
+        else {
+            return Vec::new();
+        };
+
+        self.occurrence_index
+            .occurrences_named(index::short_name(name))
+            .into_iter()
+            .filter(|(path, occurrence)| {
+                occurrences::resolve(graph, &self.symbol_index, path, occurrence) == Some(target)
+            })
+            .map(|(path, occurrence)| ResolvedOccurrence {
+                path,
+                occurrence,
+                target,
+            })
+            .collect()
+    }
+
+    /// Current text of a file: the open document if any, otherwise the file on disk.
+    pub fn document_text(&self, path: &Path) -> Option<String> {
+        if let Ok(uri) = Url::from_file_path(path) {
+            if let Some(doc) = self.open_documents.get(&uri) {
+                return Some(doc.value().clone());
+            }
+        }
+        std::fs::read_to_string(path).ok()
+    }
+
+    /// Ranges of the call expressions in `caller` that call `callee`, one per call.
+    ///
+    /// Uses the `call_site_line` recorded on Calls edges when present and the
+    /// caller's body otherwise. Returns nothing when the calls cannot be
+    /// located in the source.
+    pub fn find_call_site_ranges(
+        &self,
+        graph: &CodeGraph,
+        caller: NodeId,
+        callee: NodeId,
+    ) -> Vec<Range> {
+        let Ok(caller_location) = self.node_to_location(graph, caller) else {
+            return Vec::new();
+        };
+
+        let Some(callee_name) = graph
+            .get_node(callee)
+            .ok()
+            .and_then(|n| n.properties.get_string("name"))
+        else {
+            return Vec::new();
+        };
+        // Methods are recorded as `Class.method` or `Type::method`.
+        let short_name = index::short_name(callee_name);
+
+        let Ok(path) = caller_location.uri.to_file_path() else {
+            return Vec::new();
+        };
+        let (Some(language), Some(text)) = (Language::from_path(&path), self.document_text(&path))
+        else {
+            return Vec::new();
+        };
+        let tokens = lexer::tokenize(&text, language);
+
+        let recorded_lines: Vec<u32> = graph
+            .get_edges_between(caller, callee)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|id| graph.get_edge(id).ok())
+            .filter(|edge| edge.edge_type == EdgeType::Calls)
+            .filter_map(|edge| int_property(&edge.properties, "call_site_line"))
+            .filter(|line| *line > 0)
+            .map(|line| line as u32 - 1)
+            .collect();
+
+        let mut sites: Vec<Range> = recorded_lines
+            .iter()
+            .flat_map(|&line| lexer::find_calls(&tokens, short_name, line..=line, language))
+            .collect();
+
+        if sites.is_empty() {
+            let body = caller_location.range.start.line..=caller_location.range.end.line;
+            sites = lexer::find_calls(&tokens, short_name, body, language);
+        }
+
+        if sites.is_empty() && !recorded_lines.is_empty() {
+            // The call is on a known line but not lexically visible (e.g. a macro).
+            sites = recorded_lines
+                .iter()
+                .map(|&line| Range {
+                    start: Position { line, character: 0 },
+                    end: Position {
+                        line,
+                        character: u32::MAX,
+                    },
+                })
+                .collect();
+        }
+
+        sites.sort_by_key(|r| (r.start.line, r.start.character));
+        sites.dedup();
+        sites
+    }
+
     /// Helper to get a string property from a node
     #[allow(dead_code)]
     fn get_node_string_property(
@@ -569,6 +836,14 @@ impl LanguageServer for CodeGraphBackend {
             }
         }
 
+        if let Some(config) = params
+            .initialization_options
+            .as_ref()
+            .and_then(DiagnosticsConfig::from_settings)
+        {
+            *self.diagnostics_config.write().await = config;
+        }
+
         Ok(InitializeResult {
             capabilities: ServerCapabilities {
                 text_document_sync: Some(TextDocumentSyncCapability::Options(
@@ -583,26 +858,46 @@ impl LanguageServer for CodeGraphBackend {
                 )),
                 definition_provider: Some(OneOf::Left(true)),
                 references_provider: Some(OneOf::Left(true)),
+                document_highlight_provider: Some(OneOf::Left(true)),
+                completion_provider: Some(CompletionOptions::default()),
+                semantic_tokens_provider: Some(
+                    SemanticTokensServerCapabilities::SemanticTokensOptions(
+                        SemanticTokensOptions {
+                            legend: crate::handlers::semantic_tokens::legend(),
+                            full: Some(SemanticTokensFullOptions::Bool(true)),
+                            range: None,
+                            work_done_progress_options: WorkDoneProgressOptions::default(),
+                        },
+                    ),
+                ),
+                signature_help_provider: Some(SignatureHelpOptions {
+                    trigger_characters: Some(vec!["(".to_string(), ",".to_string()]),
+                    retrigger_characters: None,
+                    work_done_progress_options: WorkDoneProgressOptions::default(),
+                }),
                 hover_provider: Some(HoverProviderCapability::Simple(true)),
                 document_symbol_provider: Some(OneOf::Left(true)),
                 call_hierarchy_provider: Some(CallHierarchyServerCapability::Simple(true)),
+                code_action_provider: Some(CodeActionProviderCapability::Options(
+                    CodeActionOptions {
+                        code_action_kinds: Some(vec![
+                            CodeActionKind::QUICKFIX,
+                            CodeActionKind::REFACTOR,
+                        ]),
+                        ..Default::default()
+                    },
+                )),
                 execute_command_provider: Some(ExecuteCommandOptions {
-                    commands: vec![
-                        "codegraph.getDependencyGraph".to_string(),
-                        "codegraph.getCallGraph".to_string(),
-                        "codegraph.analyzeImpact".to_string(),
-                        "codegraph.getParserMetrics".to_string(),
-                        "codegraph.reindexWorkspace".to_string(),
-                        "codegraph.getAIContext".to_string(),
-                        "codegraph.findRelatedTests".to_string(),
-                        "codegraph.getNodeLocation".to_string(),
-                        "codegraph.getWorkspaceSymbols".to_string(),
-                        "codegraph.analyzeComplexity".to_string(),
-                        "codegraph.findUnusedCode".to_string(),
-                        "codegraph.analyzeCoupling".to_string(),
-                    ],
+                    commands: crate::custom_requests::command_names(),
                     work_done_progress_options: WorkDoneProgressOptions::default(),
                 }),
+                workspace: Some(WorkspaceServerCapabilities {
+                    workspace_folders: Some(WorkspaceFoldersServerCapabilities {
+                        supported: Some(true),
+                        change_notifications: Some(OneOf::Left(true)),
+                    }),
+                    file_operations: None,
+                }),
                 ..Default::default()
             },
             ..Default::default()
@@ -635,11 +930,14 @@ impl LanguageServer for CodeGraphBackend {
                 format!("Total files indexed: {total_indexed}"),
             )
             .await;
+        self.schedule_rank_refresh();
 
         // Start file watcher for incremental updates
         if !folders.is_empty() {
             self.start_file_watcher(&folders).await;
         }
+
+        self.publish_all_diagnostics().await;
     }
 
     async fn shutdown(&self) -> Result<()> {
@@ -653,6 +951,8 @@ impl LanguageServer for CodeGraphBackend {
 
         tracing::info!("did_open called for: {}", uri);
 
+        self.open_documents.insert(uri.clone(), text.clone());
+
         let path = match uri.to_file_path() {
             Ok(p) => p,
             Err(_) => {
@@ -670,13 +970,18 @@ impl LanguageServer for CodeGraphBackend {
                     tracing::info!("Parse succeeded for: {:?}", path);
                     // Update symbol index
                     self.symbol_index.add_file(path.clone(), &file_info, &graph);
+                    self.occurrence_index.index_file(&path, &text);
 
                     // Update file cache
                     self.file_cache.insert(uri.clone(), file_info);
 
+                    drop(graph);
+
+                    self.workspace_file_changed(&path).await;
                     self.client
                         .log_message(MessageType::INFO, format!("Indexed: {uri}"))
                         .await;
+                    self.publish_diagnostics(std::slice::from_ref(&uri)).await;
                 }
                 Err(e) => {
                     tracing::error!("Parse failed for {:?}: {}", path, e);
@@ -700,6 +1005,8 @@ impl LanguageServer for CodeGraphBackend {
 
         // Get the full text (assuming full sync mode)
         if let Some(change) = params.content_changes.into_iter().next() {
+            self.open_documents.insert(uri.clone(), change.text.clone());
+
             if let Some(parser) = self.parsers.parser_for_path(&path) {
                 // Remove old entries
                 self.remove_file_from_graph(&path).await;
@@ -708,8 +1015,11 @@ impl LanguageServer for CodeGraphBackend {
                 let mut graph = self.graph.write().await;
                 if let Ok(file_info) = parser.parse_source(&change.text, &path, &mut graph) {
                     self.symbol_index.add_file(path.clone(), &file_info, &graph);
+                    self.occurrence_index.index_file(&path, &change.text);
                     self.file_cache.insert(uri, file_info);
                 }
+                drop(graph);
+                self.workspace_file_changed(&path).await;
             }
         }
     }
@@ -729,15 +1039,51 @@ impl LanguageServer for CodeGraphBackend {
                 let mut graph = self.graph.write().await;
                 if let Ok(file_info) = parser.parse_source(&text, &path, &mut graph) {
                     self.symbol_index.add_file(path.clone(), &file_info, &graph);
-                    self.file_cache.insert(uri, file_info);
+                    self.occurrence_index.index_file(&path, &text);
+                    self.file_cache.insert(uri.clone(), file_info);
                 }
+                drop(graph);
+                self.workspace_file_changed(&path).await;
             }
         }
+
+        self.publish_diagnostics_after_save(&uri).await;
     }
 
     async fn did_close(&self, params: DidCloseTextDocumentParams) {
+        let uri = params.text_document.uri;
+        self.open_documents.remove(&uri);
+
         // Keep in graph for cross-file references, but remove from file cache
-        self.file_cache.remove(&params.text_document.uri);
+        self.file_cache.remove(&uri);
+
+        if self.diagnostics_config.read().await.scope == DiagnosticsScope::OpenFiles {
+            self.clear_diagnostics(&uri).await;
+        }
+    }
+
+    async fn did_change_workspace_folders(&self, params: DidChangeWorkspaceFoldersParams) {
+        for folder in params.event.removed {
+            if let Ok(path) = folder.uri.to_file_path() {
+                tracing::info!("Workspace folder removed: {}", path.display());
+                self.remove_workspace_folder(&path).await;
+            }
+        }
+        for folder in params.event.added {
+            if let Ok(path) = folder.uri.to_file_path() {
+                tracing::info!("Workspace folder added: {}", path.display());
+                self.add_workspace_folder(path).await;
+            }
+        }
+
+        self.invalidate_workspace_findings().await;
+        self.publish_all_diagnostics().await;
+    }
+
+    async fn did_change_configuration(&self, params: DidChangeConfigurationParams) {
+        if let Some(config) = DiagnosticsConfig::from_settings(&params.settings) {
+            self.update_diagnostics_config(config).await;
+        }
     }
 
     async fn goto_definition(
@@ -753,6 +1099,13 @@ impl LanguageServer for CodeGraphBackend {
 
         let graph = self.graph.read().await;
 
+        // Identifier under the cursor (e.g. a call inside a function body)
+        if let Some(resolved) = self.resolve_occurrence_at(&graph, &path, position) {
+            if let Ok(location) = self.node_to_location(&graph, resolved.target) {
+                return Ok(Some(GotoDefinitionResponse::Scalar(location)));
+            }
+        }
+
         // Find node at the given position
         let node_id = match self.find_node_at_position(&graph, &path, position)? {
             Some(id) => id,
@@ -795,6 +1148,24 @@ impl LanguageServer for CodeGraphBackend {
 
         let graph = self.graph.read().await;
 
+        // Exact reference ranges from the occurrence index
+        if let Some(resolved) = self.resolve_occurrence_at(&graph, &path, position) {
+            let locations: Vec<Location> = self
+                .find_target_occurrences(&graph, resolved.target)
+                .into_iter()
+                .filter(|o| include_declaration || o.occurrence.kind != OccurrenceKind::Definition)
+                .filter_map(|o| {
+                    Some(Location {
+                        uri: Url::from_file_path(&o.path).ok()?,
+                        range: o.occurrence.range,
+                    })
+                })
+                .collect();
+            if !locations.is_empty() {
+                return Ok(Some(locations));
+            }
+        }
+
         // Find node at position
         let node_id = match self.find_node_at_position(&graph, &path, position)? {
             Some(id) => id,
@@ -841,9 +1212,13 @@ impl LanguageServer for CodeGraphBackend {
 
         let graph = self.graph.read().await;
 
-        let node_id = match self.find_node_at_position(&graph, &path, position)? {
-            Some(id) => id,
-            None => return Ok(None),
+        let resolved = self.resolve_occurrence_at(&graph, &path, position);
+        let node_id = match &resolved {
+            Some(r) => r.target,
+            None => match self.find_node_at_position(&graph, &path, position)? {
+                Some(id) => id,
+                None => return Ok(None),
+            },
         };
 
         let node = graph
@@ -885,10 +1260,32 @@ impl LanguageServer for CodeGraphBackend {
                 kind: MarkupKind::Markdown,
                 value: content,
             }),
-            range: None,
+            range: resolved.map(|r| r.occurrence.range),
         }))
     }
 
+    async fn completion(&self, params: CompletionParams) -> Result<Option<CompletionResponse>> {
+        self.handle_completion(params).await
+    }
+
+    async fn semantic_tokens_full(
+        &self,
+        params: SemanticTokensParams,
+    ) -> Result<Option<SemanticTokensResult>> {
+        self.handle_semantic_tokens_full(params).await
+    }
+
+    async fn signature_help(&self, params: SignatureHelpParams) -> Result<Option<SignatureHelp>> {
+        self.handle_signature_help(params).await
+    }
+
+    async fn document_highlight(
+        &self,
+        params: DocumentHighlightParams,
+    ) -> Result<Option<Vec<DocumentHighlight>>> {
+        self.handle_document_highlight(params).await
+    }
+
     async fn document_symbol(
         &self,
         params: DocumentSymbolParams,
@@ -941,6 +1338,10 @@ impl LanguageServer for CodeGraphBackend {
         }
     }
 
+    async fn code_action(&self, params: CodeActionParams) -> Result<Option<CodeActionResponse>> {
+        self.handle_code_action(params).await
+    }
+
     async fn prepare_call_hierarchy(
         &self,
         params: CallHierarchyPrepareParams,
@@ -1004,6 +1405,10 @@ impl LanguageServer for CodeGraphBackend {
                     let name = node.properties.get_string("name").unwrap_or("").to_string();
 
                     if let Ok(location) = self.node_to_location(&graph, source) {
+                        let mut from_ranges = self.find_call_site_ranges(&graph, source, node_id);
+                        if from_ranges.is_empty() {
+                            from_ranges.push(location.range);
+                        }
                         calls.push(CallHierarchyIncomingCall {
                             from: CallHierarchyItem {
                                 name,
@@ -1018,7 +1423,7 @@ impl LanguageServer for CodeGraphBackend {
                                 selection_range: location.range,
                                 data: Some(serde_json::json!({ "nodeId": source.to_string() })),
                             },
-                            from_ranges: vec![location.range],
+                            from_ranges,
                         });
                     }
                 }
@@ -1050,6 +1455,11 @@ impl LanguageServer for CodeGraphBackend {
                     let name = node.properties.get_string("name").unwrap_or("").to_string();
 
                     if let Ok(location) = self.node_to_location(&graph, target) {
+                        // Ranges are in the caller's document, where the calls are made.
+                        let mut from_ranges = self.find_call_site_ranges(&graph, node_id, target);
+                        if from_ranges.is_empty() {
+                            from_ranges.push(params.item.selection_range);
+                        }
                         calls.push(CallHierarchyOutgoingCall {
                             to: CallHierarchyItem {
                                 name,
@@ -1064,7 +1474,7 @@ impl LanguageServer for CodeGraphBackend {
                                 selection_range: location.range,
                                 data: Some(serde_json::json!({ "nodeId": target.to_string() })),
                             },
-                            from_ranges: vec![location.range],
+                            from_ranges,
                         });
                     }
                 }
@@ -1084,171 +1494,14 @@ impl LanguageServer for CodeGraphBackend {
     ) -> Result<Option<serde_json::Value>> {
         tracing::info!("Executing command: {}", params.command);
 
-        match params.command.as_str() {
-            "codegraph.getDependencyGraph" => {
-                let args = params.arguments.first().ok_or_else(|| {
-                    tower_lsp::jsonrpc::Error::invalid_params("Missing arguments")
-                })?;
-                let params: crate::handlers::DependencyGraphParams =
-                    serde_json::from_value(args.clone()).map_err(|e| {
-                        tower_lsp::jsonrpc::Error::invalid_params(format!("Invalid params: {e}"))
-                    })?;
-                let response = self.handle_get_dependency_graph(params).await?;
-                Ok(Some(serde_json::to_value(response).unwrap()))
-            }
-
-            "codegraph.getCallGraph" => {
-                let args = params.arguments.first().ok_or_else(|| {
-                    tower_lsp::jsonrpc::Error::invalid_params("Missing arguments")
-                })?;
-                let params: crate::handlers::CallGraphParams = serde_json::from_value(args.clone())
-                    .map_err(|e| {
-                        tower_lsp::jsonrpc::Error::invalid_params(format!("Invalid params: {e}"))
-                    })?;
-                let response = self.handle_get_call_graph(params).await?;
-                Ok(Some(serde_json::to_value(response).unwrap()))
-            }
-
-            "codegraph.analyzeImpact" => {
-                let args = params.arguments.first().ok_or_else(|| {
-                    tower_lsp::jsonrpc::Error::invalid_params("Missing arguments")
-                })?;
-                let params: crate::handlers::ImpactAnalysisParams =
-                    serde_json::from_value(args.clone()).map_err(|e| {
-                        tower_lsp::jsonrpc::Error::invalid_params(format!("Invalid params: {e}"))
-                    })?;
-                let response = self.handle_analyze_impact(params).await?;
-                Ok(Some(serde_json::to_value(response).unwrap()))
-            }
-
-            "codegraph.getParserMetrics" => {
-                let response = self
-                    .handle_get_parser_metrics(crate::handlers::ParserMetricsParams {
-                        language: None,
-                    })
-                    .await?;
-                Ok(Some(serde_json::to_value(response).unwrap()))
-            }
-
-            "codegraph.reindexWorkspace" => {
-                // Clear graph and caches
-                {
-                    let mut graph = self.graph.write().await;
-                    *graph = CodeGraph::in_memory().expect("Failed to create graph");
-                }
-                self.symbol_index.clear();
-                self.file_cache.clear();
-
-                self.client
-                    .log_message(MessageType::INFO, "Reindexing workspace...")
-                    .await;
-
-                // Index all workspace folders
-                let workspace_folders = self.workspace_folders.read().await.clone();
-                let mut total_indexed = 0;
-
-                for folder in workspace_folders {
-                    tracing::info!("Indexing folder: {:?}", folder);
-                    let count = self.index_directory(&folder).await;
-                    total_indexed += count;
-                }
-
-                self.client
-                    .log_message(
-                        MessageType::INFO,
-                        format!("Workspace reindexed: {total_indexed} files"),
-                    )
-                    .await;
-
-                Ok(None)
-            }
-
-            "codegraph.getAIContext" => {
-                let args = params.arguments.first().ok_or_else(|| {
-                    tower_lsp::jsonrpc::Error::invalid_params("Missing arguments")
-                })?;
-                let params: crate::handlers::AIContextParams = serde_json::from_value(args.clone())
-                    .map_err(|e| {
-                        tower_lsp::jsonrpc::Error::invalid_params(format!("Invalid params: {e}"))
-                    })?;
-                let response = self.handle_get_ai_context(params).await?;
-                Ok(Some(serde_json::to_value(response).unwrap()))
-            }
-
-            "codegraph.findRelatedTests" => {
-                let args = params.arguments.first().ok_or_else(|| {
-                    tower_lsp::jsonrpc::Error::invalid_params("Missing arguments")
-                })?;
-                let params: crate::handlers::RelatedTestsParams =
-                    serde_json::from_value(args.clone()).map_err(|e| {
-                        tower_lsp::jsonrpc::Error::invalid_params(format!("Invalid params: {e}"))
-                    })?;
-                let response = self.handle_find_related_tests(params).await?;
-                Ok(Some(serde_json::to_value(response).unwrap()))
-            }
-
-            "codegraph.getNodeLocation" => {
-                let args = params.arguments.first().ok_or_else(|| {
-                    tower_lsp::jsonrpc::Error::invalid_params("Missing arguments")
-                })?;
-                let params: crate::handlers::GetNodeLocationParams =
-                    serde_json::from_value(args.clone()).map_err(|e| {
-                        tower_lsp::jsonrpc::Error::invalid_params(format!("Invalid params: {e}"))
-                    })?;
-                let response = self.handle_get_node_location(params).await?;
-                Ok(Some(serde_json::to_value(response).unwrap()))
-            }
-
-            "codegraph.getWorkspaceSymbols" => {
-                let args = params.arguments.first().ok_or_else(|| {
-                    tower_lsp::jsonrpc::Error::invalid_params("Missing arguments")
-                })?;
-                let params: crate::handlers::WorkspaceSymbolsParams =
-                    serde_json::from_value(args.clone()).map_err(|e| {
-                        tower_lsp::jsonrpc::Error::invalid_params(format!("Invalid params: {e}"))
-                    })?;
-                let response = self.handle_get_workspace_symbols(params).await?;
-                Ok(Some(serde_json::to_value(response).unwrap()))
-            }
-
-            "codegraph.analyzeComplexity" => {
-                let args = params.arguments.first().ok_or_else(|| {
-                    tower_lsp::jsonrpc::Error::invalid_params("Missing arguments")
-                })?;
-                let params: crate::handlers::ComplexityParams =
-                    serde_json::from_value(args.clone()).map_err(|e| {
-                        tower_lsp::jsonrpc::Error::invalid_params(format!("Invalid params: {e}"))
-                    })?;
-                let response = self.handle_analyze_complexity(params).await?;
-                Ok(Some(serde_json::to_value(response).unwrap()))
-            }
-
-            "codegraph.findUnusedCode" => {
-                let args = params.arguments.first().ok_or_else(|| {
-                    tower_lsp::jsonrpc::Error::invalid_params("Missing arguments")
-                })?;
-                let params: crate::handlers::UnusedCodeParams =
-                    serde_json::from_value(args.clone()).map_err(|e| {
-                        tower_lsp::jsonrpc::Error::invalid_params(format!("Invalid params: {e}"))
-                    })?;
-                let response = self.handle_find_unused_code(params).await?;
-                Ok(Some(serde_json::to_value(response).unwrap()))
-            }
-
-            "codegraph.analyzeCoupling" => {
-                let args = params.arguments.first().ok_or_else(|| {
-                    tower_lsp::jsonrpc::Error::invalid_params("Missing arguments")
-                })?;
-                let params: crate::handlers::CouplingParams = serde_json::from_value(args.clone())
-                    .map_err(|e| {
-                        tower_lsp::jsonrpc::Error::invalid_params(format!("Invalid params: {e}"))
-                    })?;
-                let response = self.handle_analyze_coupling(params).await?;
-                Ok(Some(serde_json::to_value(response).unwrap()))
-            }
-
-            _ => Err(tower_lsp::jsonrpc::Error::method_not_found()),
-        }
+        let args = params
+            .arguments
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| serde_json::json!({}));
+        self.handle_custom_request(&params.command, args)
+            .await
+            .map(Some)
     }
 }
 
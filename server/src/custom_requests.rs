@@ -1,104 +1,105 @@
 //! Custom LSP requests for CodeGraph-specific features.
 //!
-//! Tower-LSP handles custom requests through the request method on LanguageServer trait.
+//! Every analysis is declared once in the registry below and exposed two ways:
+//! as a typed `codegraph/<name>` JSON-RPC request registered with tower-lsp, and
+//! as a `codegraph.<name>` command through `workspace/executeCommand`, whose
+//! first argument carries the same params object.
 
 use crate::backend::CodeGraphBackend;
 use crate::handlers::*;
 use serde_json::Value;
 use tower_lsp::jsonrpc::{Error, Result};
+use tower_lsp::LspServiceBuilder;
 
-/// Custom request handler dispatcher
-impl CodeGraphBackend {
-    pub async fn handle_custom_request(&self, method: &str, params: Value) -> Result<Value> {
-        match method {
-            "codegraph/getDependencyGraph" => {
-                let params: DependencyGraphParams = serde_json::from_value(params)
-                    .map_err(|e| Error::invalid_params(format!("Invalid params: {e}")))?;
-                let response = self.handle_get_dependency_graph(params).await?;
-                serde_json::to_value(response).map_err(|_| Error::internal_error())
-            }
-
-            "codegraph/getCallGraph" => {
-                let params: CallGraphParams = serde_json::from_value(params)
-                    .map_err(|e| Error::invalid_params(format!("Invalid params: {e}")))?;
-                let response = self.handle_get_call_graph(params).await?;
-                serde_json::to_value(response).map_err(|_| Error::internal_error())
-            }
-
-            "codegraph/analyzeImpact" => {
-                let params: ImpactAnalysisParams = serde_json::from_value(params)
-                    .map_err(|e| Error::invalid_params(format!("Invalid params: {e}")))?;
-                let response = self.handle_analyze_impact(params).await?;
-                serde_json::to_value(response).map_err(|_| Error::internal_error())
-            }
-
-            "codegraph/getParserMetrics" => {
-                let params: ParserMetricsParams = serde_json::from_value(params)
-                    .map_err(|e| Error::invalid_params(format!("Invalid params: {e}")))?;
-                let response = self.handle_get_parser_metrics(params).await?;
-                serde_json::to_value(response).map_err(|_| Error::internal_error())
-            }
-
-            "codegraph/reindexWorkspace" => {
-                self.handle_reindex_workspace().await?;
-                Ok(Value::Null)
-            }
-
-            "codegraph/getAIContext" => {
-                let params: AIContextParams = serde_json::from_value(params)
-                    .map_err(|e| Error::invalid_params(format!("Invalid params: {e}")))?;
-                let response = self.handle_get_ai_context(params).await?;
-                serde_json::to_value(response).map_err(|_| Error::internal_error())
-            }
+/// Prefix of custom JSON-RPC request methods.
+pub const REQUEST_PREFIX: &str = "codegraph/";
 
-            "codegraph/findRelatedTests" => {
-                let params: RelatedTestsParams = serde_json::from_value(params)
-                    .map_err(|e| Error::invalid_params(format!("Invalid params: {e}")))?;
-                let response = self.handle_find_related_tests(params).await?;
-                serde_json::to_value(response).map_err(|_| Error::internal_error())
-            }
+/// Prefix of `workspace/executeCommand` command names.
+pub const COMMAND_PREFIX: &str = "codegraph.";
 
-            "codegraph/getNodeLocation" => {
-                let params: GetNodeLocationParams = serde_json::from_value(params)
-                    .map_err(|e| Error::invalid_params(format!("Invalid params: {e}")))?;
-                let response = self.handle_get_node_location(params).await?;
-                serde_json::to_value(response).map_err(|_| Error::internal_error())
-            }
+macro_rules! codegraph_methods {
+    ($($name:literal => $handler:ident $(($params:ty))?,)*) => {
+        /// Names of all CodeGraph methods, without prefix.
+        pub const METHOD_NAMES: &[&str] = &[$($name),*];
 
-            "codegraph/getWorkspaceSymbols" => {
-                let params: WorkspaceSymbolsParams = serde_json::from_value(params)
-                    .map_err(|e| Error::invalid_params(format!("Invalid params: {e}")))?;
-                let response = self.handle_get_workspace_symbols(params).await?;
-                serde_json::to_value(response).map_err(|_| Error::internal_error())
-            }
+        /// Register every CodeGraph method as a custom request on the service.
+        pub fn register_custom_methods(
+            builder: LspServiceBuilder<CodeGraphBackend>,
+        ) -> LspServiceBuilder<CodeGraphBackend> {
+            builder
+                $(.custom_method(concat!("codegraph/", $name), CodeGraphBackend::$handler))*
+        }
 
-            "codegraph/analyzeComplexity" => {
-                let params: ComplexityParams = serde_json::from_value(params)
-                    .map_err(|e| Error::invalid_params(format!("Invalid params: {e}")))?;
-                let response = self.handle_analyze_complexity(params).await?;
-                serde_json::to_value(response).map_err(|_| Error::internal_error())
+        /// Custom request handler dispatcher
+        impl CodeGraphBackend {
+            /// Dispatch a `codegraph/<name>` request or `codegraph.<name>`
+            /// command with untyped params.
+            pub async fn handle_custom_request(&self, method: &str, params: Value) -> Result<Value> {
+                match method_name(method) {
+                    $(Some($name) => codegraph_methods!(@call self, $handler, params $(, $params)?),)*
+                    _ => Err(Error::method_not_found()),
+                }
             }
+        }
+    };
+
+    (@call $self:ident, $handler:ident, $value:ident, $params:ty) => {{
+        let params: $params = serde_json::from_value($value)
+            .map_err(|e| Error::invalid_params(format!("Invalid params: {e}")))?;
+        let response = $self.$handler(params).await?;
+        serde_json::to_value(response).map_err(|_| Error::internal_error())
+    }};
+
+    (@call $self:ident, $handler:ident, $value:ident) => {{
+        let _ = $value;
+        let response = $self.$handler().await?;
+        serde_json::to_value(response).map_err(|_| Error::internal_error())
+    }};
+}
 
-            "codegraph/findUnusedCode" => {
-                let params: UnusedCodeParams = serde_json::from_value(params)
-                    .map_err(|e| Error::invalid_params(format!("Invalid params: {e}")))?;
-                let response = self.handle_find_unused_code(params).await?;
-                serde_json::to_value(response).map_err(|_| Error::internal_error())
-            }
+codegraph_methods! {
+    "getDependencyGraph" => handle_get_dependency_graph(DependencyGraphParams),
+    "getCallGraph" => handle_get_call_graph(CallGraphParams),
+    "findPaths" => handle_find_paths(FindPathsParams),
+    "analyzeImpact" => handle_analyze_impact(ImpactAnalysisParams),
+    "getParserMetrics" => handle_get_parser_metrics(ParserMetricsParams),
+    "reindexWorkspace" => handle_reindex_workspace,
+    "getAIContext" => handle_get_ai_context(AIContextParams),
+    "findRelatedTests" => handle_find_related_tests(RelatedTestsParams),
+    "getNodeLocation" => handle_get_node_location(GetNodeLocationParams),
+    "getWorkspaceSymbols" => handle_get_workspace_symbols(WorkspaceSymbolsParams),
+    "analyzeComplexity" => handle_analyze_complexity(ComplexityParams),
+    "getComplexityReport" => handle_get_complexity_report(ComplexityReportParams),
+    "findUnusedCode" => handle_find_unused_code(UnusedCodeParams),
+    "analyzeCoupling" => handle_analyze_coupling(CouplingParams),
+    "analyzeCohesion" => handle_analyze_cohesion(ClassCohesionParams),
+    "findCycles" => handle_find_cycles(FindCyclesParams),
+    "checkArchitecture" => handle_check_architecture(CheckArchitectureParams),
+    "selectTests" => handle_select_tests(SelectTestsParams),
+    "findDuplicates" => handle_find_duplicates(FindDuplicatesParams),
+    "getDependencyMatrix" => handle_get_dependency_matrix(DependencyMatrixParams),
+    "query" => handle_graph_query(GraphQueryParams),
+    "getKeySymbols" => handle_get_key_symbols(KeySymbolsParams),
+}
 
-            "codegraph/analyzeCoupling" => {
-                let params: CouplingParams = serde_json::from_value(params)
-                    .map_err(|e| Error::invalid_params(format!("Invalid params: {e}")))?;
-                let response = self.handle_analyze_coupling(params).await?;
-                serde_json::to_value(response).map_err(|_| Error::internal_error())
-            }
+/// Strip the request or command prefix from a method name.
+pub fn method_name(method: &str) -> Option<&str> {
+    method
+        .strip_prefix(REQUEST_PREFIX)
+        .or_else(|| method.strip_prefix(COMMAND_PREFIX))
+}
 
-            _ => Err(Error::method_not_found()),
-        }
-    }
+/// Command names advertised through `executeCommandProvider`.
+pub fn command_names() -> Vec<String> {
+    METHOD_NAMES
+        .iter()
+        .map(|name| format!("{COMMAND_PREFIX}{name}"))
+        .collect()
+}
 
+impl CodeGraphBackend {
     /// Handle reindex workspace request
-    async fn handle_reindex_workspace(&self) -> Result<()> {
+    pub async fn handle_reindex_workspace(&self) -> Result<()> {
         tracing::info!("Reindexing workspace");
 
         // Clear current graph and indexes
@@ -107,15 +108,63 @@ impl CodeGraphBackend {
             *graph = codegraph::CodeGraph::in_memory().expect("Failed to create in-memory graph");
         }
         self.symbol_index.clear();
+        self.occurrence_index.clear();
         self.file_cache.clear();
+        self.invalidate_workspace_findings().await;
+
+        self.client
+            .log_message(
+                tower_lsp::lsp_types::MessageType::INFO,
+                "Reindexing workspace...",
+            )
+            .await;
+
+        // Index all workspace folders
+        let workspace_folders = self.workspace_folders.read().await.clone();
+        let mut total_indexed = 0;
+        for folder in workspace_folders {
+            tracing::info!("Indexing folder: {:?}", folder);
+            total_indexed += self.index_directory(&folder).await;
+        }
+
+        self.schedule_rank_refresh();
 
         self.client
             .log_message(
                 tower_lsp::lsp_types::MessageType::INFO,
-                "Workspace reindexed",
+                format!("Workspace reindexed: {total_indexed} files"),
             )
             .await;
 
+        self.publish_all_diagnostics().await;
+
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_method_name() {
+        assert_eq!(method_name("codegraph/getCallGraph"), Some("getCallGraph"));
+        assert_eq!(method_name("codegraph.getCallGraph"), Some("getCallGraph"));
+        assert_eq!(method_name("textDocument/hover"), None);
+    }
+
+    #[test]
+    fn test_method_names_unique() {
+        let unique: HashSet<_> = METHOD_NAMES.iter().collect();
+        assert_eq!(unique.len(), METHOD_NAMES.len());
+    }
+
+    #[test]
+    fn test_command_names() {
+        let commands = command_names();
+        assert_eq!(commands.len(), METHOD_NAMES.len());
+        assert!(commands.contains(&"codegraph.analyzeImpact".to_string()));
+        assert!(commands.contains(&"codegraph.reindexWorkspace".to_string()));
+    }
+}
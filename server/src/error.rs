@@ -33,6 +33,9 @@ pub enum LspError {
 
     #[error("Node not found: {0}")]
     NodeNotFound(String),
+
+    #[error("Invalid configuration: {0}")]
+    Config(String),
 }
 
 impl From<LspError> for tower_lsp::jsonrpc::Error {
@@ -270,6 +270,31 @@ impl CodeGraphBackend {
         })
     }
 
+    /// Connected edges with the most important neighbours first, so the
+    /// token budget goes to the symbols that matter most.
+    fn edges_by_importance(
+        &self,
+        graph: &codegraph::CodeGraph,
+        node_id: NodeId,
+        direction: Direction,
+    ) -> Vec<(NodeId, NodeId, EdgeType)> {
+        let ranks = self.symbol_ranks(graph);
+        let neighbour = |(source, target, _): &(NodeId, NodeId, EdgeType)| {
+            if *source == node_id {
+                *target
+            } else {
+                *source
+            }
+        };
+        let mut edges = self.get_connected_edges(graph, node_id, direction);
+        edges.sort_by(|a, b| {
+            ranks
+                .score(neighbour(b))
+                .total_cmp(&ranks.score(neighbour(a)))
+        });
+        edges
+    }
+
     /// Get context optimized for explaining code.
     async fn get_explanation_context(
         &self,
@@ -280,7 +305,7 @@ impl CodeGraphBackend {
         let mut context = Vec::new();
 
         // Priority 1: Direct dependencies (things this symbol uses)
-        let outgoing = self.get_connected_edges(graph, node_id, Direction::Outgoing);
+        let outgoing = self.edges_by_importance(graph, node_id, Direction::Outgoing);
         for (_, target, _) in outgoing.iter().take(5) {
             if !budget.has_budget() {
                 break;
@@ -297,7 +322,7 @@ impl CodeGraphBackend {
         }
 
         // Priority 2: Direct callers (who uses this)
-        let incoming = self.get_connected_edges(graph, node_id, Direction::Incoming);
+        let incoming = self.edges_by_importance(graph, node_id, Direction::Incoming);
         for (source, _, _edge_type) in incoming
             .iter()
             .filter(|(_, _, t)| *t == EdgeType::Calls)
@@ -346,7 +371,7 @@ impl CodeGraphBackend {
         let mut context = Vec::new();
 
         // Priority 1: Tests for this symbol
-        let incoming = self.get_connected_edges(graph, node_id, Direction::Incoming);
+        let incoming = self.edges_by_importance(graph, node_id, Direction::Incoming);
         for (source, _, _edge_type) in incoming
             .iter()
             .filter(|(_, _, t)| *t == EdgeType::Calls)
@@ -418,7 +443,7 @@ impl CodeGraphBackend {
         let mut depth = 0;
 
         while depth < 5 && budget.has_budget() {
-            let incoming = self.get_connected_edges(graph, current, Direction::Incoming);
+            let incoming = self.edges_by_importance(graph, current, Direction::Incoming);
             let caller = incoming
                 .iter()
                 .filter(|(_, _, t)| *t == EdgeType::Calls)
@@ -454,7 +479,7 @@ impl CodeGraphBackend {
         }
 
         // Add data dependencies
-        let outgoing = self.get_connected_edges(graph, node_id, Direction::Outgoing);
+        let outgoing = self.edges_by_importance(graph, node_id, Direction::Outgoing);
         for (_, target, _) in outgoing.iter().take(3) {
             if !budget.has_budget() {
                 break;
@@ -483,7 +508,7 @@ impl CodeGraphBackend {
         let mut context = Vec::new();
 
         // Find existing tests that might be similar
-        let incoming = self.get_connected_edges(graph, node_id, Direction::Incoming);
+        let incoming = self.edges_by_importance(graph, node_id, Direction::Incoming);
         for (source, _, _edge_type) in incoming
             .iter()
             .filter(|(_, _, t)| *t == EdgeType::Calls)
@@ -514,7 +539,7 @@ impl CodeGraphBackend {
         }
 
         // Add dependencies that might need mocking
-        let outgoing = self.get_connected_edges(graph, node_id, Direction::Outgoing);
+        let outgoing = self.edges_by_importance(graph, node_id, Direction::Outgoing);
         for (_, target, _) in outgoing.iter().take(3) {
             if !budget.has_budget() {
                 break;
@@ -580,7 +605,7 @@ impl CodeGraphBackend {
         let mut examples = Vec::new();
 
         // Find nodes that call or reference this symbol
-        let incoming = self.get_connected_edges(graph, node_id, Direction::Incoming);
+        let incoming = self.edges_by_importance(graph, node_id, Direction::Incoming);
 
         // Filter for actual usage (Calls edge type) and exclude tests for main examples
         let usages: Vec<_> = incoming
@@ -753,8 +778,22 @@ impl CodeGraphBackend {
         })
     }
 
+    /// Detect the layer of a file from its path relative to the workspace
+    /// folder containing it, so folders above the project root (e.g.
+    /// `~/services/app`) do not leak into the result.
+    pub(crate) fn detect_file_layer(
+        file: &std::path::Path,
+        workspace_folders: &[std::path::PathBuf],
+    ) -> Option<String> {
+        let relative = workspace_folders
+            .iter()
+            .find_map(|root| file.strip_prefix(root).ok())
+            .unwrap_or(file);
+        Self::detect_layer(&format!("/{}", relative.to_string_lossy()))
+    }
+
     /// Detect architectural layer from file path using common conventions.
-    fn detect_layer(path: &str) -> Option<String> {
+    pub(crate) fn detect_layer(path: &str) -> Option<String> {
         let path_lower = path.to_lowercase();
 
         // Common layer patterns (ordered by specificity)
@@ -0,0 +1,657 @@
+//! Architecture Handler - Declarative layer rules.
+//!
+//! A workspace folder can declare its layers in `.codegraph/architecture.toml`:
+//!
+//! ```toml
+//! [[layers]]
+//! name = "domain"
+//! paths = ["src/domain/**"]
+//! allow = ["shared"]          # optional: the only layers it may depend on
+//!
+//! [[layers]]
+//! name = "infrastructure"
+//! paths = ["src/infra/**", "src/db"]
+//!
+//! [[forbid]]
+//! from = "domain"
+//! to = "infrastructure"
+//! reason = "the domain model must stay persistence-agnostic"
+//! ```
+//!
+//! Files are assigned to the first layer with a matching glob. Folders
+//! without a config fall back to the path conventions of `detect_layer`.
+//! `codegraph/checkArchitecture` reports every import and call edge that
+//! crosses layers in a direction the rules do not allow; the same rules drive
+//! the `layer-violation` diagnostics.
+
+use crate::backend::CodeGraphBackend;
+use crate::error::{LspError, LspResult};
+use crate::handlers::diagnostics::layer_dependency_allowed;
+use crate::import_graph::{self, ImportGraph};
+use codegraph::{Direction, EdgeType, NodeType};
+use glob::{MatchOptions, Pattern};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use tower_lsp::jsonrpc::Result;
+use tower_lsp::lsp_types::{Location, Position, Range, Url};
+
+/// Location of the architecture config, relative to a workspace folder.
+pub const ARCHITECTURE_CONFIG: &str = ".codegraph/architecture.toml";
+
+// ==========================================
+// Configuration
+// ==========================================
+
+/// Contents of `.codegraph/architecture.toml`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ArchitectureConfig {
+    #[serde(default)]
+    pub layers: Vec<LayerConfig>,
+    #[serde(default)]
+    pub forbid: Vec<ForbiddenDependency>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LayerConfig {
+    pub name: String,
+    /// Globs relative to the workspace folder; a plain directory matches
+    /// everything below it.
+    pub paths: Vec<String>,
+    /// Layers this layer may depend on besides itself; unrestricted if absent.
+    pub allow: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ForbiddenDependency {
+    pub from: String,
+    pub to: String,
+    pub reason: Option<String>,
+}
+
+/// Compile path globs relative to a workspace folder; a plain directory
+/// matches everything below it.
+pub(crate) fn path_patterns(paths: &[String]) -> std::result::Result<Vec<Pattern>, String> {
+    let mut patterns = Vec::new();
+    for path in paths {
+        let path = path.trim_start_matches("./").trim_end_matches('/');
+        let mut globs = vec![path.to_string()];
+        if !path.contains(['*', '?', '[']) {
+            globs.push(format!("{path}/**"));
+        }
+        for glob in globs {
+            patterns
+                .push(Pattern::new(&glob).map_err(|e| format!("invalid path glob '{glob}': {e}"))?);
+        }
+    }
+    Ok(patterns)
+}
+
+/// Whether a relative path matches any of the globs; `*` stops at `/`.
+pub(crate) fn matches_path(patterns: &[Pattern], relative: &Path) -> bool {
+    let options = MatchOptions {
+        require_literal_separator: true,
+        ..MatchOptions::new()
+    };
+    patterns
+        .iter()
+        .any(|p| p.matches_path_with(relative, options))
+}
+
+/// Compiled rules of one workspace folder.
+#[derive(Debug)]
+pub struct LayerRules {
+    root: PathBuf,
+    layers: Vec<(LayerConfig, Vec<Pattern>)>,
+    forbid: Vec<ForbiddenDependency>,
+}
+
+impl LayerRules {
+    /// Validate a config and compile its globs.
+    pub fn new(root: PathBuf, config: ArchitectureConfig) -> std::result::Result<Self, String> {
+        let mut names = HashSet::new();
+        for layer in &config.layers {
+            if !names.insert(layer.name.as_str()) {
+                return Err(format!("layer '{}' is declared twice", layer.name));
+            }
+        }
+        let check_layer = |name: &str| {
+            if names.contains(name) {
+                Ok(())
+            } else {
+                Err(format!("unknown layer '{name}'"))
+            }
+        };
+        for layer in &config.layers {
+            for allowed in layer.allow.iter().flatten() {
+                check_layer(allowed)?;
+            }
+        }
+        for rule in &config.forbid {
+            check_layer(&rule.from)?;
+            check_layer(&rule.to)?;
+        }
+
+        let mut layers = Vec::new();
+        for layer in config.layers {
+            let patterns = path_patterns(&layer.paths)?;
+            layers.push((layer, patterns));
+        }
+
+        Ok(Self {
+            root,
+            layers,
+            forbid: config.forbid,
+        })
+    }
+
+    /// Read `.codegraph/architecture.toml` from a workspace folder, if present.
+    pub fn load(root: &Path) -> LspResult<Option<Self>> {
+        let path = root.join(ARCHITECTURE_CONFIG);
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let invalid = |message: String| LspError::Config(format!("{}: {message}", path.display()));
+        let config: ArchitectureConfig =
+            toml::from_str(&text).map_err(|e| invalid(e.message().to_string()))?;
+        Self::new(root.to_path_buf(), config)
+            .map(Some)
+            .map_err(invalid)
+    }
+
+    /// Layer of a file under this folder, if any glob matches it.
+    pub fn layer_of(&self, file: &Path) -> Option<&str> {
+        let relative = file.strip_prefix(&self.root).ok()?;
+        self.layers
+            .iter()
+            .find(|(_, patterns)| matches_path(patterns, relative))
+            .map(|(layer, _)| layer.name.as_str())
+    }
+
+    /// Why `from` must not depend on `to`, or `None` when it may.
+    pub fn violation(&self, from: &str, to: &str) -> Option<String> {
+        if from == to {
+            return None;
+        }
+        if let Some(rule) = self.forbid.iter().find(|r| r.from == from && r.to == to) {
+            return Some(match &rule.reason {
+                Some(reason) => format!("'{from}' must not depend on '{to}': {reason}"),
+                None => format!("'{from}' must not depend on '{to}'"),
+            });
+        }
+        let allow = self
+            .layers
+            .iter()
+            .find(|(layer, _)| layer.name == from)
+            .and_then(|(layer, _)| layer.allow.as_ref())?;
+        if allow.iter().any(|a| a == to) {
+            None
+        } else if allow.is_empty() {
+            Some(format!("'{from}' may not depend on other layers"))
+        } else {
+            Some(format!("'{from}' may only depend on {}", allow.join(", ")))
+        }
+    }
+}
+
+/// Layer assignment and rules for all workspace folders.
+#[derive(Debug, Default)]
+pub struct Architecture {
+    folders: Vec<PathBuf>,
+    rules: Vec<LayerRules>,
+}
+
+impl Architecture {
+    /// Load the config of every workspace folder. Invalid configs are
+    /// returned as errors alongside the folders that loaded.
+    pub fn load(folders: &[PathBuf]) -> (Self, Vec<LspError>) {
+        let mut rules = Vec::new();
+        let mut errors = Vec::new();
+        for folder in folders {
+            match LayerRules::load(folder) {
+                Ok(Some(r)) => rules.push(r),
+                Ok(None) => {}
+                Err(e) => errors.push(e),
+            }
+        }
+        (Self::new(folders.to_vec(), rules), errors)
+    }
+
+    pub fn new(folders: Vec<PathBuf>, rules: Vec<LayerRules>) -> Self {
+        Self { folders, rules }
+    }
+
+    /// Config files in effect.
+    pub fn config_files(&self) -> Vec<PathBuf> {
+        self.rules
+            .iter()
+            .map(|r| r.root.join(ARCHITECTURE_CONFIG))
+            .collect()
+    }
+
+    /// Rules of the configured folder containing `file`.
+    fn rules_for(&self, file: &Path) -> Option<&LayerRules> {
+        self.rules
+            .iter()
+            .filter(|r| file.starts_with(&r.root))
+            .max_by_key(|r| r.root.components().count())
+    }
+
+    /// Layer of a file: from the folder's config if it has one, otherwise
+    /// guessed from path conventions.
+    pub fn layer_of(&self, file: &Path) -> Option<String> {
+        match self.rules_for(file) {
+            Some(rules) => rules.layer_of(file).map(str::to_string),
+            None => CodeGraphBackend::detect_file_layer(file, &self.folders),
+        }
+    }
+
+    /// Why a dependency from `from_file` in layer `from` on layer `to` is not
+    /// allowed, or `None` when it is.
+    pub fn violation(&self, from_file: &Path, from: &str, to: &str) -> Option<String> {
+        match self.rules_for(from_file) {
+            Some(rules) => rules.violation(from, to),
+            None => (!layer_dependency_allowed(from, to))
+                .then(|| format!("'{from}' should not depend on '{to}'")),
+        }
+    }
+}
+
+// ==========================================
+// Request Types
+// ==========================================
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckArchitectureParams {
+    /// Only report violations originating in this file
+    pub uri: Option<String>,
+    /// Also check call edges (default true)
+    pub include_calls: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckArchitectureResponse {
+    /// Whether any workspace folder has an architecture config; without one
+    /// layers are guessed from path conventions
+    pub configured: bool,
+    pub config_files: Vec<String>,
+    pub layers: Vec<LayerSummary>,
+    pub violations: Vec<ArchitectureViolation>,
+    pub summary: ArchitectureSummary,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LayerSummary {
+    pub name: String,
+    pub file_count: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DependencyKind {
+    Import,
+    Call,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchitectureViolation {
+    pub kind: DependencyKind,
+    pub from_layer: String,
+    pub to_layer: String,
+    /// Import statement or call site
+    pub source: Location,
+    /// Imported file or called symbol
+    pub target: Location,
+    /// Imported module or called symbol name
+    pub symbol: String,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchitectureSummary {
+    pub total_violations: usize,
+    pub import_violations: usize,
+    pub call_violations: usize,
+    pub files_checked: usize,
+    /// Files that belong to no layer and are not checked
+    pub unassigned_files: usize,
+}
+
+fn file_location(path: &Path, range: Range) -> Option<Location> {
+    Some(Location {
+        uri: Url::from_file_path(path).ok()?,
+        range,
+    })
+}
+
+fn line_range(line: u32) -> Range {
+    Range {
+        start: Position { line, character: 0 },
+        end: Position {
+            line,
+            character: u32::MAX,
+        },
+    }
+}
+
+impl CodeGraphBackend {
+    /// Load the architecture rules of all workspace folders, logging invalid
+    /// configs.
+    pub(crate) async fn load_architecture(&self) -> (Architecture, Vec<LspError>) {
+        let folders = self.workspace_folders.read().await.clone();
+        let (architecture, errors) = Architecture::load(&folders);
+        for error in &errors {
+            tracing::warn!("Ignoring architecture config: {}", error);
+        }
+        (architecture, errors)
+    }
+
+    /// Report import and call edges that violate the layer rules.
+    pub async fn handle_check_architecture(
+        &self,
+        params: CheckArchitectureParams,
+    ) -> Result<CheckArchitectureResponse> {
+        let focus = match &params.uri {
+            Some(uri) => Some(
+                Url::parse(uri)
+                    .ok()
+                    .and_then(|u| u.to_file_path().ok())
+                    .ok_or_else(|| tower_lsp::jsonrpc::Error::invalid_params("Invalid URI"))?,
+            ),
+            None => None,
+        };
+
+        let (architecture, errors) = self.load_architecture().await;
+        if let Some(error) = errors.into_iter().next() {
+            return Err(error.into());
+        }
+
+        let graph = self.graph.read().await;
+        let imports = ImportGraph::build(&graph);
+        let layers: Vec<Option<String>> = imports
+            .files
+            .iter()
+            .map(|f| architecture.layer_of(f))
+            .collect();
+
+        let mut layer_counts: BTreeMap<String, usize> = BTreeMap::new();
+        for layer in layers.iter().flatten() {
+            *layer_counts.entry(layer.clone()).or_default() += 1;
+        }
+
+        let mut violations = Vec::new();
+
+        for edge in &imports.edges {
+            let from_path = &imports.files[edge.from];
+            if focus.as_ref().is_some_and(|f| f != from_path) {
+                continue;
+            }
+            let (Some(from_layer), Some(to_layer)) = (&layers[edge.from], &layers[edge.to]) else {
+                continue;
+            };
+            let Some(reason) = architecture.violation(from_path, from_layer, to_layer) else {
+                continue;
+            };
+            let line = self
+                .document_text(from_path)
+                .and_then(|text| import_graph::find_import_line(&text, &edge.module))
+                .unwrap_or(0);
+            let (Some(source), Some(target)) = (
+                file_location(from_path, line_range(line)),
+                file_location(&imports.files[edge.to], Range::default()),
+            ) else {
+                continue;
+            };
+            violations.push(ArchitectureViolation {
+                kind: DependencyKind::Import,
+                from_layer: from_layer.clone(),
+                to_layer: to_layer.clone(),
+                source,
+                target,
+                symbol: edge.module.clone(),
+                message: format!("Import crosses layers: {reason}"),
+            });
+        }
+
+        if params.include_calls.unwrap_or(true) {
+            let functions = graph
+                .query()
+                .node_type(NodeType::Function)
+                .execute()
+                .unwrap_or_default();
+            for caller in functions {
+                let Some(from_path) = self.symbol_index.find_file_for_node(caller) else {
+                    continue;
+                };
+                if focus.as_ref().is_some_and(|f| *f != from_path) {
+                    continue;
+                }
+                let Some(from_layer) = architecture.layer_of(&from_path) else {
+                    continue;
+                };
+                for callee in graph
+                    .get_neighbors(caller, Direction::Outgoing)
+                    .unwrap_or_default()
+                {
+                    let is_call = graph
+                        .get_edges_between(caller, callee)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .filter_map(|e| graph.get_edge(e).ok())
+                        .any(|e| e.edge_type == EdgeType::Calls);
+                    if !is_call {
+                        continue;
+                    }
+                    let Some(to_path) = self.symbol_index.find_file_for_node(callee) else {
+                        continue;
+                    };
+                    let Some(to_layer) = architecture.layer_of(&to_path) else {
+                        continue;
+                    };
+                    let Some(reason) = architecture.violation(&from_path, &from_layer, &to_layer)
+                    else {
+                        continue;
+                    };
+                    let Ok(target) = self.node_to_location(&graph, callee) else {
+                        continue;
+                    };
+                    let source = match self
+                        .find_call_site_ranges(&graph, caller, callee)
+                        .into_iter()
+                        .next()
+                    {
+                        Some(range) => file_location(&from_path, range),
+                        None => self.node_to_location(&graph, caller).ok(),
+                    };
+                    let Some(source) = source else {
+                        continue;
+                    };
+                    let symbol = graph
+                        .get_node(callee)
+                        .ok()
+                        .and_then(|n| n.properties.get_string("name").map(str::to_string))
+                        .unwrap_or_default();
+                    violations.push(ArchitectureViolation {
+                        kind: DependencyKind::Call,
+                        from_layer: from_layer.clone(),
+                        to_layer,
+                        source,
+                        target,
+                        message: format!("Call to '{symbol}' crosses layers: {reason}"),
+                        symbol,
+                    });
+                }
+            }
+        }
+        drop(graph);
+
+        violations.sort_by(|a, b| {
+            (
+                a.source.uri.as_str(),
+                a.source.range.start.line,
+                a.kind as u8,
+            )
+                .cmp(&(
+                    b.source.uri.as_str(),
+                    b.source.range.start.line,
+                    b.kind as u8,
+                ))
+        });
+
+        let import_violations = violations
+            .iter()
+            .filter(|v| v.kind == DependencyKind::Import)
+            .count();
+        let config_files = architecture
+            .config_files()
+            .iter()
+            .map(|p| p.display().to_string())
+            .collect::<Vec<_>>();
+
+        Ok(CheckArchitectureResponse {
+            configured: !config_files.is_empty(),
+            config_files,
+            layers: layer_counts
+                .into_iter()
+                .map(|(name, file_count)| LayerSummary { name, file_count })
+                .collect(),
+            summary: ArchitectureSummary {
+                total_violations: violations.len(),
+                import_violations,
+                call_violations: violations.len() - import_violations,
+                files_checked: imports.files.len(),
+                unassigned_files: layers.iter().filter(|l| l.is_none()).count(),
+            },
+            violations,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+[[layers]]
+name = "domain"
+paths = ["src/domain/**"]
+allow = ["shared"]
+
+[[layers]]
+name = "infrastructure"
+paths = ["src/infra"]
+
+[[layers]]
+name = "shared"
+paths = ["src/*.rs"]
+
+[[layers]]
+name = "api"
+paths = ["src/api/**"]
+
+[[forbid]]
+from = "api"
+to = "infrastructure"
+reason = "go through the domain"
+"#;
+
+    fn rules() -> LayerRules {
+        let config: ArchitectureConfig = toml::from_str(CONFIG).unwrap();
+        LayerRules::new(PathBuf::from("/w"), config).unwrap()
+    }
+
+    #[test]
+    fn test_layer_globs() {
+        let rules = rules();
+        assert_eq!(
+            rules.layer_of(Path::new("/w/src/domain/user/model.rs")),
+            Some("domain")
+        );
+        assert_eq!(
+            rules.layer_of(Path::new("/w/src/infra/db.rs")),
+            Some("infrastructure")
+        );
+        assert_eq!(rules.layer_of(Path::new("/w/src/util.rs")), Some("shared"));
+        // `*` does not cross directories.
+        assert_eq!(rules.layer_of(Path::new("/w/src/other/util.rs")), None);
+        assert_eq!(rules.layer_of(Path::new("/elsewhere/src/util.rs")), None);
+    }
+
+    #[test]
+    fn test_allow_and_forbid_rules() {
+        let rules = rules();
+        assert!(rules.violation("domain", "domain").is_none());
+        assert!(rules.violation("domain", "shared").is_none());
+        assert_eq!(
+            rules.violation("domain", "infrastructure").as_deref(),
+            Some("'domain' may only depend on shared")
+        );
+        assert_eq!(
+            rules.violation("api", "infrastructure").as_deref(),
+            Some("'api' must not depend on 'infrastructure': go through the domain")
+        );
+        // Layers without `allow` are unrestricted apart from `forbid`.
+        assert!(rules.violation("api", "domain").is_none());
+        assert!(rules.violation("infrastructure", "domain").is_none());
+    }
+
+    #[test]
+    fn test_invalid_configs() {
+        let unknown: ArchitectureConfig = toml::from_str(
+            "[[layers]]\nname = \"a\"\npaths = [\"a\"]\n[[forbid]]\nfrom = \"a\"\nto = \"b\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            LayerRules::new(PathBuf::from("/w"), unknown).unwrap_err(),
+            "unknown layer 'b'"
+        );
+
+        let duplicate: ArchitectureConfig = toml::from_str(
+            "[[layers]]\nname = \"a\"\npaths = [\"a\"]\n[[layers]]\nname = \"a\"\npaths = [\"b\"]\n",
+        )
+        .unwrap();
+        assert!(LayerRules::new(PathBuf::from("/w"), duplicate).is_err());
+
+        assert!(toml::from_str::<ArchitectureConfig>("[[layer]]\nname = \"a\"\n").is_err());
+    }
+
+    #[test]
+    fn test_unconfigured_folders_use_path_conventions() {
+        let architecture = Architecture::new(
+            vec![PathBuf::from("/w"), PathBuf::from("/legacy")],
+            vec![rules()],
+        );
+        assert_eq!(
+            architecture
+                .layer_of(Path::new("/w/src/controllers/api.py"))
+                .as_deref(),
+            None
+        );
+        assert_eq!(
+            architecture
+                .layer_of(Path::new("/legacy/src/controllers/api.py"))
+                .as_deref(),
+            Some("controller")
+        );
+        assert!(architecture
+            .violation(
+                Path::new("/legacy/src/domain/user.py"),
+                "domain",
+                "controller"
+            )
+            .is_some());
+        assert!(architecture
+            .violation(Path::new("/w/src/api/a.rs"), "api", "domain")
+            .is_none());
+    }
+}
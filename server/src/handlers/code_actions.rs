@@ -0,0 +1,679 @@
+//! Code Actions Handler - Quick fixes driven by the graph analyses.
+//!
+//! Offers "Remove unused function" for functions the unused-code analysis
+//! marks as safe to remove, "Generate test stub" in the language's
+//! conventional test location, and "Show impact" which runs the extension's
+//! `codegraph.showImpact` command for the symbol under the cursor.
+
+use crate::backend::CodeGraphBackend;
+use crate::handlers::diagnostics::codes;
+use crate::lexer::{self, Language};
+use codegraph::NodeType;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tower_lsp::jsonrpc::Result;
+use tower_lsp::lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams, CodeActionResponse, Command,
+    CreateFile, CreateFileOptions, DocumentChangeOperation, DocumentChanges, NumberOrString, OneOf,
+    OptionalVersionedTextDocumentIdentifier, Position, Range, ResourceOp, TextDocumentEdit,
+    TextEdit, Url, WorkspaceEdit,
+};
+
+/// Extension command that runs `codegraph/analyzeImpact` and shows the result.
+pub const SHOW_IMPACT_COMMAND: &str = "codegraph.showImpact";
+
+// ==========================================
+// Test Stub Generation
+// ==========================================
+
+/// A test stub to add for a function.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestStub {
+    /// File that receives the test.
+    pub path: PathBuf,
+    /// Text inserted at the start of the test file (imports), if any.
+    pub header: Option<String>,
+    /// Text appended at the end of the test file, or inserted into the
+    /// existing test module when `insert_line` is set.
+    pub body: String,
+    /// 0-indexed line to insert `body` at instead of appending.
+    pub insert_line: Option<u32>,
+    /// Name of the generated test, used to skip functions that already have one.
+    pub test_name: String,
+}
+
+fn to_snake_case(name: &str) -> String {
+    let mut out = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() {
+            if i > 0 && !out.ends_with('_') {
+                out.push('_');
+            }
+            out.extend(c.to_lowercase());
+        } else if c.is_alphanumeric() {
+            out.push(c);
+        } else if !out.ends_with('_') {
+            out.push('_');
+        }
+    }
+    out.trim_matches('_').to_string()
+}
+
+fn to_pascal_case(name: &str) -> String {
+    name.split(|c: char| !c.is_alphanumeric())
+        .filter(|s| !s.is_empty())
+        .map(|s| {
+            let mut chars = s.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+/// Work out where and what test stub to generate for `function` defined in `path`.
+///
+/// Follows each language's conventions: `tests/test_<module>.py` for Python,
+/// the in-file `tests` module for Rust, `<file>_test.go` for Go and
+/// `<file>.test.<ext>` next to TypeScript/JavaScript sources. `existing` is
+/// the current content of the test file when it already exists.
+pub fn plan_test_stub(
+    path: &Path,
+    source: &str,
+    function: &str,
+    workspace_root: &Path,
+    existing: impl Fn(&Path) -> Option<String>,
+) -> Option<TestStub> {
+    let ext = path.extension()?.to_str()?;
+    let stem = path.file_stem()?.to_str()?;
+    let dir = path.parent()?;
+    // Methods are recorded as `Class.method`; the importable name is the class.
+    let top_level = function.split('.').next().unwrap_or(function);
+
+    let (stub_path, test_name, header_new, header_existing, body) = match ext {
+        "py" | "pyw" => {
+            let relative = path.strip_prefix(workspace_root).unwrap_or(path);
+            let module: Vec<String> = relative
+                .with_extension("")
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().to_string())
+                .collect();
+            let module = module
+                .strip_prefix(&["src".to_string()])
+                .unwrap_or(&module)
+                .join(".");
+            let test_name = format!("test_{}", to_snake_case(function));
+            let import = format!("from {module} import {top_level}\n");
+            let body = format!(
+                "\n\ndef {test_name}():\n    # TODO: exercise {function}\n    raise NotImplementedError\n"
+            );
+            (
+                workspace_root.join("tests").join(format!("test_{stem}.py")),
+                test_name,
+                import.clone(),
+                Some(import),
+                body,
+            )
+        }
+        "rs" => {
+            let test_name = format!("test_{}", to_snake_case(function));
+            // Prefer the existing `mod tests` block: insert before its closing brace.
+            let tokens = lexer::tokenize(source, Language::Rust);
+            let open = tokens
+                .windows(3)
+                .position(|w| w[0].is("mod") && w[1].is("tests") && w[2].is("{"))
+                .map(|i| i + 2);
+            if let Some(open) = open {
+                let close = lexer::matching_close(&tokens, open)?;
+                let body = format!(
+                    "\n    #[test]\n    fn {test_name}() {{\n        todo!(\"exercise {function}\")\n    }}\n"
+                );
+                return Some(TestStub {
+                    path: path.to_path_buf(),
+                    header: None,
+                    body,
+                    insert_line: Some(tokens[close].start.line),
+                    test_name,
+                });
+            }
+            let body = format!(
+                "\n#[cfg(test)]\nmod tests {{\n    use super::*;\n\n    #[test]\n    fn {test_name}() {{\n        todo!(\"exercise {function}\")\n    }}\n}}\n"
+            );
+            return Some(TestStub {
+                path: path.to_path_buf(),
+                header: None,
+                body,
+                insert_line: None,
+                test_name,
+            });
+        }
+        "go" => {
+            let package = source
+                .lines()
+                .find_map(|l| l.trim().strip_prefix("package "))
+                .map(|p| p.trim().to_string())?;
+            let test_name = format!("Test{}", to_pascal_case(function));
+            let body = format!(
+                "\nfunc {test_name}(t *testing.T) {{\n\tt.Skip(\"TODO: exercise {function}\")\n}}\n"
+            );
+            (
+                dir.join(format!("{stem}_test.go")),
+                test_name,
+                format!("package {package}\n\nimport \"testing\"\n"),
+                None,
+                body,
+            )
+        }
+        "ts" | "tsx" | "js" | "jsx" => {
+            let test_name = function.to_string();
+            let import = format!("import {{ {top_level} }} from './{stem}';\n");
+            let body = format!(
+                "\ndescribe('{function}', () => {{\n    it('should work', () => {{\n        // TODO: exercise {function}\n    }});\n}});\n"
+            );
+            (
+                dir.join(format!("{stem}.test.{ext}")),
+                test_name,
+                import.clone(),
+                Some(import),
+                body,
+            )
+        }
+        _ => return None,
+    };
+
+    let header = match existing(&stub_path) {
+        // Existing file: only add the import when it is missing.
+        Some(content) => header_existing.filter(|h| !content.contains(h.trim_end())),
+        None => Some(header_new),
+    };
+
+    Some(TestStub {
+        path: stub_path,
+        header,
+        body,
+        insert_line: None,
+        test_name,
+    })
+}
+
+/// Whether a line directly above a definition belongs to it: a decorator,
+/// attribute or comment in the language's syntax.
+fn is_leading_line(line: &str, language: Language) -> bool {
+    let line = line.trim_start();
+    let c_comment = || line.starts_with("//") || line.starts_with("/*") || line.starts_with('*');
+    match language {
+        Language::Python => {
+            line.starts_with('@') || (line.starts_with('#') && !line.starts_with("#!"))
+        }
+        Language::Rust => {
+            line.starts_with("#[") || (line.starts_with("//") && !line.starts_with("//!"))
+        }
+        Language::Go => line.starts_with("//"),
+        Language::TypeScript => line.starts_with('@') || c_comment(),
+        Language::C => c_comment(),
+    }
+}
+
+/// Range covering a function definition plus its leading decorators,
+/// attributes and comments, as whole lines.
+pub fn removal_range(text: &str, language: Language, start_line: u32, end_line: u32) -> Range {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut start = start_line as usize;
+    while start > 0
+        && lines
+            .get(start - 1)
+            .is_some_and(|l| is_leading_line(l, language))
+    {
+        start -= 1;
+    }
+
+    // Swallow one trailing blank line so removal does not leave a double gap.
+    let mut end = end_line as usize + 1;
+    let blank_before = start == 0 || lines.get(start - 1).is_some_and(|l| l.trim().is_empty());
+    if blank_before && lines.get(end).is_some_and(|l| l.trim().is_empty()) {
+        end += 1;
+    }
+
+    Range {
+        start: Position {
+            line: start as u32,
+            character: 0,
+        },
+        end: Position {
+            line: end as u32,
+            character: 0,
+        },
+    }
+}
+
+fn end_of_text(text: &str) -> Position {
+    Position {
+        line: text.lines().count() as u32,
+        character: 0,
+    }
+}
+
+// ==========================================
+// Code Action Handler
+// ==========================================
+
+impl CodeGraphBackend {
+    /// Handle textDocument/codeAction.
+    pub async fn handle_code_action(
+        &self,
+        params: CodeActionParams,
+    ) -> Result<Option<CodeActionResponse>> {
+        let uri = params.text_document.uri.clone();
+        let path = uri
+            .to_file_path()
+            .map_err(|_| tower_lsp::jsonrpc::Error::invalid_params("Invalid file path"))?;
+
+        let (name, node_type, location, is_test) = {
+            let graph = self.graph.read().await;
+            let Some(node_id) = self
+                .find_node_at_position(&graph, &path, params.range.start)
+                .ok()
+                .flatten()
+            else {
+                return Ok(None);
+            };
+            let Ok(node) = graph.get_node(node_id) else {
+                return Ok(None);
+            };
+            let Ok(location) = self.node_to_location(&graph, node_id) else {
+                return Ok(None);
+            };
+            let name = node.properties.get_string("name").unwrap_or("").to_string();
+            let is_test = Self::is_test_node(node)
+                || self.is_test_symbol(&graph, node_id, &mut HashMap::new());
+            (name, node.node_type, location, is_test)
+        };
+        if name.is_empty() {
+            return Ok(None);
+        }
+
+        let text = self.document_text(&path).unwrap_or_default();
+
+        let mut actions = Vec::new();
+
+        if node_type == NodeType::Function {
+            if let Some(action) = self
+                .remove_unused_action(&uri, &name, location.range, &text, &params)
+                .await
+            {
+                actions.push(action);
+            }
+            if !is_test {
+                if let Some(action) = self.generate_test_action(&path, &name, &text).await {
+                    actions.push(action);
+                }
+            }
+        }
+
+        actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+            title: format!("Show impact of changing '{name}'"),
+            kind: Some(CodeActionKind::REFACTOR),
+            command: Some(Command {
+                title: "Analyze impact".to_string(),
+                // Not a `codegraph.<method>` name: those are executed by the
+                // server, and the extension must show the result.
+                command: SHOW_IMPACT_COMMAND.to_string(),
+                arguments: Some(vec![serde_json::json!({
+                    "uri": uri.to_string(),
+                    "position": location.range.start,
+                    "analysisType": "modify",
+                })]),
+            }),
+            ..Default::default()
+        }));
+
+        if let Some(only) = &params.context.only {
+            actions.retain(|action| match action {
+                CodeActionOrCommand::CodeAction(a) => {
+                    let kind = a.kind.as_ref().map(|k| k.as_str()).unwrap_or("");
+                    only.iter().any(|o| kind.starts_with(o.as_str()))
+                }
+                CodeActionOrCommand::Command(_) => false,
+            });
+        }
+
+        Ok(if actions.is_empty() {
+            None
+        } else {
+            Some(actions)
+        })
+    }
+
+    async fn remove_unused_action(
+        &self,
+        uri: &Url,
+        name: &str,
+        range: Range,
+        text: &str,
+        params: &CodeActionParams,
+    ) -> Option<CodeActionOrCommand> {
+        // Code actions are requested on every cursor move, so reuse the
+        // liveness sweep the diagnostics keep for the current graph.
+        let path = uri.to_file_path().ok()?;
+        let language = Language::from_path(&path)?;
+        let liveness = self.unused_code_liveness().await;
+        let unused = {
+            let graph = self.graph.read().await;
+            self.unreachable_items(&graph, &liveness, Some(&path), false, 0.0)
+        };
+        unused.iter().find(|item| {
+            item.item_type == "function"
+                && item.safe_to_remove
+                && item.location.range.start.line == range.start.line
+        })?;
+
+        // Only edit when the graph's range still matches the document.
+        let short_name = name.rsplit('.').next().unwrap_or(name);
+        let definition_line = text.lines().nth(range.start.line as usize)?;
+        if !definition_line.contains(short_name) || range.end.line < range.start.line {
+            return None;
+        }
+
+        let diagnostics: Vec<_> = params
+            .context
+            .diagnostics
+            .iter()
+            .filter(|d| {
+                d.code == Some(NumberOrString::String(codes::UNUSED_FUNCTION.to_string()))
+                    && d.range.start.line == range.start.line
+            })
+            .cloned()
+            .collect();
+
+        let edit = TextEdit {
+            range: removal_range(text, language, range.start.line, range.end.line),
+            new_text: String::new(),
+        };
+
+        Some(CodeActionOrCommand::CodeAction(CodeAction {
+            title: format!("Remove unused function '{name}'"),
+            kind: Some(CodeActionKind::QUICKFIX),
+            diagnostics: (!diagnostics.is_empty()).then_some(diagnostics),
+            edit: Some(WorkspaceEdit {
+                changes: Some(HashMap::from([(uri.clone(), vec![edit])])),
+                ..Default::default()
+            }),
+            is_preferred: Some(true),
+            ..Default::default()
+        }))
+    }
+
+    async fn generate_test_action(
+        &self,
+        path: &Path,
+        name: &str,
+        text: &str,
+    ) -> Option<CodeActionOrCommand> {
+        let workspace_root = self
+            .workspace_folders
+            .read()
+            .await
+            .iter()
+            .find(|root| path.starts_with(root))
+            .cloned()
+            .or_else(|| path.parent().map(Path::to_path_buf))?;
+
+        let read_existing = |p: &Path| self.document_text(p);
+
+        let stub = plan_test_stub(path, text, name, &workspace_root, read_existing)?;
+        let existing = if stub.path == path {
+            Some(text.to_string())
+        } else {
+            read_existing(&stub.path)
+        };
+        if existing
+            .as_deref()
+            .is_some_and(|content| content.contains(&stub.test_name))
+        {
+            return None;
+        }
+
+        let stub_uri = Url::from_file_path(&stub.path).ok()?;
+        let mut edits = Vec::new();
+        if let Some(header) = &stub.header {
+            edits.push(OneOf::Left(TextEdit {
+                range: Range::default(),
+                new_text: header.clone(),
+            }));
+        }
+        let insert_at = match stub.insert_line {
+            Some(line) => Position { line, character: 0 },
+            None => end_of_text(existing.as_deref().unwrap_or("")),
+        };
+        let needs_newline = stub.insert_line.is_none()
+            && existing
+                .as_deref()
+                .is_some_and(|c| !c.is_empty() && !c.ends_with('\n'));
+        edits.push(OneOf::Left(TextEdit {
+            range: Range {
+                start: insert_at,
+                end: insert_at,
+            },
+            new_text: if needs_newline {
+                format!("\n{}", stub.body)
+            } else {
+                stub.body.clone()
+            },
+        }));
+
+        let mut operations = Vec::new();
+        if existing.is_none() {
+            operations.push(DocumentChangeOperation::Op(ResourceOp::Create(
+                CreateFile {
+                    uri: stub_uri.clone(),
+                    options: Some(CreateFileOptions {
+                        overwrite: Some(false),
+                        ignore_if_exists: Some(true),
+                    }),
+                    annotation_id: None,
+                },
+            )));
+        }
+        operations.push(DocumentChangeOperation::Edit(TextDocumentEdit {
+            text_document: OptionalVersionedTextDocumentIdentifier {
+                uri: stub_uri,
+                version: None,
+            },
+            edits,
+        }));
+
+        let file_name = stub
+            .path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        Some(CodeActionOrCommand::CodeAction(CodeAction {
+            title: format!("Generate test stub for '{name}' in {file_name}"),
+            kind: Some(CodeActionKind::REFACTOR),
+            edit: Some(WorkspaceEdit {
+                document_changes: Some(DocumentChanges::Operations(operations)),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn no_file(_: &Path) -> Option<String> {
+        None
+    }
+
+    #[test]
+    fn test_show_impact_command_is_client_side() {
+        // The language client registers every advertised server command, so
+        // the extension could not register one with the same name.
+        assert!(!crate::custom_requests::command_names().contains(&SHOW_IMPACT_COMMAND.to_string()));
+    }
+
+    #[test]
+    fn test_case_conversion() {
+        assert_eq!(to_snake_case("parseConfig"), "parse_config");
+        assert_eq!(to_snake_case("Foo.bar"), "foo_bar");
+        assert_eq!(to_snake_case("already_snake"), "already_snake");
+        assert_eq!(to_pascal_case("parse_config"), "ParseConfig");
+        assert_eq!(to_pascal_case("Server.handle"), "ServerHandle");
+    }
+
+    #[test]
+    fn test_python_stub_in_tests_directory() {
+        let stub = plan_test_stub(
+            Path::new("/w/src/pkg/util.py"),
+            "def helper():\n    pass\n",
+            "helper",
+            Path::new("/w"),
+            no_file,
+        )
+        .unwrap();
+        assert_eq!(stub.path, PathBuf::from("/w/tests/test_util.py"));
+        assert_eq!(stub.test_name, "test_helper");
+        assert_eq!(
+            stub.header.as_deref(),
+            Some("from pkg.util import helper\n")
+        );
+        assert!(stub.body.contains("def test_helper():"));
+    }
+
+    #[test]
+    fn test_python_stub_skips_existing_import() {
+        let stub = plan_test_stub(
+            Path::new("/w/pkg/util.py"),
+            "",
+            "Parser.parse",
+            Path::new("/w"),
+            |_| Some("from pkg.util import Parser\n".to_string()),
+        )
+        .unwrap();
+        assert_eq!(stub.header, None);
+        assert_eq!(stub.test_name, "test_parser_parse");
+    }
+
+    #[test]
+    fn test_rust_stub_inserts_into_existing_tests_module() {
+        let source = "fn helper() {}\n\n#[cfg(test)]\nmod tests {\n    use super::*;\n}\n";
+        let stub = plan_test_stub(
+            Path::new("/w/src/lib.rs"),
+            source,
+            "helper",
+            Path::new("/w"),
+            no_file,
+        )
+        .unwrap();
+        assert_eq!(stub.path, PathBuf::from("/w/src/lib.rs"));
+        assert_eq!(stub.insert_line, Some(5));
+        assert!(stub.body.contains("fn test_helper()"));
+
+        let source = "fn helper() {}\n\n#[cfg(test)]\nmod tests {\n    #[test]\n    fn other() {\n    }\n}\n\nfn after() {\n}\n";
+        let stub = plan_test_stub(
+            Path::new("/w/src/lib.rs"),
+            source,
+            "helper",
+            Path::new("/w"),
+            no_file,
+        )
+        .unwrap();
+        assert_eq!(stub.insert_line, Some(7));
+    }
+
+    #[test]
+    fn test_rust_stub_creates_tests_module() {
+        let stub = plan_test_stub(
+            Path::new("/w/src/lib.rs"),
+            "fn helper() {}\n",
+            "helper",
+            Path::new("/w"),
+            no_file,
+        )
+        .unwrap();
+        assert_eq!(stub.insert_line, None);
+        assert!(stub.body.contains("#[cfg(test)]\nmod tests {"));
+    }
+
+    #[test]
+    fn test_go_stub_uses_package_clause() {
+        let stub = plan_test_stub(
+            Path::new("/w/pkg/server.go"),
+            "package server\n\nfunc handle() {}\n",
+            "handle",
+            Path::new("/w"),
+            no_file,
+        )
+        .unwrap();
+        assert_eq!(stub.path, PathBuf::from("/w/pkg/server_test.go"));
+        assert_eq!(stub.test_name, "TestHandle");
+        assert!(stub.header.unwrap().starts_with("package server\n"));
+    }
+
+    #[test]
+    fn test_typescript_stub_next_to_source() {
+        let stub = plan_test_stub(
+            Path::new("/w/src/format.ts"),
+            "",
+            "formatDate",
+            Path::new("/w"),
+            no_file,
+        )
+        .unwrap();
+        assert_eq!(stub.path, PathBuf::from("/w/src/format.test.ts"));
+        assert_eq!(
+            stub.header.as_deref(),
+            Some("import { formatDate } from './format';\n")
+        );
+    }
+
+    #[test]
+    fn test_unsupported_language_has_no_stub() {
+        assert!(
+            plan_test_stub(Path::new("/w/main.c"), "", "main", Path::new("/w"), no_file).is_none()
+        );
+    }
+
+    #[test]
+    fn test_removal_range_includes_decorators() {
+        let text = "import x\n\n@cache\ndef unused():\n    pass\n\ndef used():\n    pass\n";
+        let range = removal_range(text, Language::Python, 3, 4);
+        assert_eq!(range.start, Position::new(2, 0));
+        // Trailing blank line is swallowed because the line above is blank too.
+        assert_eq!(range.end, Position::new(6, 0));
+    }
+
+    #[test]
+    fn test_removal_range_keeps_separator_when_needed() {
+        let text = "fn a() {}\nfn unused() {\n}\n\nfn b() {}\n";
+        let range = removal_range(text, Language::Rust, 1, 2);
+        assert_eq!(range.start, Position::new(1, 0));
+        assert_eq!(range.end, Position::new(3, 0));
+    }
+
+    #[test]
+    fn test_removal_range_includes_doc_comments() {
+        let go =
+            "package p\n\n// unused does nothing.\n// It is never called.\nfunc unused() {\n}\n";
+        assert_eq!(
+            removal_range(go, Language::Go, 4, 5).start,
+            Position::new(2, 0)
+        );
+
+        let ts = "const a = 1;\n/**\n * Unused.\n * @deprecated\n */\nfunction unused() {\n}\n";
+        assert_eq!(
+            removal_range(ts, Language::TypeScript, 5, 6).start,
+            Position::new(1, 0)
+        );
+
+        let rs = "//! Module docs\n/// Unused.\nfn unused() {}\n";
+        assert_eq!(
+            removal_range(rs, Language::Rust, 2, 2).start,
+            Position::new(1, 0)
+        );
+    }
+}
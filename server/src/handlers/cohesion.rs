@@ -0,0 +1,549 @@
+//! Class Cohesion Handler - LCOM4 and god-class detection.
+//!
+//! LCOM4 (lack of cohesion of methods, Hitz & Montazeri) is the number of
+//! connected components in the graph whose nodes are a class's methods and
+//! whose edges join methods that touch the same field or call each other.
+//! A cohesive class has LCOM4 = 1; each extra component is a group of
+//! methods that could live in a class of its own. Field and method accesses
+//! are read from the method bodies through the receiver (`self`, `this`,
+//! `cls` or the Go receiver name), calls also from the graph's call edges.
+//! Constructors are left out because they touch every field.
+
+use crate::algorithms::weakly_connected_components;
+use crate::backend::CodeGraphBackend;
+use crate::index::short_name;
+use crate::lexer::{tokenize, Language, TokenKind};
+use codegraph::{CodeGraph, Direction, EdgeType, NodeId, NodeType};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::PathBuf;
+use tower_lsp::jsonrpc::Result;
+use tower_lsp::lsp_types::{Range, Url};
+
+const DEFAULT_GOD_CLASS_METHODS: usize = 20;
+const DEFAULT_GOD_CLASS_FAN_OUT: usize = 10;
+
+/// Methods that initialize every field and would join all components.
+const CONSTRUCTORS: &[&str] = &["__init__", "__new__", "constructor"];
+
+/// Edges from a method that make its class depend on the target.
+const FAN_OUT_EDGES: &[EdgeType] = &[
+    EdgeType::Calls,
+    EdgeType::Invokes,
+    EdgeType::Instantiates,
+    EdgeType::Uses,
+    EdgeType::References,
+];
+
+// ==========================================
+// Cohesion Types
+// ==========================================
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClassCohesionParams {
+    /// Only analyze classes in this file (default: whole workspace)
+    pub uri: Option<String>,
+    /// Skip classes with fewer methods (default 2)
+    pub min_methods: Option<usize>,
+    /// Method count from which a class can be a god class (default 20)
+    pub god_class_methods: Option<usize>,
+    /// Fan-out from which a class can be a god class (default 10)
+    pub god_class_fan_out: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClassCohesionResponse {
+    /// God classes first, then by LCOM4, highest first
+    pub classes: Vec<ClassCohesion>,
+    pub summary: ClassCohesionSummary,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClassCohesion {
+    pub name: String,
+    pub uri: String,
+    pub range: Range,
+    pub methods: usize,
+    /// Fields accessed through the receiver by any method
+    pub fields: usize,
+    /// Connected components of the method graph, 1 = cohesive
+    pub lcom4: usize,
+    /// Distinct classes and functions outside the class that its methods use
+    pub fan_out: usize,
+    /// Many methods, high fan-out and LCOM4 > 1
+    pub is_god_class: bool,
+    /// The connected components, largest first
+    pub components: Vec<MethodGroup>,
+    pub suggestions: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MethodGroup {
+    pub methods: Vec<String>,
+    pub fields: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClassCohesionSummary {
+    pub classes_analyzed: usize,
+    /// Classes with LCOM4 = 1
+    pub cohesive_classes: usize,
+    pub average_lcom4: f64,
+    pub god_classes: Vec<String>,
+}
+
+/// Thresholds that together make a god class.
+#[derive(Debug, Clone, Copy)]
+pub struct GodClassThresholds {
+    pub methods: usize,
+    pub fan_out: usize,
+}
+
+impl Default for GodClassThresholds {
+    fn default() -> Self {
+        Self {
+            methods: DEFAULT_GOD_CLASS_METHODS,
+            fan_out: DEFAULT_GOD_CLASS_FAN_OUT,
+        }
+    }
+}
+
+// ==========================================
+// LCOM4
+// ==========================================
+
+/// What a method body touches through its receiver.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct MethodUsage {
+    pub fields: BTreeSet<String>,
+    pub calls: BTreeSet<String>,
+}
+
+/// Receiver names of a method, read from its definition.
+fn receivers(body: &str, language: Language) -> Vec<String> {
+    match language {
+        Language::Python => vec!["self".to_string(), "cls".to_string()],
+        Language::Rust => vec!["self".to_string(), "Self".to_string()],
+        Language::TypeScript => vec!["this".to_string()],
+        // func (r *Server) Name(...)
+        Language::Go => {
+            let tokens = tokenize(body, Language::Go);
+            match tokens.as_slice() {
+                [func, open, name, ..]
+                    if func.is("func") && open.is("(") && name.kind == TokenKind::Identifier =>
+                {
+                    vec![name.text.clone()]
+                }
+                _ => Vec::new(),
+            }
+        }
+        Language::C => Vec::new(),
+    }
+}
+
+/// Fields and sibling methods a method body accesses through its receiver.
+/// A member is a call when its name is one of `methods`, a field otherwise.
+pub fn method_usage(body: &str, language: Language, methods: &HashSet<&str>) -> MethodUsage {
+    let receivers = receivers(body, language);
+    let tokens = tokenize(body, language);
+    let mut usage = MethodUsage::default();
+    for window in tokens.windows(3) {
+        let [receiver, dot, member] = window else {
+            continue;
+        };
+        if !receivers.iter().any(|r| receiver.is(r))
+            || !(dot.is(".") || dot.is("?.") || dot.is("::"))
+            || member.kind != TokenKind::Identifier
+        {
+            continue;
+        }
+        if methods.contains(member.text.as_str()) {
+            usage.calls.insert(member.text.clone());
+        } else if !dot.is("::") {
+            usage.fields.insert(member.text.clone());
+        }
+    }
+    usage
+}
+
+/// Components of the method graph: methods are joined when one calls the
+/// other or both use a field. Each component lists method indices, sorted.
+pub fn lcom4_components(names: &[&str], usages: &[MethodUsage]) -> Vec<Vec<usize>> {
+    let index: HashMap<&str, usize> = names.iter().enumerate().map(|(i, &n)| (n, i)).collect();
+    let mut adjacency = vec![Vec::new(); names.len()];
+    let mut first_user: HashMap<&str, usize> = HashMap::new();
+    for (i, usage) in usages.iter().enumerate() {
+        for call in &usage.calls {
+            if let Some(&j) = index.get(call.as_str()) {
+                adjacency[i].push(j);
+            }
+        }
+        for field in &usage.fields {
+            match first_user.get(field.as_str()) {
+                Some(&j) => adjacency[i].push(j),
+                None => {
+                    first_user.insert(field, i);
+                }
+            }
+        }
+    }
+    let mut components = weakly_connected_components(&adjacency, &vec![true; names.len()]);
+    components.sort_by_key(|c| std::cmp::Reverse(c.len()));
+    components
+}
+
+/// Split suggestions for a class with more than one component: the largest
+/// component stays, stateless methods can become functions, and every other
+/// component is a candidate class.
+fn split_suggestions(class: &str, groups: &[MethodGroup]) -> Vec<String> {
+    if groups.len() < 2 {
+        return Vec::new();
+    }
+    let list = |items: &[String]| {
+        items
+            .iter()
+            .map(|i| format!("`{i}`"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    let mut suggestions = Vec::new();
+    let mut stateless = Vec::new();
+    for group in &groups[1..] {
+        if group.methods.len() == 1 && group.fields.is_empty() {
+            stateless.push(group.methods[0].clone());
+        } else if group.fields.is_empty() {
+            suggestions.push(format!(
+                "Extract {} from `{class}`; they call each other but use no fields",
+                list(&group.methods)
+            ));
+        } else {
+            suggestions.push(format!(
+                "Extract {} with field(s) {} from `{class}` into a separate class",
+                list(&group.methods),
+                list(&group.fields)
+            ));
+        }
+    }
+    if !stateless.is_empty() {
+        suggestions.push(format!(
+            "{} use(s) no instance state; move to free functions or static methods",
+            list(&stateless)
+        ));
+    }
+    suggestions
+}
+
+impl CodeGraphBackend {
+    /// Cohesion of one class, or `None` when it has too few methods.
+    pub(crate) fn class_cohesion(
+        &self,
+        graph: &CodeGraph,
+        class_id: NodeId,
+        min_methods: usize,
+        thresholds: GodClassThresholds,
+        sources: &mut HashMap<PathBuf, Option<String>>,
+    ) -> Option<ClassCohesion> {
+        let class = graph.get_node(class_id).ok()?;
+        let class_name = class.properties.get_string("name")?.to_string();
+        let file = self.symbol_index.find_file_for_node(class_id)?;
+        let language = Language::from_path(&file)?;
+
+        let method_ids: Vec<NodeId> = self
+            .get_connected_edges(graph, class_id, Direction::Outgoing)
+            .into_iter()
+            .filter(|(_, _, edge_type)| *edge_type == EdgeType::Contains)
+            .map(|(_, target, _)| target)
+            .filter(|&target| {
+                graph
+                    .get_node(target)
+                    .is_ok_and(|n| n.node_type == NodeType::Function)
+            })
+            .collect();
+        if method_ids.len() < min_methods.max(1) {
+            return None;
+        }
+        let owned: HashSet<NodeId> = method_ids.iter().copied().collect();
+
+        let text = sources
+            .entry(file.clone())
+            .or_insert_with(|| self.document_text(&file))
+            .as_deref()?;
+        let lines: Vec<&str> = text.lines().collect();
+
+        let names: Vec<String> = method_ids
+            .iter()
+            .map(|&id| {
+                graph
+                    .get_node(id)
+                    .ok()
+                    .and_then(|n| n.properties.get_string("name"))
+                    .map(|n| short_name(n).to_string())
+                    .unwrap_or_default()
+            })
+            .collect();
+        let method_names: HashSet<&str> = names.iter().map(String::as_str).collect();
+
+        let mut fields = BTreeSet::new();
+        let mut fan_out = HashSet::new();
+        let mut analyzed: Vec<&str> = Vec::new();
+        let mut usages = Vec::new();
+        for (&method_id, name) in method_ids.iter().zip(&names) {
+            let Ok(node) = graph.get_node(method_id) else {
+                continue;
+            };
+            let start = node.properties.get_int("line_start").unwrap_or(1).max(1) as usize;
+            let end = node
+                .properties
+                .get_int("line_end")
+                .unwrap_or(start as i64)
+                .max(start as i64) as usize;
+            let body = lines
+                .get(start - 1..end.min(lines.len()))
+                .map(|l| l.join("\n"))
+                .unwrap_or_default();
+            let mut usage = method_usage(&body, language, &method_names);
+
+            for (_, target, edge_type) in
+                self.get_connected_edges(graph, method_id, Direction::Outgoing)
+            {
+                if !FAN_OUT_EDGES.contains(&edge_type) {
+                    continue;
+                }
+                if owned.contains(&target) {
+                    if let Some(callee) =
+                        names.iter().zip(&method_ids).find(|(_, &id)| id == target)
+                    {
+                        usage.calls.insert(callee.0.clone());
+                    }
+                    continue;
+                }
+                // Count a method of another class as a dependency on that class.
+                let owner = self
+                    .get_connected_edges(graph, target, Direction::Incoming)
+                    .into_iter()
+                    .find(|(source, _, edge_type)| {
+                        *edge_type == EdgeType::Contains
+                            && graph
+                                .get_node(*source)
+                                .is_ok_and(|n| n.node_type == NodeType::Class)
+                    })
+                    .map(|(source, _, _)| source)
+                    .unwrap_or(target);
+                if owner != class_id {
+                    fan_out.insert(owner);
+                }
+            }
+
+            fields.extend(usage.fields.iter().cloned());
+            if !CONSTRUCTORS.contains(&name.as_str()) {
+                analyzed.push(name);
+                usages.push(usage);
+            }
+        }
+
+        let groups: Vec<MethodGroup> = lcom4_components(&analyzed, &usages)
+            .into_iter()
+            .map(|component| {
+                let mut fields = BTreeSet::new();
+                let methods = component
+                    .iter()
+                    .map(|&i| {
+                        fields.extend(usages[i].fields.iter().cloned());
+                        analyzed[i].to_string()
+                    })
+                    .collect();
+                MethodGroup {
+                    methods,
+                    fields: fields.into_iter().collect(),
+                }
+            })
+            .collect();
+
+        let lcom4 = groups.len();
+        let is_god_class = method_ids.len() >= thresholds.methods
+            && fan_out.len() >= thresholds.fan_out
+            && lcom4 > 1;
+        let mut suggestions = Vec::new();
+        if is_god_class {
+            suggestions.push(format!(
+                "`{class_name}` has {} methods, depends on {} other symbols and splits into {lcom4} unrelated groups",
+                method_ids.len(),
+                fan_out.len()
+            ));
+        }
+        suggestions.extend(split_suggestions(&class_name, &groups));
+
+        let location = self.node_to_location(graph, class_id).ok()?;
+        Some(ClassCohesion {
+            name: class_name,
+            uri: location.uri.to_string(),
+            range: location.range,
+            methods: method_ids.len(),
+            fields: fields.len(),
+            lcom4,
+            fan_out: fan_out.len(),
+            is_god_class,
+            components: groups,
+            suggestions,
+        })
+    }
+
+    /// Cohesion of the classes defined in `files`, worst first.
+    pub(crate) fn classes_cohesion(
+        &self,
+        graph: &CodeGraph,
+        files: &[PathBuf],
+        min_methods: usize,
+        thresholds: GodClassThresholds,
+    ) -> Vec<ClassCohesion> {
+        let mut sources = HashMap::new();
+        let mut classes: Vec<ClassCohesion> = files
+            .iter()
+            .flat_map(|file| self.symbol_index.get_file_symbols(file))
+            .filter(|&id| {
+                graph
+                    .get_node(id)
+                    .is_ok_and(|n| n.node_type == NodeType::Class)
+            })
+            .filter_map(|id| self.class_cohesion(graph, id, min_methods, thresholds, &mut sources))
+            .collect();
+        classes.sort_by(|a, b| {
+            b.is_god_class
+                .cmp(&a.is_god_class)
+                .then_with(|| b.lcom4.cmp(&a.lcom4))
+                .then_with(|| b.methods.cmp(&a.methods))
+        });
+        classes
+    }
+
+    /// Per-class cohesion and god classes in a file or the workspace.
+    pub async fn handle_analyze_cohesion(
+        &self,
+        params: ClassCohesionParams,
+    ) -> Result<ClassCohesionResponse> {
+        let files = match &params.uri {
+            Some(uri) => vec![Url::parse(uri)
+                .map_err(|_| tower_lsp::jsonrpc::Error::invalid_params("Invalid URI"))?
+                .to_file_path()
+                .map_err(|_| tower_lsp::jsonrpc::Error::invalid_params("Invalid file path"))?],
+            None => self.symbol_index.files(),
+        };
+        let thresholds = GodClassThresholds {
+            methods: params
+                .god_class_methods
+                .unwrap_or(DEFAULT_GOD_CLASS_METHODS),
+            fan_out: params
+                .god_class_fan_out
+                .unwrap_or(DEFAULT_GOD_CLASS_FAN_OUT),
+        };
+        let min_methods = params.min_methods.unwrap_or(2);
+
+        let graph = self.graph.read().await;
+        let classes = self.classes_cohesion(&graph, &files, min_methods, thresholds);
+
+        let average_lcom4 = if classes.is_empty() {
+            0.0
+        } else {
+            classes.iter().map(|c| c.lcom4).sum::<usize>() as f64 / classes.len() as f64
+        };
+        Ok(ClassCohesionResponse {
+            summary: ClassCohesionSummary {
+                classes_analyzed: classes.len(),
+                cohesive_classes: classes.iter().filter(|c| c.lcom4 <= 1).count(),
+                average_lcom4,
+                god_classes: classes
+                    .iter()
+                    .filter(|c| c.is_god_class)
+                    .map(|c| c.name.clone())
+                    .collect(),
+            },
+            classes,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PY_CLASS: &str = r#"
+class Report:
+    def __init__(self, db, mailer):
+        self.db = db
+        self.mailer = mailer
+        self.rows = []
+
+    def load(self):
+        self.rows = self.db.fetch()
+
+    def total(self):
+        return sum(r.amount for r in self.rows)
+
+    def send(self, to):
+        self.mailer.send(to, self.render())
+
+    def render(self):
+        return "<html>"
+
+    def slugify(self, text):
+        return text.lower()
+"#;
+
+    fn method_body(source: &str, name: &str) -> String {
+        let start = source.find(&format!("def {name}")).unwrap();
+        let rest = &source[start..];
+        let end = rest[1..].find("\n    def ").map_or(rest.len(), |e| e + 1);
+        format!("    {}", &rest[..end])
+    }
+
+    #[test]
+    fn test_method_usage() {
+        let methods: HashSet<&str> = ["load", "total", "send", "render"].into_iter().collect();
+        let usage = method_usage(&method_body(PY_CLASS, "send"), Language::Python, &methods);
+        assert_eq!(usage.fields, BTreeSet::from(["mailer".to_string()]));
+        assert_eq!(usage.calls, BTreeSet::from(["render".to_string()]));
+
+        let go = "func (s *Server) Start() {\n\ts.listener.Accept()\n\ts.log()\n}";
+        let methods: HashSet<&str> = ["Start", "log"].into_iter().collect();
+        let usage = method_usage(go, Language::Go, &methods);
+        assert_eq!(usage.fields, BTreeSet::from(["listener".to_string()]));
+        assert_eq!(usage.calls, BTreeSet::from(["log".to_string()]));
+    }
+
+    #[test]
+    fn test_lcom4_components() {
+        let names = ["load", "total", "send", "render", "slugify"];
+        let all: HashSet<&str> = names.into_iter().collect();
+        let usages: Vec<MethodUsage> = names
+            .iter()
+            .map(|n| method_usage(&method_body(PY_CLASS, n), Language::Python, &all))
+            .collect();
+
+        // load+total share `rows`, send calls render, slugify stands alone.
+        let components = lcom4_components(&names, &usages);
+        assert_eq!(components, vec![vec![0, 1], vec![2, 3], vec![4]]);
+
+        let groups: Vec<MethodGroup> = components
+            .iter()
+            .map(|c| MethodGroup {
+                methods: c.iter().map(|&i| names[i].to_string()).collect(),
+                fields: c
+                    .iter()
+                    .flat_map(|&i| usages[i].fields.iter().cloned())
+                    .collect::<BTreeSet<_>>()
+                    .into_iter()
+                    .collect(),
+            })
+            .collect();
+        let suggestions = split_suggestions("Report", &groups);
+        assert_eq!(suggestions.len(), 2);
+        assert!(suggestions[0].contains("`send`, `render` with field(s) `mailer`"));
+        assert!(suggestions[1].starts_with("`slugify` use(s) no instance state"));
+    }
+}
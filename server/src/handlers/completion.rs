@@ -0,0 +1,327 @@
+//! Completion Handler - Workspace symbols ranked by graph proximity.
+//!
+//! Suggests symbols from the symbol index whose name starts with the
+//! identifier being typed. Symbols the current file already imports (or
+//! defines) come first, then symbols from files it imports, then the same
+//! directory, then everything else; within each tier symbols with more
+//! incoming calls and references rank higher.
+
+use crate::backend::CodeGraphBackend;
+use crate::import_graph;
+use crate::index;
+use crate::lexer::{self, Language, TokenKind};
+use codegraph::{CodeGraph, Direction, EdgeType, NodeId, NodeType};
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use tower_lsp::jsonrpc::Result;
+use tower_lsp::lsp_types::{
+    CompletionItem, CompletionItemKind, CompletionItemLabelDetails, CompletionList,
+    CompletionParams, CompletionResponse, Documentation, MarkupContent, MarkupKind,
+};
+
+/// Maximum number of items returned; the list is marked incomplete beyond it.
+const MAX_ITEMS: usize = 100;
+
+/// Maximum number of matching symbols, closest tiers first, whose fan-in is
+/// computed per request.
+const MAX_CANDIDATES: usize = 2000;
+
+/// How close a candidate is to the file being edited; lower is closer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Proximity {
+    /// Imported by name, or defined in the current file.
+    Imported,
+    /// Defined in a file the current file imports.
+    Dependency,
+    /// Defined in the same directory.
+    SameDirectory,
+    /// Anywhere else in the workspace.
+    Workspace,
+}
+
+/// Names brought into scope by a file's imports: the imported symbols, and
+/// the last segment of each module path (`pkg.util` -> `util`).
+pub fn imported_names(imports: &[import_graph::RawImport]) -> HashSet<String> {
+    let mut names = HashSet::new();
+    for (_, module, symbols) in imports {
+        names.extend(symbols.iter().map(|s| index::short_name(s).to_string()));
+        let last = module
+            .trim_matches('"')
+            .rsplit(['.', ':', '/'])
+            .next()
+            .unwrap_or(module);
+        if !last.is_empty() && last != "*" {
+            names.insert(last.to_string());
+        }
+    }
+    names
+}
+
+/// Number of distinct symbols that call, use or reference `node_id`.
+pub(crate) fn fan_in(graph: &CodeGraph, node_id: NodeId) -> usize {
+    graph
+        .get_neighbors(node_id, Direction::Incoming)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|&source| {
+            graph
+                .get_edges_between(source, node_id)
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|e| graph.get_edge(e).ok())
+                .any(|e| !matches!(e.edge_type, EdgeType::Contains | EdgeType::Defines))
+        })
+        .count()
+}
+
+fn completion_kind(node_type: NodeType, name: &str) -> CompletionItemKind {
+    match node_type {
+        NodeType::Function if name.contains('.') || name.contains("::") => {
+            CompletionItemKind::METHOD
+        }
+        NodeType::Function => CompletionItemKind::FUNCTION,
+        NodeType::Class => CompletionItemKind::CLASS,
+        NodeType::Interface => CompletionItemKind::INTERFACE,
+        NodeType::Module => CompletionItemKind::MODULE,
+        NodeType::Variable => CompletionItemKind::VARIABLE,
+        NodeType::Type => CompletionItemKind::STRUCT,
+        NodeType::CodeFile => CompletionItemKind::FILE,
+        NodeType::Generic => CompletionItemKind::TEXT,
+    }
+}
+
+impl CodeGraphBackend {
+    /// Handle textDocument/completion.
+    pub async fn handle_completion(
+        &self,
+        params: CompletionParams,
+    ) -> Result<Option<CompletionResponse>> {
+        let position = params.text_document_position.position;
+        let path = params
+            .text_document_position
+            .text_document
+            .uri
+            .to_file_path()
+            .map_err(|_| tower_lsp::jsonrpc::Error::invalid_params("Invalid URI"))?;
+
+        let Some(language) = Language::from_path(&path) else {
+            return Ok(None);
+        };
+        let Some(text) = self.document_text(&path) else {
+            return Ok(None);
+        };
+
+        // Only complete identifiers in code: comments and strings produce no
+        // identifier token ending at the cursor.
+        let tokens = lexer::tokenize(&text, language);
+        let Some(prefix) = tokens
+            .iter()
+            .find(|t| {
+                t.end == position && matches!(t.kind, TokenKind::Identifier | TokenKind::Keyword)
+            })
+            .map(|t| t.text.clone())
+        else {
+            return Ok(None);
+        };
+
+        let folders = self.workspace_folders.read().await.clone();
+        let graph = self.graph.read().await;
+
+        let candidates = self.symbol_index.find_by_prefix(&prefix);
+        if candidates.is_empty() {
+            return Ok(None);
+        }
+
+        let (names, dependencies) =
+            Self::file_import_scope(&graph, &path, &self.symbol_index.files());
+        let dir = path.parent();
+
+        let mut tiered: Vec<(Proximity, String, NodeId, PathBuf)> = candidates
+            .into_iter()
+            .filter_map(|id| {
+                let node = graph.get_node(id).ok()?;
+                let name = node.properties.get_string("name")?;
+                let short = index::short_name(name).to_string();
+                let file = self.symbol_index.find_file_for_node(id).unwrap_or_default();
+                let proximity = if file == path || names.contains(&short) {
+                    Proximity::Imported
+                } else if dependencies.contains(&file) {
+                    Proximity::Dependency
+                } else if file.parent() == dir {
+                    Proximity::SameDirectory
+                } else {
+                    Proximity::Workspace
+                };
+                Some((proximity, short, id, file))
+            })
+            .collect();
+        tiered.sort_by_key(|(proximity, ..)| *proximity);
+        tiered.truncate(MAX_CANDIDATES);
+
+        let mut scored: Vec<(Proximity, Reverse<usize>, String, NodeId, PathBuf)> = tiered
+            .into_iter()
+            .map(|(proximity, short, id, file)| {
+                (proximity, Reverse(fan_in(&graph, id)), short, id, file)
+            })
+            .collect();
+        scored.sort();
+
+        let is_incomplete = scored.len() > MAX_ITEMS;
+        let items = scored
+            .into_iter()
+            .take(MAX_ITEMS)
+            .enumerate()
+            .filter_map(|(rank, (_, _, short, id, file))| {
+                let node = graph.get_node(id).ok()?;
+                let name = node.properties.get_string("name").unwrap_or(&short);
+                let module = folders
+                    .iter()
+                    .find_map(|f| file.strip_prefix(f).ok())
+                    .unwrap_or(&file)
+                    .display()
+                    .to_string();
+                let signature = node
+                    .properties
+                    .get_string("signature")
+                    .filter(|s| !s.is_empty())
+                    .map(|s| s.trim().to_string());
+                Some(CompletionItem {
+                    label: short.clone(),
+                    label_details: Some(CompletionItemLabelDetails {
+                        detail: None,
+                        description: (!module.is_empty()).then_some(module),
+                    }),
+                    kind: Some(completion_kind(node.node_type, name)),
+                    detail: signature,
+                    documentation: node.properties.get_string("doc").map(|doc| {
+                        Documentation::MarkupContent(MarkupContent {
+                            kind: MarkupKind::Markdown,
+                            value: doc.to_string(),
+                        })
+                    }),
+                    sort_text: Some(format!("{rank:04}")),
+                    filter_text: Some(short),
+                    data: Some(serde_json::json!({ "nodeId": id.to_string() })),
+                    ..Default::default()
+                })
+            })
+            .collect();
+
+        Ok(Some(CompletionResponse::List(CompletionList {
+            is_incomplete,
+            items,
+        })))
+    }
+
+    /// Names imported by `path` and the files among `files` it imports.
+    pub(crate) fn file_import_scope(
+        graph: &CodeGraph,
+        path: &Path,
+        files: &[PathBuf],
+    ) -> (HashSet<String>, HashSet<PathBuf>) {
+        let file_node = graph
+            .query()
+            .node_type(NodeType::CodeFile)
+            .property("path", path.to_string_lossy().to_string())
+            .execute()
+            .unwrap_or_default()
+            .into_iter()
+            .next();
+        let Some(file_node) = file_node else {
+            return (HashSet::new(), HashSet::new());
+        };
+
+        let imports = import_graph::file_imports(graph, file_node, path);
+        let names = imported_names(&imports);
+        let dependencies = import_graph::imported_files(&imports, files);
+        (names, dependencies)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_imported_names() {
+        let imports = vec![
+            (
+                PathBuf::from("/w/a.py"),
+                "pkg.util".to_string(),
+                vec!["helper".to_string(), "Parser.parse".to_string()],
+            ),
+            (PathBuf::from("/w/a.py"), "\"fmt\"".to_string(), Vec::new()),
+            (
+                PathBuf::from("/w/a.rs"),
+                "crate::graph::*".to_string(),
+                Vec::new(),
+            ),
+        ];
+        let names = imported_names(&imports);
+        assert!(names.contains("helper"));
+        assert!(names.contains("parse"));
+        assert!(names.contains("util"));
+        assert!(names.contains("fmt"));
+        assert!(!names.contains("*"));
+    }
+
+    #[test]
+    fn test_proximity_order() {
+        let mut tiers = vec![
+            Proximity::Workspace,
+            Proximity::Imported,
+            Proximity::SameDirectory,
+            Proximity::Dependency,
+        ];
+        tiers.sort();
+        assert_eq!(
+            tiers,
+            vec![
+                Proximity::Imported,
+                Proximity::Dependency,
+                Proximity::SameDirectory,
+                Proximity::Workspace,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_fan_in_ignores_containment() {
+        let mut graph = CodeGraph::in_memory().unwrap();
+        let file = graph
+            .add_node(NodeType::CodeFile, Default::default())
+            .unwrap();
+        let target = graph
+            .add_node(NodeType::Function, Default::default())
+            .unwrap();
+        let caller = graph
+            .add_node(NodeType::Function, Default::default())
+            .unwrap();
+        graph
+            .add_edge(file, target, EdgeType::Contains, Default::default())
+            .unwrap();
+        graph
+            .add_edge(caller, target, EdgeType::Calls, Default::default())
+            .unwrap();
+
+        assert_eq!(fan_in(&graph, target), 1);
+        assert_eq!(fan_in(&graph, caller), 0);
+    }
+
+    #[test]
+    fn test_completion_kind() {
+        assert_eq!(
+            completion_kind(NodeType::Function, "helper"),
+            CompletionItemKind::FUNCTION
+        );
+        assert_eq!(
+            completion_kind(NodeType::Function, "Parser.parse"),
+            CompletionItemKind::METHOD
+        );
+        assert_eq!(
+            completion_kind(NodeType::Class, "Parser"),
+            CompletionItemKind::CLASS
+        );
+    }
+}
@@ -0,0 +1,332 @@
+//! Complexity Report Handler - Workspace-wide complexity ranking.
+//!
+//! `codegraph/getComplexityReport` reads the `complexity*` properties the
+//! parsers attach to function nodes across the workspace or a directory and
+//! returns the most complex functions, a histogram of the distribution, grade
+//! counts and per-directory averages. Files can be narrowed by language and
+//! by path globs relative to the workspace folder.
+
+use crate::backend::CodeGraphBackend;
+use crate::handlers::ai_context::LocationInfo;
+use crate::handlers::architecture::{matches_path, path_patterns};
+use crate::handlers::metrics::{
+    function_source_metrics, source_metrics_summary, FunctionComplexity,
+};
+use crate::lexer::Language;
+use codegraph::NodeType;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tower_lsp::jsonrpc::Result;
+use tower_lsp::lsp_types::Url;
+
+const DEFAULT_TOP: usize = 20;
+const DEFAULT_THRESHOLD: u32 = 10;
+
+/// Lower bounds of the histogram buckets; the last one is open-ended.
+const BUCKETS: &[u32] = &[1, 3, 6, 11, 16, 21, 31, 51];
+
+const GRADES: &[char] = &['A', 'B', 'C', 'D', 'F'];
+
+// ==========================================
+// Report Types
+// ==========================================
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComplexityReportParams {
+    /// Directory or file to report on (default: whole workspace)
+    pub uri: Option<String>,
+    /// Number of most complex functions to return (default 20)
+    pub top: Option<usize>,
+    /// Complexity threshold for counting functions as too complex (default 10)
+    pub threshold: Option<u32>,
+    /// Only these languages: python, rust, typescript, go, c
+    pub languages: Option<Vec<String>>,
+    /// Only files matching these globs, relative to the workspace folder
+    pub include: Option<Vec<String>>,
+    /// Skip files matching these globs
+    pub exclude: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComplexityReportResponse {
+    /// Most complex functions, highest first
+    pub functions: Vec<FunctionComplexity>,
+    pub distribution: Vec<ComplexityBucket>,
+    /// Functions per grade, A to F
+    pub grades: BTreeMap<char, usize>,
+    /// Highest average first
+    pub directories: Vec<DirectoryComplexity>,
+    pub summary: ComplexityReportSummary,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComplexityBucket {
+    /// Inclusive lower bound
+    pub min: u32,
+    /// Inclusive upper bound, `None` for the last bucket
+    pub max: Option<u32>,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DirectoryComplexity {
+    /// Path relative to the workspace folder
+    pub path: String,
+    pub functions: usize,
+    pub average_complexity: f64,
+    pub max_complexity: u32,
+    pub functions_above_threshold: usize,
+    pub grade: char,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComplexityReportSummary {
+    pub files_analyzed: usize,
+    pub total_functions: usize,
+    pub average_complexity: f64,
+    pub max_complexity: u32,
+    pub functions_above_threshold: usize,
+    pub overall_grade: char,
+    pub average_maintainability_index: Option<f64>,
+    pub min_maintainability_index: Option<f64>,
+    pub total_halstead_volume: f64,
+    pub total_halstead_effort: f64,
+}
+
+// ==========================================
+// Aggregation
+// ==========================================
+
+/// Count complexities into the fixed histogram buckets.
+pub fn complexity_histogram(complexities: &[u32]) -> Vec<ComplexityBucket> {
+    let mut buckets: Vec<ComplexityBucket> = BUCKETS
+        .iter()
+        .enumerate()
+        .map(|(i, &min)| ComplexityBucket {
+            min,
+            max: BUCKETS.get(i + 1).map(|next| next - 1),
+            count: 0,
+        })
+        .collect();
+    for &complexity in complexities {
+        let bucket = BUCKETS
+            .iter()
+            .rposition(|&min| complexity >= min)
+            .unwrap_or(0);
+        buckets[bucket].count += 1;
+    }
+    buckets
+}
+
+/// Average, maximum and above-threshold count per directory, highest
+/// average first. `functions` holds `(directory, complexity)` pairs.
+pub fn directory_averages(functions: &[(String, u32)], threshold: u32) -> Vec<DirectoryComplexity> {
+    let mut per_dir: BTreeMap<&str, Vec<u32>> = BTreeMap::new();
+    for (dir, complexity) in functions {
+        per_dir.entry(dir).or_default().push(*complexity);
+    }
+
+    let mut directories: Vec<DirectoryComplexity> = per_dir
+        .into_iter()
+        .map(|(path, complexities)| {
+            let average = complexities.iter().sum::<u32>() as f64 / complexities.len() as f64;
+            DirectoryComplexity {
+                path: path.to_string(),
+                functions: complexities.len(),
+                average_complexity: average,
+                max_complexity: complexities.iter().copied().max().unwrap_or(0),
+                functions_above_threshold: complexities.iter().filter(|&&c| c > threshold).count(),
+                grade: CodeGraphBackend::file_grade(average),
+            }
+        })
+        .collect();
+    directories.sort_by(|a, b| {
+        b.average_complexity
+            .total_cmp(&a.average_complexity)
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    directories
+}
+
+fn language_name(language: Language) -> String {
+    format!("{language:?}").to_lowercase()
+}
+
+impl CodeGraphBackend {
+    /// Rank function complexity across the workspace or a directory.
+    pub async fn handle_get_complexity_report(
+        &self,
+        params: ComplexityReportParams,
+    ) -> Result<ComplexityReportResponse> {
+        let scope = match &params.uri {
+            Some(uri) => Some(
+                Url::parse(uri)
+                    .map_err(|_| tower_lsp::jsonrpc::Error::invalid_params("Invalid URI"))?
+                    .to_file_path()
+                    .map_err(|_| tower_lsp::jsonrpc::Error::invalid_params("Invalid file path"))?,
+            ),
+            None => None,
+        };
+        let include = path_patterns(params.include.as_deref().unwrap_or_default())
+            .map_err(tower_lsp::jsonrpc::Error::invalid_params)?;
+        let exclude = path_patterns(params.exclude.as_deref().unwrap_or_default())
+            .map_err(tower_lsp::jsonrpc::Error::invalid_params)?;
+        let languages: Option<Vec<String>> = params
+            .languages
+            .map(|l| l.iter().map(|l| l.to_lowercase()).collect());
+        let top = params.top.unwrap_or(DEFAULT_TOP);
+        let threshold = params.threshold.unwrap_or(DEFAULT_THRESHOLD);
+        let folders = self.workspace_folders.read().await.clone();
+
+        let relative = |path: &Path| -> PathBuf {
+            folders
+                .iter()
+                .find_map(|f| path.strip_prefix(f).ok())
+                .unwrap_or(path)
+                .to_path_buf()
+        };
+
+        let mut files: Vec<PathBuf> = self
+            .symbol_index
+            .files()
+            .into_iter()
+            .filter(|file| scope.as_ref().is_none_or(|s| file.starts_with(s)))
+            .filter(|file| {
+                let Some(language) = Language::from_path(file) else {
+                    return false;
+                };
+                languages
+                    .as_ref()
+                    .is_none_or(|l| l.contains(&language_name(language)))
+            })
+            .filter(|file| {
+                let rel = relative(file);
+                (include.is_empty() || matches_path(&include, &rel))
+                    && !matches_path(&exclude, &rel)
+            })
+            .collect();
+        files.sort();
+
+        let graph = self.graph.read().await;
+        let mut functions = Vec::new();
+        let mut by_directory = Vec::new();
+        for file in &files {
+            let directory = relative(file)
+                .parent()
+                .map(|p| p.display().to_string())
+                .filter(|p| !p.is_empty())
+                .unwrap_or_else(|| ".".to_string());
+            let language = Language::from_path(file);
+            let text = self.document_text(file).unwrap_or_default();
+            let lines: Vec<&str> = text.lines().collect();
+            for node_id in self.symbol_index.get_file_symbols(file) {
+                let Ok(node) = graph.get_node(node_id) else {
+                    continue;
+                };
+                if node.node_type != NodeType::Function {
+                    continue;
+                }
+                let Ok(location) = self.node_to_location(&graph, node_id) else {
+                    continue;
+                };
+                let (complexity, details, grade) = Self::get_complexity_from_node(node);
+                let source_metrics = language.and_then(|language| {
+                    function_source_metrics(&lines, node, language, complexity)
+                });
+                by_directory.push((directory.clone(), complexity));
+                functions.push(FunctionComplexity {
+                    name: node
+                        .properties
+                        .get_string("name")
+                        .unwrap_or("anonymous")
+                        .to_string(),
+                    complexity,
+                    grade,
+                    location: LocationInfo {
+                        uri: location.uri.to_string(),
+                        range: location.range,
+                    },
+                    details,
+                    maintainability_index: source_metrics.as_ref().map(|(_, mi)| *mi),
+                    halstead: source_metrics.map(|(halstead, _)| halstead),
+                });
+            }
+        }
+        drop(graph);
+
+        let complexities: Vec<u32> = functions.iter().map(|f| f.complexity).collect();
+        let mut grades: BTreeMap<char, usize> = GRADES.iter().map(|&g| (g, 0)).collect();
+        for function in &functions {
+            *grades.entry(function.grade).or_default() += 1;
+        }
+        let total = complexities.iter().sum::<u32>();
+        let average_complexity = if functions.is_empty() {
+            0.0
+        } else {
+            total as f64 / functions.len() as f64
+        };
+
+        let (average_maintainability_index, min_maintainability_index, volume, effort) =
+            source_metrics_summary(&functions);
+        let summary = ComplexityReportSummary {
+            files_analyzed: files.len(),
+            total_functions: functions.len(),
+            average_complexity,
+            max_complexity: complexities.iter().copied().max().unwrap_or(0),
+            functions_above_threshold: complexities.iter().filter(|&&c| c > threshold).count(),
+            overall_grade: Self::file_grade(average_complexity),
+            average_maintainability_index,
+            min_maintainability_index,
+            total_halstead_volume: volume,
+            total_halstead_effort: effort,
+        };
+
+        functions.sort_by_key(|f| std::cmp::Reverse(f.complexity));
+        functions.truncate(top);
+
+        Ok(ComplexityReportResponse {
+            functions,
+            distribution: complexity_histogram(&complexities),
+            grades,
+            directories: directory_averages(&by_directory, threshold),
+            summary,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_complexity_histogram() {
+        let buckets = complexity_histogram(&[1, 2, 3, 5, 10, 12, 25, 80, 0]);
+        let counts: Vec<usize> = buckets.iter().map(|b| b.count).collect();
+        assert_eq!(counts, vec![3, 2, 1, 1, 0, 1, 0, 1]);
+        assert_eq!((buckets[0].min, buckets[0].max), (1, Some(2)));
+        assert_eq!((buckets[7].min, buckets[7].max), (51, None));
+    }
+
+    #[test]
+    fn test_directory_averages() {
+        let functions = vec![
+            ("src/api".to_string(), 4),
+            ("src/core".to_string(), 30),
+            ("src/api".to_string(), 12),
+            ("src/core".to_string(), 2),
+        ];
+        let dirs = directory_averages(&functions, 10);
+        assert_eq!(dirs[0].path, "src/core");
+        assert_eq!(dirs[0].average_complexity, 16.0);
+        assert_eq!(dirs[0].grade, 'D');
+        assert_eq!(dirs[1].path, "src/api");
+        assert_eq!(dirs[1].max_complexity, 12);
+        assert_eq!(dirs[1].functions_above_threshold, 1);
+    }
+}
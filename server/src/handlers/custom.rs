@@ -1,9 +1,11 @@
 //! Custom LSP request handlers for graph-based features.
 
 use crate::backend::CodeGraphBackend;
-use codegraph::{CodeGraph, Direction, EdgeType, Node, NodeId};
+use crate::index;
+use codegraph::{CodeGraph, Direction, EdgeType, Node, NodeId, NodeType};
 use serde::{Deserialize, Serialize};
-use std::collections::{HashSet, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::PathBuf;
 use tower_lsp::jsonrpc::Result;
 use tower_lsp::lsp_types::{Position, Range, Url};
 
@@ -358,7 +360,7 @@ impl CodeGraphBackend {
         Ok(RelatedTestsResponse { tests, truncated })
     }
 
-    fn is_test_node(node: &Node) -> bool {
+    pub(crate) fn is_test_node(node: &Node) -> bool {
         let name = node.properties.get_string("name").unwrap_or("");
         let path = node.properties.get_string("path").unwrap_or("");
 
@@ -608,7 +610,7 @@ impl CodeGraphBackend {
                         edges.push(CallEdge {
                             from: source_id.to_string(),
                             to: target_id.to_string(),
-                            call_sites: Vec::new(), // Could be populated from edge properties
+                            call_sites: self.find_call_site_ranges(&graph, source_id, target_id),
                             is_recursive,
                         });
 
@@ -635,12 +637,37 @@ impl CodeGraphBackend {
 // Impact Analysis Request
 // ==========================================
 
+/// Default number of dependency hops followed from the changed symbol.
+const DEFAULT_IMPACT_DEPTH: usize = 3;
+
+/// Upper bound for a user-specified depth.
+const MAX_IMPACT_DEPTH: usize = 20;
+
+/// Default number of dependents followed from any one symbol.
+const DEFAULT_IMPACT_FAN_OUT: usize = 50;
+
+/// Edge types along which a change propagates to the edge's source.
+const IMPACT_EDGES: &[EdgeType] = &[
+    EdgeType::Calls,
+    EdgeType::Invokes,
+    EdgeType::Instantiates,
+    EdgeType::References,
+    EdgeType::Uses,
+    EdgeType::Extends,
+    EdgeType::Implements,
+    EdgeType::Imports,
+];
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ImpactAnalysisParams {
     pub uri: String,
     pub position: Position,
     pub analysis_type: String, // "modify", "delete", "rename"
+    /// Maximum number of dependency hops (default 3)
+    pub depth: Option<usize>,
+    /// Maximum number of dependents followed from any one symbol (default 50)
+    pub max_fan_out: Option<usize>,
 }
 
 #[derive(Debug, Serialize)]
@@ -651,16 +678,34 @@ pub struct DirectImpact {
     #[serde(rename = "type")]
     pub impact_type: String,
     pub severity: String,
+    pub symbol: String,
 }
 
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct IndirectImpact {
     pub uri: String,
+    pub range: Range,
+    pub symbol: String,
+    /// Number of dependency hops from the changed symbol
+    pub distance: usize,
+    /// Symbol names from the changed symbol to this one
     pub path: Vec<String>,
+    /// Dependency edges that reached this symbol, starting at the changed symbol
+    pub edge_path: Vec<ImpactEdge>,
     pub severity: String,
 }
 
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImpactEdge {
+    /// The dependent symbol
+    pub from: String,
+    /// The symbol it depends on
+    pub to: String,
+    pub edge_type: String,
+}
+
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AffectedTest {
@@ -674,6 +719,8 @@ pub struct ImpactSummary {
     pub files_affected: usize,
     pub breaking_changes: usize,
     pub warnings: usize,
+    /// Dependents not followed because of the fan-out cap
+    pub truncated_dependents: usize,
 }
 
 #[derive(Debug, Serialize)]
@@ -685,6 +732,97 @@ pub struct ImpactAnalysisResponse {
     pub summary: ImpactSummary,
 }
 
+/// A symbol that depends on the changed symbol, directly or transitively.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReachedSymbol {
+    pub node: NodeId,
+    /// Number of dependency hops from the changed symbol
+    pub distance: usize,
+    /// `(dependent, dependency, edge type)` from the changed symbol outwards
+    pub path: Vec<(NodeId, NodeId, EdgeType)>,
+}
+
+/// Symbols whose behaviour can change when the start symbols change.
+#[derive(Debug, Default)]
+pub struct ReverseReachability {
+    /// Reached symbols in breadth-first order, nearest first
+    pub reached: Vec<ReachedSymbol>,
+    /// Dependents skipped because a symbol had more than the fan-out cap
+    pub truncated: usize,
+}
+
+/// Walk dependency edges backwards from `starts`, breadth first.
+///
+/// Each symbol is reported once, at its shortest distance, with the edge path
+/// that first reached it. `importers` supplies extra `Imports` dependents that
+/// are not graph edges, such as files importing the symbol by name.
+pub fn reverse_reachability(
+    graph: &CodeGraph,
+    starts: &[NodeId],
+    max_depth: usize,
+    max_fan_out: usize,
+    importers: impl Fn(NodeId) -> Vec<NodeId>,
+) -> ReverseReachability {
+    let mut result = ReverseReachability::default();
+    let mut parent: HashMap<NodeId, (NodeId, EdgeType)> = HashMap::new();
+    let mut visited: HashSet<NodeId> = starts.iter().copied().collect();
+    let mut queue: VecDeque<(NodeId, usize)> = starts.iter().map(|&s| (s, 0)).collect();
+
+    while let Some((node, distance)) = queue.pop_front() {
+        if distance >= max_depth {
+            continue;
+        }
+
+        let mut dependents: Vec<(NodeId, EdgeType)> =
+            CodeGraphBackend::get_incoming_edges(graph, node)
+                .into_iter()
+                .filter(|(_, _, edge_type)| IMPACT_EDGES.contains(edge_type))
+                .map(|(source, _, edge_type)| (source, edge_type))
+                .chain(importers(node).into_iter().map(|n| (n, EdgeType::Imports)))
+                .filter(|(source, _)| !visited.contains(source))
+                .collect();
+        dependents.sort_by_key(|(source, _)| *source);
+        dependents.dedup_by_key(|(source, _)| *source);
+
+        if dependents.len() > max_fan_out {
+            result.truncated += dependents.len() - max_fan_out;
+            dependents.truncate(max_fan_out);
+        }
+
+        for (dependent, edge_type) in dependents {
+            visited.insert(dependent);
+            parent.insert(dependent, (node, edge_type));
+
+            let mut path = Vec::with_capacity(distance + 1);
+            let mut current = dependent;
+            while let Some(&(dependency, edge_type)) = parent.get(&current) {
+                path.push((current, dependency, edge_type));
+                current = dependency;
+            }
+            path.reverse();
+
+            result.reached.push(ReachedSymbol {
+                node: dependent,
+                distance: distance + 1,
+                path,
+            });
+            queue.push_back((dependent, distance + 1));
+        }
+    }
+
+    result
+}
+
+fn impact_type(edge_type: EdgeType) -> &'static str {
+    match edge_type {
+        EdgeType::Calls | EdgeType::Invokes | EdgeType::Instantiates => "caller",
+        EdgeType::Extends => "subclass",
+        EdgeType::Implements => "implementation",
+        EdgeType::Imports => "importer",
+        _ => "reference",
+    }
+}
+
 impl CodeGraphBackend {
     pub async fn handle_analyze_impact(
         &self,
@@ -697,6 +835,14 @@ impl CodeGraphBackend {
             .to_file_path()
             .map_err(|_| tower_lsp::jsonrpc::Error::invalid_params("Invalid file path"))?;
 
+        let max_depth = params
+            .depth
+            .unwrap_or(DEFAULT_IMPACT_DEPTH)
+            .clamp(1, MAX_IMPACT_DEPTH);
+        let max_fan_out = params.max_fan_out.unwrap_or(DEFAULT_IMPACT_FAN_OUT).max(1);
+
+        // Takes the graph lock itself, so it must come first.
+        let import_findings = self.import_findings().await;
         let graph = self.graph.read().await;
 
         // Find node at position
@@ -711,140 +857,129 @@ impl CodeGraphBackend {
                         files_affected: 0,
                         breaking_changes: 0,
                         warnings: 0,
+                        truncated_dependents: 0,
                     },
                 })
             }
         };
 
+        // Files that import a symbol by name depend on it even when no call
+        // or reference edge was recorded.
+        let imports = &import_findings.graph;
+        let file_nodes: HashMap<PathBuf, NodeId> = graph
+            .query()
+            .node_type(NodeType::CodeFile)
+            .execute()
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|id| {
+                let path = graph.get_node(id).ok()?.properties.get_string("path")?;
+                Some((PathBuf::from(path), id))
+            })
+            .collect();
+        let importers = |node: NodeId| -> Vec<NodeId> {
+            let Some(name) = graph
+                .get_node(node)
+                .ok()
+                .filter(|n| n.node_type != NodeType::CodeFile)
+                .and_then(|n| n.properties.get_string("name"))
+            else {
+                return Vec::new();
+            };
+            let Some(file) = self
+                .symbol_index
+                .find_file_for_node(node)
+                .and_then(|f| imports.index_of(&f))
+            else {
+                return Vec::new();
+            };
+            imports
+                .importers_of(file)
+                .filter(|edge| {
+                    edge.symbols
+                        .iter()
+                        .any(|s| index::short_name(s) == index::short_name(name))
+                })
+                .filter_map(|edge| file_nodes.get(&imports.files[edge.from]).copied())
+                .collect()
+        };
+
+        let reachability =
+            reverse_reachability(&graph, &[node_id], max_depth, max_fan_out, importers);
+
+        let name_of = |id: NodeId| {
+            graph
+                .get_node(id)
+                .ok()
+                .and_then(|n| {
+                    n.properties
+                        .get_string("name")
+                        .or_else(|| n.properties.get_string("path"))
+                })
+                .unwrap_or("")
+                .to_string()
+        };
+
+        let direct_severity = match params.analysis_type.as_str() {
+            "delete" => "breaking",
+            "rename" => "breaking",
+            "modify" => "warning",
+            _ => "info",
+        };
+
         let mut direct_impact = Vec::new();
         let mut indirect_impact = Vec::new();
         let mut affected_tests = Vec::new();
         let mut affected_files = HashSet::new();
+        let mut sources = HashMap::new();
 
-        // Find direct references
-        let incoming_edges = Self::get_incoming_edges(&graph, node_id);
-
-        for (source_id, _target_id, edge_type) in incoming_edges {
-            if let Ok(ref_node) = graph.get_node(source_id) {
-                let ref_path = ref_node
-                    .properties
-                    .get_string("path")
-                    .map(|s| s.to_string())
-                    .or_else(|| {
-                        self.symbol_index
-                            .find_file_for_node(source_id)
-                            .and_then(|p| p.to_str().map(|s| format!("file://{s}")))
-                    })
-                    .unwrap_or_default();
-                let ref_name = ref_node
-                    .properties
-                    .get_string("name")
-                    .unwrap_or("")
-                    .to_string();
-
-                affected_files.insert(ref_path.clone());
-
-                let start_line = get_line_start(ref_node).saturating_sub(1);
-                let start_col = get_col_start(ref_node);
-                let end_line = get_line_end(ref_node, start_line + 2).saturating_sub(1);
-                let end_col = get_col_end(ref_node);
-
-                let impact_type = match edge_type {
-                    EdgeType::Calls => "caller",
-                    EdgeType::References => "reference",
-                    EdgeType::Extends => "subclass",
-                    EdgeType::Implements => "implementation",
-                    _ => "reference",
-                };
-
-                let severity = match params.analysis_type.as_str() {
-                    "delete" => "breaking",
-                    "rename" => "breaking",
-                    "modify" => "warning",
-                    _ => "info",
-                };
-
-                // Check if it's a test
-                let is_test = ref_name.starts_with("test_")
-                    || ref_name.ends_with("_test")
-                    || ref_path.contains("test");
-
-                if is_test {
-                    affected_tests.push(AffectedTest {
-                        uri: ref_path.clone(),
-                        test_name: ref_name,
-                    });
-                }
-
-                direct_impact.push(DirectImpact {
-                    uri: ref_path,
-                    range: Range {
-                        start: Position {
-                            line: start_line,
-                            character: start_col,
-                        },
-                        end: Position {
-                            line: end_line,
-                            character: end_col,
-                        },
-                    },
-                    impact_type: impact_type.to_string(),
-                    severity: severity.to_string(),
+        for reached in reachability.reached {
+            let Ok(location) = self.node_to_location(&graph, reached.node) else {
+                continue;
+            };
+            let uri = location.uri.to_string();
+            affected_files.insert(uri.clone());
+
+            let symbol = name_of(reached.node);
+            if self.is_test_symbol(&graph, reached.node, &mut sources) {
+                affected_tests.push(AffectedTest {
+                    uri: uri.clone(),
+                    test_name: symbol.clone(),
                 });
             }
-        }
-
-        // Find indirect impact (transitive dependencies)
-        let mut visited = HashSet::new();
-        let mut queue: VecDeque<(NodeId, Vec<String>)> = VecDeque::new();
-
-        for impact in &direct_impact {
-            // Get node IDs for direct impacts and trace further
-            if let Ok(nodes) = graph.query().property("path", impact.uri.clone()).execute() {
-                for n_id in nodes {
-                    if !visited.contains(&n_id) && n_id != node_id {
-                        visited.insert(n_id);
-                        queue.push_back((n_id, vec![impact.uri.clone()]));
-                    }
-                }
-            }
-        }
 
-        while let Some((current_id, path_chain)) = queue.pop_front() {
-            if path_chain.len() >= 3 {
-                continue; // Limit depth
-            }
-
-            let incoming_edges = Self::get_incoming_edges(&graph, current_id);
-            for (source_id, _target_id, _edge_type) in incoming_edges {
-                if source_id != node_id && !visited.contains(&source_id) {
-                    if let Ok(ref_node) = graph.get_node(source_id) {
-                        let ref_path = ref_node
-                            .properties
-                            .get_string("path")
-                            .map(|s| s.to_string())
-                            .or_else(|| {
-                                self.symbol_index
-                                    .find_file_for_node(source_id)
-                                    .and_then(|p| p.to_str().map(|s| format!("file://{s}")))
-                            })
-                            .unwrap_or_default();
-
-                        if !affected_files.contains(&ref_path) {
-                            let mut new_path = path_chain.clone();
-                            new_path.push(ref_path.clone());
-
-                            indirect_impact.push(IndirectImpact {
-                                uri: ref_path,
-                                path: new_path.clone(),
-                                severity: "warning".to_string(),
-                            });
-
-                            visited.insert(source_id);
-                            queue.push_back((source_id, new_path));
-                        }
-                    }
-                }
+            let Some(&(_, _, edge_type)) = reached.path.last() else {
+                continue;
+            };
+            if reached.distance == 1 {
+                direct_impact.push(DirectImpact {
+                    uri,
+                    range: location.range,
+                    impact_type: impact_type(edge_type).to_string(),
+                    severity: direct_severity.to_string(),
+                    symbol,
+                });
+            } else {
+                let edge_path: Vec<ImpactEdge> = reached
+                    .path
+                    .iter()
+                    .map(|&(from, to, edge_type)| ImpactEdge {
+                        from: name_of(from),
+                        to: name_of(to),
+                        edge_type: edge_type.to_string(),
+                    })
+                    .collect();
+                let mut path: Vec<String> = vec![name_of(node_id)];
+                path.extend(edge_path.iter().map(|e| e.from.clone()));
+                indirect_impact.push(IndirectImpact {
+                    uri,
+                    range: location.range,
+                    symbol,
+                    distance: reached.distance,
+                    path,
+                    edge_path,
+                    severity: "warning".to_string(),
+                });
             }
         }
 
@@ -866,6 +1001,7 @@ impl CodeGraphBackend {
                 files_affected: affected_files.len(),
                 breaking_changes: breaking_count,
                 warnings: warning_count,
+                truncated_dependents: reachability.truncated,
             },
         })
     }
@@ -956,3 +1092,87 @@ impl CodeGraphBackend {
         Ok(ParserMetricsResponse { metrics, totals })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn add(graph: &mut CodeGraph, node_type: NodeType) -> NodeId {
+        graph.add_node(node_type, Default::default()).unwrap()
+    }
+
+    fn link(graph: &mut CodeGraph, from: NodeId, to: NodeId, edge_type: EdgeType) {
+        graph
+            .add_edge(from, to, edge_type, Default::default())
+            .unwrap();
+    }
+
+    #[test]
+    fn test_reverse_reachability_distances_and_paths() {
+        let mut graph = CodeGraph::in_memory().unwrap();
+        let target = add(&mut graph, NodeType::Function);
+        let caller = add(&mut graph, NodeType::Function);
+        let outer = add(&mut graph, NodeType::Function);
+        let subclass = add(&mut graph, NodeType::Class);
+        let file = add(&mut graph, NodeType::CodeFile);
+        let callee = add(&mut graph, NodeType::Function);
+        link(&mut graph, caller, target, EdgeType::Calls);
+        link(&mut graph, outer, caller, EdgeType::Calls);
+        link(&mut graph, subclass, outer, EdgeType::Extends);
+        link(&mut graph, file, target, EdgeType::Contains);
+        link(&mut graph, target, callee, EdgeType::Calls);
+
+        let result = reverse_reachability(&graph, &[target], 2, 10, |_| Vec::new());
+        let reached: Vec<(NodeId, usize)> = result
+            .reached
+            .iter()
+            .map(|r| (r.node, r.distance))
+            .collect();
+        // Containment and outgoing calls do not propagate; depth stops at 2.
+        assert_eq!(reached, vec![(caller, 1), (outer, 2)]);
+        assert_eq!(
+            result.reached[1].path,
+            vec![
+                (caller, target, EdgeType::Calls),
+                (outer, caller, EdgeType::Calls)
+            ]
+        );
+
+        let deeper = reverse_reachability(&graph, &[target], 5, 10, |_| Vec::new());
+        assert_eq!(deeper.reached.last().unwrap().node, subclass);
+        assert_eq!(deeper.reached.last().unwrap().distance, 3);
+    }
+
+    #[test]
+    fn test_reverse_reachability_fan_out_and_importers() {
+        let mut graph = CodeGraph::in_memory().unwrap();
+        let target = add(&mut graph, NodeType::Function);
+        let callers: Vec<NodeId> = (0..4)
+            .map(|_| add(&mut graph, NodeType::Function))
+            .collect();
+        for &caller in &callers {
+            link(&mut graph, caller, target, EdgeType::Calls);
+        }
+        let importer = add(&mut graph, NodeType::CodeFile);
+
+        let result = reverse_reachability(&graph, &[target], 3, 2, |node| {
+            if node == target {
+                vec![importer]
+            } else {
+                Vec::new()
+            }
+        });
+        assert_eq!(result.reached.len(), 2);
+        assert_eq!(result.truncated, 3);
+
+        let result = reverse_reachability(&graph, &[target], 3, 10, |node| {
+            if node == target {
+                vec![importer]
+            } else {
+                Vec::new()
+            }
+        });
+        let import = result.reached.iter().find(|r| r.node == importer).unwrap();
+        assert_eq!(import.path, vec![(importer, target, EdgeType::Imports)]);
+    }
+}
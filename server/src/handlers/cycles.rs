@@ -0,0 +1,518 @@
+//! Import Cycle Handler - Strongly connected components of the import graph.
+//!
+//! `codegraph/findCycles` groups files by file, directory or package, runs
+//! Tarjan's SCC algorithm on the resulting import graph and reports every
+//! group of units that import each other, with the import statements that
+//! form the cycle and the edge that is cheapest to break.
+
+use crate::algorithms;
+use crate::backend::CodeGraphBackend;
+use crate::import_graph::{self, ImportGraph};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use tower_lsp::jsonrpc::Result;
+use tower_lsp::lsp_types::Url;
+
+/// Components with more internal edges than this only get a cost-based
+/// suggestion; the "does removing it break the cycle" check is skipped.
+const MAX_EDGES_FOR_BREAK_ANALYSIS: usize = 300;
+
+/// Files that mark the root of a package.
+const PACKAGE_MANIFESTS: &[&str] = &[
+    "Cargo.toml",
+    "package.json",
+    "go.mod",
+    "pyproject.toml",
+    "setup.py",
+    "setup.cfg",
+];
+
+// ==========================================
+// Cycle Types
+// ==========================================
+
+/// Unit the import graph is collapsed to before looking for cycles.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CycleGranularity {
+    #[default]
+    File,
+    Directory,
+    Package,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FindCyclesParams {
+    /// file (default), directory or package
+    pub granularity: Option<CycleGranularity>,
+    /// Only report cycles that include this file
+    pub uri: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FindCyclesResponse {
+    pub granularity: CycleGranularity,
+    pub cycles: Vec<ImportCycle>,
+    pub summary: CycleSummary,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CycleSummary {
+    pub total_cycles: usize,
+    /// Files, directories or packages that are part of some cycle
+    pub units_in_cycles: usize,
+    pub total_units: usize,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportCycle {
+    /// Files, directories or packages in the cycle
+    pub members: Vec<String>,
+    /// URIs of the files involved
+    pub files: Vec<String>,
+    /// Dependencies between members that form the cycle
+    pub edges: Vec<CycleEdge>,
+    pub suggested_break: Option<CycleBreak>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CycleEdge {
+    pub from: String,
+    pub to: String,
+    /// Import statements behind this dependency
+    pub imports: Vec<CycleImport>,
+    /// Number of imported names that would have to move (at least 1 per import)
+    pub cost: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CycleImport {
+    pub from_uri: String,
+    pub to_uri: String,
+    pub module: String,
+    /// 0-indexed line of the import statement, if found
+    pub line: Option<u32>,
+    pub symbols: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CycleBreak {
+    pub from: String,
+    pub to: String,
+    pub cost: usize,
+    /// Size of the largest cycle left after removing this dependency (0 = none)
+    pub remaining_cycle_size: usize,
+    pub reason: String,
+}
+
+// ==========================================
+// Cycle Analysis
+// ==========================================
+
+/// A dependency between two units, backed by file-level import edges.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnitEdge {
+    pub from: usize,
+    pub to: usize,
+    /// Indices into [`ImportGraph::edges`].
+    pub imports: Vec<usize>,
+    pub cost: usize,
+}
+
+/// One strongly connected component of the unit graph.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnitCycle {
+    pub members: Vec<usize>,
+    pub edges: Vec<UnitEdge>,
+    /// Index into `edges` of the suggested break, and the size of the largest
+    /// cycle left without it.
+    pub suggested_break: Option<(usize, usize)>,
+}
+
+/// Find cycles in `imports` after mapping each file to a unit via `unit_of`.
+/// Imports between files of the same unit are ignored.
+pub fn find_unit_cycles(
+    imports: &ImportGraph,
+    unit_of: &[usize],
+    unit_count: usize,
+) -> Vec<UnitCycle> {
+    let mut unit_edges: BTreeMap<(usize, usize), Vec<usize>> = BTreeMap::new();
+    for (i, edge) in imports.edges.iter().enumerate() {
+        let (from, to) = (unit_of[edge.from], unit_of[edge.to]);
+        if from != to {
+            unit_edges.entry((from, to)).or_default().push(i);
+        }
+    }
+
+    let mut adjacency = vec![Vec::new(); unit_count];
+    for &(from, to) in unit_edges.keys() {
+        adjacency[from].push(to);
+    }
+
+    let mut cycles: Vec<UnitCycle> = algorithms::cyclic_components(&adjacency)
+        .into_iter()
+        .map(|members| {
+            let edges: Vec<UnitEdge> = unit_edges
+                .iter()
+                .filter(|((from, to), _)| {
+                    members.binary_search(from).is_ok() && members.binary_search(to).is_ok()
+                })
+                .map(|(&(from, to), edge_ids)| UnitEdge {
+                    from,
+                    to,
+                    cost: edge_ids
+                        .iter()
+                        .map(|&e| imports.edges[e].symbols.len().max(1))
+                        .sum(),
+                    imports: edge_ids.clone(),
+                })
+                .collect();
+            let suggested_break = suggest_break(&members, &edges);
+            UnitCycle {
+                members,
+                edges,
+                suggested_break,
+            }
+        })
+        .collect();
+
+    cycles.sort_by(|a, b| {
+        b.members
+            .len()
+            .cmp(&a.members.len())
+            .then(a.members.cmp(&b.members))
+    });
+    cycles
+}
+
+/// Pick the dependency to remove: one that actually shrinks the cycle, with
+/// the fewest imported names, then the smallest cycle left behind.
+fn suggest_break(members: &[usize], edges: &[UnitEdge]) -> Option<(usize, usize)> {
+    if edges.len() > MAX_EDGES_FOR_BREAK_ANALYSIS {
+        return edges
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, e)| e.cost)
+            .map(|(i, _)| (i, members.len()));
+    }
+
+    let local: HashMap<usize, usize> = members.iter().enumerate().map(|(i, &m)| (m, i)).collect();
+    edges
+        .iter()
+        .enumerate()
+        .map(|(skip, edge)| {
+            let mut adjacency = vec![Vec::new(); members.len()];
+            for (i, e) in edges.iter().enumerate() {
+                if i != skip {
+                    adjacency[local[&e.from]].push(local[&e.to]);
+                }
+            }
+            let remaining = algorithms::cyclic_components(&adjacency)
+                .iter()
+                .map(Vec::len)
+                .max()
+                .unwrap_or(0);
+            (remaining >= members.len(), edge.cost, remaining, skip)
+        })
+        .min()
+        .map(|(_, _, remaining, skip)| (skip, remaining))
+}
+
+/// Root directory of the package containing `file`: the nearest ancestor
+/// with a package manifest, not above the workspace folder.
+pub fn package_root(
+    file: &Path,
+    workspace_root: Option<&Path>,
+    has_manifest: impl Fn(&Path) -> bool,
+) -> PathBuf {
+    let mut dir = file.parent();
+    while let Some(d) = dir {
+        if has_manifest(d) {
+            return d.to_path_buf();
+        }
+        if Some(d) == workspace_root {
+            break;
+        }
+        dir = d.parent();
+    }
+    workspace_root
+        .or(file.parent())
+        .map(Path::to_path_buf)
+        .unwrap_or_default()
+}
+
+/// Map every file to the file, directory or package it belongs to. Returns
+/// the sorted units and the unit index of each file.
+pub fn group_files(
+    files: &[PathBuf],
+    granularity: CycleGranularity,
+    folders: &[PathBuf],
+) -> (Vec<PathBuf>, Vec<usize>) {
+    let workspace_root = |file: &Path| folders.iter().find(|f| file.starts_with(f)).cloned();
+    let unit_paths: Vec<PathBuf> = files
+        .iter()
+        .map(|file| match granularity {
+            CycleGranularity::File => file.clone(),
+            CycleGranularity::Directory => file.parent().map(Path::to_path_buf).unwrap_or_default(),
+            CycleGranularity::Package => {
+                let root = workspace_root(file);
+                package_root(file, root.as_deref(), |dir| {
+                    PACKAGE_MANIFESTS.iter().any(|m| dir.join(m).is_file())
+                })
+            }
+        })
+        .collect();
+
+    let mut units: Vec<PathBuf> = unit_paths.clone();
+    units.sort();
+    units.dedup();
+    let unit_of: Vec<usize> = unit_paths
+        .iter()
+        .map(|p| units.binary_search(p).unwrap_or(0))
+        .collect();
+    (units, unit_of)
+}
+
+impl CodeGraphBackend {
+    /// Find import cycles at file, directory or package granularity.
+    pub async fn handle_find_cycles(&self, params: FindCyclesParams) -> Result<FindCyclesResponse> {
+        let granularity = params.granularity.unwrap_or_default();
+        let focus = match &params.uri {
+            Some(uri) => Some(
+                Url::parse(uri)
+                    .ok()
+                    .and_then(|u| u.to_file_path().ok())
+                    .ok_or_else(|| tower_lsp::jsonrpc::Error::invalid_params("Invalid URI"))?,
+            ),
+            None => None,
+        };
+
+        let folders = self.workspace_folders.read().await.clone();
+        let imports = {
+            let graph = self.graph.read().await;
+            ImportGraph::build(&graph)
+        };
+
+        let (units, unit_of) = group_files(&imports.files, granularity, &folders);
+
+        let display = |path: &Path| {
+            folders
+                .iter()
+                .find_map(|f| path.strip_prefix(f).ok())
+                .filter(|rel| !rel.as_os_str().is_empty())
+                .unwrap_or(path)
+                .display()
+                .to_string()
+        };
+        let uri_of = |path: &Path| {
+            Url::from_file_path(path)
+                .map(|u| u.to_string())
+                .unwrap_or_else(|_| path.display().to_string())
+        };
+
+        let unit_cycles = find_unit_cycles(&imports, &unit_of, units.len());
+
+        let mut cycles = Vec::new();
+        for cycle in unit_cycles {
+            let mut files: Vec<usize> = cycle
+                .edges
+                .iter()
+                .flat_map(|e| &e.imports)
+                .flat_map(|&i| [imports.edges[i].from, imports.edges[i].to])
+                .collect();
+            files.sort_unstable();
+            files.dedup();
+            if let Some(focus) = &focus {
+                if !files.iter().any(|&f| &imports.files[f] == focus) {
+                    continue;
+                }
+            }
+
+            let edges: Vec<CycleEdge> = cycle
+                .edges
+                .iter()
+                .map(|edge| CycleEdge {
+                    from: display(&units[edge.from]),
+                    to: display(&units[edge.to]),
+                    imports: edge
+                        .imports
+                        .iter()
+                        .map(|&i| {
+                            let import = &imports.edges[i];
+                            let from_path = &imports.files[import.from];
+                            CycleImport {
+                                from_uri: uri_of(from_path),
+                                to_uri: uri_of(&imports.files[import.to]),
+                                module: import.module.clone(),
+                                line: self.document_text(from_path).and_then(|text| {
+                                    import_graph::find_import_line(&text, &import.module)
+                                }),
+                                symbols: import.symbols.clone(),
+                            }
+                        })
+                        .collect(),
+                    cost: edge.cost,
+                })
+                .collect();
+
+            let suggested_break = cycle.suggested_break.map(|(i, remaining)| {
+                let edge = &edges[i];
+                let reason = if remaining == 0 {
+                    format!(
+                        "Removing {} import(s) of {} from {} breaks the cycle",
+                        edge.cost, edge.to, edge.from
+                    )
+                } else {
+                    format!(
+                        "Removing {} import(s) of {} from {} leaves a smaller cycle of {} members",
+                        edge.cost, edge.to, edge.from, remaining
+                    )
+                };
+                CycleBreak {
+                    from: edge.from.clone(),
+                    to: edge.to.clone(),
+                    cost: edge.cost,
+                    remaining_cycle_size: remaining,
+                    reason,
+                }
+            });
+
+            cycles.push(ImportCycle {
+                members: cycle.members.iter().map(|&m| display(&units[m])).collect(),
+                files: files.iter().map(|&f| uri_of(&imports.files[f])).collect(),
+                edges,
+                suggested_break,
+            });
+        }
+
+        Ok(FindCyclesResponse {
+            granularity,
+            summary: CycleSummary {
+                total_cycles: cycles.len(),
+                units_in_cycles: cycles.iter().map(|c| c.members.len()).sum(),
+                total_units: units.len(),
+            },
+            cycles,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn import_graph(files: &[&str], imports: &[(&str, &str, usize)]) -> ImportGraph {
+        let files: Vec<PathBuf> = files.iter().map(PathBuf::from).collect();
+        let raw: Vec<import_graph::RawImport> = imports
+            .iter()
+            .map(|(from, to, symbols)| {
+                (
+                    PathBuf::from(from),
+                    to.to_string(),
+                    (0..*symbols).map(|i| format!("s{i}")).collect(),
+                )
+            })
+            .collect();
+        ImportGraph::from_imports(files, &raw)
+    }
+
+    #[test]
+    fn test_file_cycle_and_cheapest_break() {
+        // a -> b -> c -> a, where b imports three names from c
+        let graph = import_graph(
+            &["/w/a.py", "/w/b.py", "/w/c.py", "/w/d.py"],
+            &[
+                ("/w/a.py", "b", 1),
+                ("/w/b.py", "c", 3),
+                ("/w/c.py", "a", 2),
+                ("/w/d.py", "a", 1),
+            ],
+        );
+        let unit_of: Vec<usize> = (0..graph.files.len()).collect();
+        let cycles = find_unit_cycles(&graph, &unit_of, graph.files.len());
+
+        assert_eq!(cycles.len(), 1);
+        let cycle = &cycles[0];
+        assert_eq!(cycle.members, vec![0, 1, 2]);
+        assert_eq!(cycle.edges.len(), 3);
+        let (edge, remaining) = cycle.suggested_break.unwrap();
+        assert_eq!((cycle.edges[edge].from, cycle.edges[edge].to), (0, 1));
+        assert_eq!(remaining, 0);
+    }
+
+    #[test]
+    fn test_break_prefers_edge_that_shrinks_cycle() {
+        // a <-> b and b <-> c: removing a cheap edge inside one loop still
+        // leaves the other, so the suggestion reports what remains.
+        let graph = import_graph(
+            &["/w/a.py", "/w/b.py", "/w/c.py"],
+            &[
+                ("/w/a.py", "b", 5),
+                ("/w/b.py", "a", 5),
+                ("/w/b.py", "c", 1),
+                ("/w/c.py", "b", 4),
+            ],
+        );
+        let unit_of: Vec<usize> = (0..graph.files.len()).collect();
+        let cycles = find_unit_cycles(&graph, &unit_of, graph.files.len());
+
+        let cycle = &cycles[0];
+        let (edge, remaining) = cycle.suggested_break.unwrap();
+        assert_eq!((cycle.edges[edge].from, cycle.edges[edge].to), (1, 2));
+        assert_eq!(remaining, 2);
+    }
+
+    #[test]
+    fn test_directory_cycle_ignores_internal_imports() {
+        let graph = import_graph(
+            &["/w/x/a.py", "/w/x/b.py", "/w/y/c.py"],
+            &[
+                ("/w/x/a.py", "b", 1),
+                ("/w/x/b.py", "a", 1),
+                ("/w/x/a.py", "y.c", 1),
+            ],
+        );
+        // x = 0, y = 1: the a <-> b loop is internal to x.
+        let cycles = find_unit_cycles(&graph, &[0, 0, 1], 2);
+        assert!(cycles.is_empty());
+
+        let graph = import_graph(
+            &["/w/x/a.py", "/w/y/c.py"],
+            &[("/w/x/a.py", "y.c", 1), ("/w/y/c.py", "x.a", 1)],
+        );
+        let cycles = find_unit_cycles(&graph, &[0, 1], 2);
+        assert_eq!(cycles.len(), 1);
+        assert_eq!(cycles[0].members, vec![0, 1]);
+    }
+
+    #[test]
+    fn test_package_root() {
+        let manifests = [PathBuf::from("/w/pkg")];
+        let has_manifest = |dir: &Path| manifests.iter().any(|m| m == dir);
+
+        assert_eq!(
+            package_root(
+                Path::new("/w/pkg/src/lib.rs"),
+                Some(Path::new("/w")),
+                has_manifest
+            ),
+            PathBuf::from("/w/pkg")
+        );
+        assert_eq!(
+            package_root(
+                Path::new("/w/tools/run.py"),
+                Some(Path::new("/w")),
+                has_manifest
+            ),
+            PathBuf::from("/w")
+        );
+    }
+}
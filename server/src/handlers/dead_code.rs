@@ -0,0 +1,618 @@
+//! Dead Code - Mark-and-sweep reachability over the symbol graph.
+//!
+//! Functions and classes are marked live starting from the root set of
+//! `entry_points` and following call, instantiation, reference and
+//! inheritance edges, plus non-call uses found in the occurrence index
+//! (`onClick={handleSave}`); a live method keeps its class alive, and a live
+//! class keeps alive the methods the runtime calls on it, such as `__init__`.
+//! What the sweep leaves unmarked is dead, even when dead symbols call each
+//! other.
+//! Dead symbols connected by such edges form a cluster that can only be
+//! removed together.
+
+use crate::algorithms::{reachable_from, weakly_connected_components};
+use crate::backend::CodeGraphBackend;
+use crate::handlers::ai_context::LocationInfo;
+use crate::handlers::entry_points::{
+    barrel_exports, is_barrel_file, Definition, EntryPoints, RootKind,
+};
+use crate::handlers::metrics::UnusedItem;
+use crate::import_graph::resolve_modules;
+use crate::index;
+use crate::occurrences::{self, OccurrenceKind};
+use codegraph::{CodeGraph, Direction, EdgeType, NodeId, NodeType};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use tower_lsp::jsonrpc::Result;
+use tower_lsp::lsp_types::Url;
+
+/// Edges along which liveness propagates.
+const LIVE_EDGES: &[EdgeType] = &[
+    EdgeType::Calls,
+    EdgeType::Invokes,
+    EdgeType::Instantiates,
+    EdgeType::References,
+    EdgeType::Uses,
+    EdgeType::Extends,
+    EdgeType::Implements,
+];
+
+/// Record an edge from node `from` to node `to` in the liveness adjacency.
+/// A live method keeps its class alive, but a live class does not keep all
+/// of its methods alive.
+fn add_live_edge(adjacency: &mut [Vec<usize>], from: usize, to: usize, edge_type: EdgeType) {
+    if LIVE_EDGES.contains(&edge_type) {
+        adjacency[from].push(to);
+    } else if edge_type == EdgeType::Contains {
+        adjacency[to].push(from);
+    }
+}
+
+/// Let each class in `members` (class, method) keep alive its methods with
+/// [`RootKind::Runtime`]. Those stop being roots themselves, so that they die
+/// with their class; runtime methods outside any class stay roots.
+fn link_runtime_methods(
+    adjacency: &mut [Vec<usize>],
+    roots: &mut [Option<RootKind>],
+    members: &[(usize, usize)],
+) {
+    for &(class, method) in members {
+        if roots[method] == Some(RootKind::Runtime) {
+            adjacency[class].push(method);
+        }
+    }
+    for &(_, method) in members {
+        if roots[method] == Some(RootKind::Runtime) {
+            roots[method] = None;
+        }
+    }
+}
+
+/// Dead symbols that only reference each other.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeadCluster {
+    pub size: usize,
+    /// Qualified names of the members
+    pub members: Vec<String>,
+    /// URIs of the files the members are defined in
+    pub files: Vec<String>,
+    /// Lines of code the cluster spans
+    pub lines: u32,
+}
+
+/// Result of the sweep, indexed by position in `nodes`.
+#[derive(Debug, Default)]
+pub struct Liveness {
+    pub nodes: Vec<NodeId>,
+    pub files: Vec<Option<PathBuf>>,
+    pub roots: Vec<Option<RootKind>>,
+    /// Decorator or attribute no recognizer knows, per non-root node
+    pub annotations: Vec<Option<String>>,
+    pub live: Vec<bool>,
+    /// Incoming call edges and non-call uses, from live and dead users alike
+    pub callers: Vec<usize>,
+    /// Dead clusters, largest first
+    pub clusters: Vec<Vec<usize>>,
+    /// Cluster of each dead node
+    pub cluster_of: Vec<Option<usize>>,
+}
+
+impl Liveness {
+    /// Mark and sweep over a projected graph. `adjacency` holds the edges
+    /// liveness propagates along.
+    pub fn sweep(
+        nodes: Vec<NodeId>,
+        files: Vec<Option<PathBuf>>,
+        roots: Vec<Option<RootKind>>,
+        annotations: Vec<Option<String>>,
+        callers: Vec<usize>,
+        adjacency: &[Vec<usize>],
+    ) -> Self {
+        let root_indices: Vec<usize> = roots
+            .iter()
+            .enumerate()
+            .filter(|(_, kind)| kind.is_some())
+            .map(|(i, _)| i)
+            .collect();
+        let live = reachable_from(adjacency, &root_indices);
+        let dead: Vec<bool> = live.iter().map(|l| !l).collect();
+
+        let mut clusters = weakly_connected_components(adjacency, &dead);
+        clusters.sort_by_key(|c| std::cmp::Reverse(c.len()));
+        let mut cluster_of = vec![None; nodes.len()];
+        for (index, cluster) in clusters.iter().enumerate() {
+            for &member in cluster {
+                cluster_of[member] = Some(index);
+            }
+        }
+
+        Self {
+            nodes,
+            files,
+            roots,
+            annotations,
+            live,
+            callers,
+            clusters,
+            cluster_of,
+        }
+    }
+
+    /// Confidence that node `i` is unused, with the reason, or `None` when
+    /// it is live.
+    pub fn assess(&self, i: usize) -> Option<(f64, String)> {
+        let callers = self.callers[i];
+        if let Some(cluster) = self.cluster_of[i] {
+            Some(if let Some(annotation) = &self.annotations[i] {
+                (
+                    0.5,
+                    format!("Unreachable, but '{annotation}' may register it with a framework"),
+                )
+            } else if callers == 0 {
+                (0.9, "No callers found in codebase".to_string())
+            } else {
+                (
+                    0.85,
+                    format!(
+                        "Only reachable from dead code (cluster of {})",
+                        self.clusters[cluster].len()
+                    ),
+                )
+            })
+        } else if callers == 0 && self.roots[i] == Some(RootKind::PublicApi) {
+            // Nothing in the workspace calls it, but external code may.
+            Some((0.4, "Exported but no internal callers found".to_string()))
+        } else {
+            None
+        }
+    }
+}
+
+/// Root classification per symbol, kept between sweeps so that a sweep after
+/// an edit only reads the files whose symbols changed.
+#[derive(Debug, Default)]
+pub struct RootCache {
+    /// Root kind and unrecognized annotation from the symbol's definition
+    roots: HashMap<NodeId, (Option<RootKind>, Option<String>)>,
+    reexports: Option<HashMap<PathBuf, Option<HashSet<String>>>>,
+}
+
+impl RootCache {
+    /// Forget what depends on the text of `path`. Its symbols are reparsed
+    /// under new node IDs, so only barrels affect other files' entries.
+    pub fn file_changed(&mut self, path: &Path) {
+        if is_barrel_file(path) {
+            self.roots.clear();
+            self.reexports = None;
+        }
+    }
+}
+
+fn in_scope(file: &Option<PathBuf>, scope: Option<&Path>) -> bool {
+    scope.is_none_or(|scope| file.as_deref() == Some(scope))
+}
+
+impl CodeGraphBackend {
+    /// Load the entry point config of every workspace folder, plus the
+    /// symbol globs given with the request.
+    pub(crate) async fn load_entry_points(&self, symbols: &[String]) -> Result<EntryPoints> {
+        let folders = self.workspace_folders.read().await.clone();
+        let (entry_points, errors) = EntryPoints::load(&folders);
+        if let Some(error) = errors.into_iter().next() {
+            return Err(error.into());
+        }
+        entry_points
+            .with_symbols(symbols)
+            .map_err(tower_lsp::jsonrpc::Error::invalid_params)
+    }
+
+    /// Mark every function and class reachable from the entry points.
+    pub(crate) fn liveness(&self, graph: &CodeGraph, entry_points: &EntryPoints) -> Liveness {
+        self.cached_liveness(graph, entry_points, &mut RootCache::default())
+    }
+
+    /// [`Self::liveness`], reusing the root classification in `cache` for
+    /// symbols it has seen and adding the others.
+    pub(crate) fn cached_liveness(
+        &self,
+        graph: &CodeGraph,
+        entry_points: &EntryPoints,
+        cache: &mut RootCache,
+    ) -> Liveness {
+        let mut nodes = Vec::new();
+        for node_type in [NodeType::Function, NodeType::Class] {
+            nodes.extend(
+                graph
+                    .query()
+                    .node_type(node_type)
+                    .execute()
+                    .unwrap_or_default(),
+            );
+        }
+        let index: HashMap<NodeId, usize> =
+            nodes.iter().enumerate().map(|(i, &id)| (id, i)).collect();
+
+        let mut sources: HashMap<PathBuf, Option<String>> = HashMap::new();
+        let reexports = cache
+            .reexports
+            .get_or_insert_with(|| self.barrel_reexports(&mut sources));
+        let mut files = Vec::with_capacity(nodes.len());
+        let mut roots = Vec::with_capacity(nodes.len());
+        let mut annotations = Vec::with_capacity(nodes.len());
+        let mut callers = vec![0; nodes.len()];
+        let mut adjacency = vec![Vec::new(); nodes.len()];
+        let mut members = Vec::new();
+
+        for (i, &node_id) in nodes.iter().enumerate() {
+            let file = self.symbol_index.find_file_for_node(node_id);
+            let (mut root, annotation) = cache
+                .roots
+                .entry(node_id)
+                .or_insert_with(|| {
+                    let mut annotation = None;
+                    let root = file.as_ref().and_then(|file| {
+                        let node = graph.get_node(node_id).ok()?;
+                        let name = node.properties.get_string("name")?;
+                        let range = self.symbol_index.get_node_range(file, node_id)?;
+                        let text = sources
+                            .entry(file.clone())
+                            .or_insert_with(|| self.document_text(file));
+                        let lines: Vec<&str> = text
+                            .as_deref()
+                            .map(|t| t.lines().collect())
+                            .unwrap_or_default();
+                        let start = (range.start_line.saturating_sub(1) as usize).min(lines.len());
+                        let reexported = reexports.get(file).is_some_and(|names| {
+                            names
+                                .as_ref()
+                                .is_none_or(|names| names.contains(index::short_name(name)))
+                        });
+                        let definition = Definition {
+                            name,
+                            file,
+                            line: lines.get(start).copied().unwrap_or(""),
+                            preceding: &lines[..start],
+                            exported: node.properties.get_bool("exported").unwrap_or(false),
+                            reexported,
+                        };
+                        let root = entry_points.root_kind(&definition);
+                        if root.is_none() {
+                            annotation = entry_points.unrecognized_annotation(&definition);
+                        }
+                        root
+                    });
+                    (root, annotation)
+                })
+                .clone();
+
+            for (_, target, edge_type) in
+                self.get_connected_edges(graph, node_id, Direction::Outgoing)
+            {
+                if let Some(&j) = index.get(&target) {
+                    add_live_edge(&mut adjacency, i, j, edge_type);
+                    if edge_type == EdgeType::Contains {
+                        members.push((i, j));
+                    }
+                }
+            }
+
+            for (source, _, edge_type) in
+                self.get_connected_edges(graph, node_id, Direction::Incoming)
+            {
+                if edge_type != EdgeType::Calls {
+                    continue;
+                }
+                callers[i] += 1;
+                if root.is_none() && !index.contains_key(&source) {
+                    root = Some(RootKind::ModuleCode);
+                }
+            }
+
+            files.push(file);
+            roots.push(root);
+            annotations.push(annotation);
+        }
+        cache.roots.retain(|node_id, _| index.contains_key(node_id));
+        link_runtime_methods(&mut adjacency, &mut roots, &members);
+
+        // Resolving every occurrence in the workspace is slow, and only uses
+        // of symbols the sweep would report can change its result.
+        let root_indices: Vec<usize> = (0..nodes.len()).filter(|&i| roots[i].is_some()).collect();
+        let live = reachable_from(&adjacency, &root_indices);
+        let candidates: Vec<NodeId> = nodes
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| {
+                !live[i] || (callers[i] == 0 && roots[i] == Some(RootKind::PublicApi))
+            })
+            .map(|(_, &id)| id)
+            .collect();
+        for (user, used) in self.non_call_uses(graph, &candidates, &index) {
+            callers[used] += 1;
+            adjacency[user].push(used);
+        }
+
+        Liveness::sweep(nodes, files, roots, annotations, callers, &adjacency)
+    }
+
+    /// Uses of symbols named like one of `candidates` that are not calls, such
+    /// as a handler passed as a JSX prop, as (using symbol, used symbol)
+    /// indices from `index`. Uses at module level are skipped: they are mostly
+    /// imports.
+    fn non_call_uses(
+        &self,
+        graph: &CodeGraph,
+        candidates: &[NodeId],
+        index: &HashMap<NodeId, usize>,
+    ) -> Vec<(usize, usize)> {
+        let names: HashSet<String> = candidates
+            .iter()
+            .filter_map(|&id| {
+                let node = graph.get_node(id).ok()?;
+                let name = node.properties.get_string("name")?;
+                Some(index::short_name(name).to_string())
+            })
+            .collect();
+
+        let mut uses = Vec::new();
+        for path in self.symbol_index.files() {
+            for occurrence in self.occurrence_index.file_occurrences(&path) {
+                if occurrence.kind != OccurrenceKind::Read || !names.contains(&*occurrence.name) {
+                    continue;
+                }
+                let Some(&used) =
+                    occurrences::resolve(graph, &self.symbol_index, &path, &occurrence)
+                        .and_then(|id| index.get(&id))
+                else {
+                    continue;
+                };
+                let Some(&user) = self
+                    .symbol_index
+                    .find_at_position(
+                        &path,
+                        occurrence.range.start.line + 1,
+                        occurrence.range.start.character,
+                    )
+                    .and_then(|id| index.get(&id))
+                else {
+                    continue;
+                };
+                if user != used {
+                    uses.push((user, used));
+                }
+            }
+        }
+        uses
+    }
+
+    /// Files re-exported by `index.ts` barrels, with the re-exported names or
+    /// `None` when everything is re-exported.
+    fn barrel_reexports(
+        &self,
+        sources: &mut HashMap<PathBuf, Option<String>>,
+    ) -> HashMap<PathBuf, Option<HashSet<String>>> {
+        let files = self.symbol_index.files();
+        let mut imports = Vec::new();
+        let mut names = Vec::new();
+        for barrel in files.iter().filter(|f| is_barrel_file(f)) {
+            let text = sources
+                .entry(barrel.clone())
+                .or_insert_with(|| self.document_text(barrel));
+            for (module, exported) in text.as_deref().map(barrel_exports).unwrap_or_default() {
+                imports.push((barrel.clone(), module));
+                names.push(exported);
+            }
+        }
+
+        let mut reexports: HashMap<PathBuf, Option<HashSet<String>>> = HashMap::new();
+        for (targets, exported) in resolve_modules(&imports, &files).into_iter().zip(names) {
+            for target in targets {
+                match (
+                    reexports.entry(target).or_insert(Some(HashSet::new())),
+                    &exported,
+                ) {
+                    (entry @ Some(_), None) => *entry = None,
+                    (Some(known), Some(exported)) => known.extend(exported.iter().cloned()),
+                    (None, _) => {}
+                }
+            }
+        }
+        reexports
+    }
+
+    /// Dead functions and classes in `scope` (the whole workspace if `None`),
+    /// plus roots that nothing inside the workspace calls.
+    pub(crate) fn unreachable_items(
+        &self,
+        graph: &CodeGraph,
+        liveness: &Liveness,
+        scope: Option<&Path>,
+        include_tests: bool,
+        min_confidence: f64,
+    ) -> Vec<UnusedItem> {
+        let ranks = self.symbol_ranks(graph);
+        let mut sources = HashMap::new();
+        let mut items = Vec::new();
+        for (i, &node_id) in liveness.nodes.iter().enumerate() {
+            if !in_scope(&liveness.files[i], scope) {
+                continue;
+            }
+            let Ok(node) = graph.get_node(node_id) else {
+                continue;
+            };
+            let name = node.properties.get_string("name").unwrap_or("");
+
+            let callers = liveness.callers[i];
+            let Some((confidence, reason)) = liveness.assess(i) else {
+                continue;
+            };
+            if confidence < min_confidence {
+                continue;
+            }
+
+            // Skip test functions unless requested
+            if !include_tests && self.is_test_symbol(graph, node_id, &mut sources) {
+                continue;
+            }
+
+            let item_type = match node.node_type {
+                NodeType::Class => "class",
+                _ => "function",
+            };
+            if let Ok(location) = self.node_to_location(graph, node_id) {
+                items.push(UnusedItem {
+                    item_type: item_type.to_string(),
+                    name: name.to_string(),
+                    location: LocationInfo {
+                        uri: location.uri.to_string(),
+                        range: location.range,
+                    },
+                    confidence,
+                    reason,
+                    // Symbols still called by dead code go with their cluster.
+                    safe_to_remove: callers == 0 && confidence > 0.8,
+                    importance: ranks.score(node_id),
+                });
+            }
+        }
+        items
+    }
+
+    /// Dead clusters with at least one member in `scope`.
+    pub(crate) fn dead_clusters(
+        &self,
+        graph: &CodeGraph,
+        liveness: &Liveness,
+        scope: Option<&Path>,
+    ) -> Vec<DeadCluster> {
+        liveness
+            .clusters
+            .iter()
+            .filter(|cluster| cluster.iter().any(|&i| in_scope(&liveness.files[i], scope)))
+            .map(|cluster| {
+                let nodes: Vec<&codegraph::Node> = cluster
+                    .iter()
+                    .filter_map(|&i| graph.get_node(liveness.nodes[i]).ok())
+                    .collect();
+                let mut files: Vec<String> = cluster
+                    .iter()
+                    .filter_map(|&i| liveness.files[i].as_ref())
+                    .filter_map(|f| Url::from_file_path(f).ok())
+                    .map(|u| u.to_string())
+                    .collect();
+                files.sort();
+                files.dedup();
+                DeadCluster {
+                    size: cluster.len(),
+                    members: nodes
+                        .iter()
+                        .filter_map(|n| n.properties.get_string("name"))
+                        .map(str::to_string)
+                        .collect(),
+                    files,
+                    lines: nodes
+                        .iter()
+                        .map(|n| {
+                            let start = n.properties.get_int("line_start").unwrap_or(0) as u32;
+                            let end = n.properties.get_int("line_end").unwrap_or(0) as u32;
+                            end.saturating_sub(start) + 1
+                        })
+                        .sum(),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sweep_finds_dead_cycles() {
+        // main -> a -> b; c <-> d is a dead cycle; e -> b is dead but calls live code.
+        let nodes: Vec<NodeId> = (0..6).collect();
+        let adjacency = vec![vec![1], vec![2], vec![], vec![4], vec![3], vec![2]];
+        let roots = vec![Some(RootKind::Main), None, None, None, None, None];
+        let callers = vec![0, 1, 2, 1, 1, 0];
+        let liveness = Liveness::sweep(
+            nodes,
+            vec![None; 6],
+            roots,
+            vec![None; 6],
+            callers,
+            &adjacency,
+        );
+
+        assert_eq!(liveness.live, vec![true, true, true, false, false, false]);
+        assert_eq!(liveness.clusters, vec![vec![3, 4], vec![5]]);
+        assert_eq!(liveness.cluster_of[3], Some(0));
+        assert_eq!(liveness.cluster_of[5], Some(1));
+        assert_eq!(liveness.cluster_of[0], None);
+    }
+
+    #[test]
+    fn test_uncalled_method_of_live_class_is_dead() {
+        // main calls Parser.parse; Parser.unused is never called.
+        let (main, class, called, uncalled) = (0, 1, 2, 3);
+        let mut adjacency = vec![Vec::new(); 4];
+        add_live_edge(&mut adjacency, main, called, EdgeType::Calls);
+        add_live_edge(&mut adjacency, class, called, EdgeType::Contains);
+        add_live_edge(&mut adjacency, class, uncalled, EdgeType::Contains);
+        let liveness = Liveness::sweep(
+            (0..4).collect(),
+            vec![None; 4],
+            vec![Some(RootKind::Main), None, None, None],
+            vec![None; 4],
+            vec![0, 0, 1, 0],
+            &adjacency,
+        );
+
+        assert_eq!(liveness.live, vec![true, true, true, false]);
+        assert!(liveness.assess(called).is_none());
+        assert!(liveness.assess(class).is_none());
+        let (confidence, reason) = liveness.assess(uncalled).unwrap();
+        assert_eq!(confidence, 0.9);
+        assert_eq!(reason, "No callers found in codebase");
+    }
+
+    #[test]
+    fn test_runtime_methods_live_with_their_class() {
+        // main instantiates Parser, whose __init__ nothing calls; Old and its
+        // __init__ are unused; a Display impl on a foreign type has no class.
+        let (main, parser, init, old, old_init, foreign) = (0, 1, 2, 3, 4, 5);
+        let mut adjacency = vec![Vec::new(); 6];
+        let mut roots = vec![
+            Some(RootKind::Main),
+            None,
+            Some(RootKind::Runtime),
+            None,
+            Some(RootKind::Runtime),
+            Some(RootKind::Runtime),
+        ];
+        add_live_edge(&mut adjacency, main, parser, EdgeType::Instantiates);
+        add_live_edge(&mut adjacency, parser, init, EdgeType::Contains);
+        add_live_edge(&mut adjacency, old, old_init, EdgeType::Contains);
+        link_runtime_methods(
+            &mut adjacency,
+            &mut roots,
+            &[(parser, init), (old, old_init)],
+        );
+        let liveness = Liveness::sweep(
+            (0..6).collect(),
+            vec![None; 6],
+            roots,
+            vec![None; 6],
+            vec![0; 6],
+            &adjacency,
+        );
+
+        assert_eq!(liveness.live, vec![true, true, true, false, false, true]);
+        assert!(liveness.assess(init).is_none());
+        assert!(liveness.assess(foreign).is_none());
+        assert_eq!(liveness.assess(old_init).unwrap().0, 0.9);
+        assert_eq!(liveness.cluster_of[old], liveness.cluster_of[old_init]);
+    }
+}
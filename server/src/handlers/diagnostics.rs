@@ -0,0 +1,832 @@
+//! Diagnostics Handler - Publish analysis findings as LSP diagnostics.
+//!
+//! Complexity hotspots, unused functions, import cycles and layer violations
+//! are surfaced in the Problems panel. Each finding has its own diagnostic
+//! code, a configurable severity and can be switched off individually.
+
+use crate::backend::CodeGraphBackend;
+use crate::handlers::architecture::Architecture;
+use crate::handlers::dead_code::{Liveness, RootCache};
+use crate::handlers::entry_points::EntryPoints;
+use crate::handlers::metrics::ComplexityParams;
+use crate::import_graph::{file_imports, find_import_line, ImportGraph};
+use codegraph::{CodeGraph, NodeType};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tower_lsp::lsp_types::{
+    Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, DiagnosticTag, Location,
+    NumberOrString, Position, Range, Url,
+};
+
+/// Source reported on every CodeGraph diagnostic.
+pub const DIAGNOSTIC_SOURCE: &str = "codegraph";
+
+/// Diagnostic codes, one per kind of finding.
+pub mod codes {
+    pub const COMPLEXITY: &str = "complexity";
+    pub const UNUSED_FUNCTION: &str = "unused-function";
+    pub const IMPORT_CYCLE: &str = "import-cycle";
+    pub const LAYER_VIOLATION: &str = "layer-violation";
+}
+
+// ==========================================
+// Configuration Types
+// ==========================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+    Information,
+    Hint,
+}
+
+impl From<Severity> for DiagnosticSeverity {
+    fn from(severity: Severity) -> Self {
+        match severity {
+            Severity::Error => DiagnosticSeverity::ERROR,
+            Severity::Warning => DiagnosticSeverity::WARNING,
+            Severity::Information => DiagnosticSeverity::INFORMATION,
+            Severity::Hint => DiagnosticSeverity::HINT,
+        }
+    }
+}
+
+/// Which documents get diagnostics.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DiagnosticsScope {
+    /// Only documents currently open in the editor.
+    #[default]
+    OpenFiles,
+    /// Every indexed file in the workspace.
+    Workspace,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DiagnosticsConfig {
+    pub enabled: bool,
+    pub scope: DiagnosticsScope,
+    pub complexity: ComplexityRule,
+    pub unused_code: UnusedCodeRule,
+    pub import_cycles: DiagnosticRule,
+    pub layer_violations: DiagnosticRule,
+}
+
+impl Default for DiagnosticsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            scope: DiagnosticsScope::OpenFiles,
+            complexity: ComplexityRule::default(),
+            unused_code: UnusedCodeRule::default(),
+            import_cycles: DiagnosticRule::default(),
+            layer_violations: DiagnosticRule::default(),
+        }
+    }
+}
+
+impl DiagnosticsConfig {
+    /// Read the configuration from client settings.
+    ///
+    /// Accepts both `{"codegraph": {"diagnostics": ...}}` (workspace/didChangeConfiguration)
+    /// and `{"diagnostics": ...}` (initializationOptions).
+    pub fn from_settings(settings: &serde_json::Value) -> Option<Self> {
+        let section = settings
+            .get("codegraph")
+            .unwrap_or(settings)
+            .get("diagnostics")?;
+        serde_json::from_value(section.clone())
+            .map_err(|e| tracing::warn!("Invalid diagnostics configuration: {}", e))
+            .ok()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DiagnosticRule {
+    pub enabled: bool,
+    pub severity: Severity,
+}
+
+impl Default for DiagnosticRule {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            severity: Severity::Warning,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ComplexityRule {
+    pub enabled: bool,
+    pub severity: Severity,
+    /// Report functions with cyclomatic complexity above this value
+    pub threshold: u32,
+}
+
+impl Default for ComplexityRule {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            severity: Severity::Warning,
+            threshold: 10,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct UnusedCodeRule {
+    pub enabled: bool,
+    pub severity: Severity,
+    /// Only report unused functions at or above this confidence
+    pub min_confidence: f64,
+}
+
+impl Default for UnusedCodeRule {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            severity: Severity::Hint,
+            min_confidence: 0.8,
+        }
+    }
+}
+
+// ==========================================
+// Import Findings
+// ==========================================
+
+/// Returns false when a file in layer `from` must not import a file in layer `to`.
+///
+/// Dependencies should point inwards: presentation -> service -> domain, with
+/// infrastructure depending on the domain and nothing depending on tests.
+/// Applies to workspace folders without `.codegraph/architecture.toml`.
+pub fn layer_dependency_allowed(from: &str, to: &str) -> bool {
+    const ENTRY: &[&str] = &["controller", "presentation", "handler"];
+    const INFRA: &[&str] = &["repository", "persistence", "infrastructure", "client"];
+    const TESTS: &[&str] = &["test", "test_support"];
+
+    if from == to {
+        return true;
+    }
+    if TESTS.contains(&to) {
+        return TESTS.contains(&from);
+    }
+    match from {
+        "domain" | "aggregate" | "value_object" => {
+            !(ENTRY.contains(&to) || INFRA.contains(&to) || to == "service")
+        }
+        "repository" | "persistence" | "service" => !ENTRY.contains(&to),
+        "utility" => !(ENTRY.contains(&to) || to == "service"),
+        _ => true,
+    }
+}
+
+/// Workspace-wide import analysis shared by all files in one publish pass.
+pub(crate) struct ImportFindings {
+    pub(crate) graph: ImportGraph,
+    adjacency: Vec<Vec<usize>>,
+    /// File index -> index into `cycles`
+    cycle_of: HashMap<usize, usize>,
+    cycles: Vec<Vec<usize>>,
+    layers: Vec<Option<String>>,
+    architecture: Arc<Architecture>,
+}
+
+impl ImportFindings {
+    fn new(graph: ImportGraph, architecture: Arc<Architecture>) -> Self {
+        let adjacency = graph.adjacency();
+        let cycles = graph.cycles();
+        let cycle_of = cycles
+            .iter()
+            .enumerate()
+            .flat_map(|(c, members)| members.iter().map(move |&m| (m, c)))
+            .collect();
+        let layers = graph
+            .files
+            .iter()
+            .map(|file| architecture.layer_of(file))
+            .collect();
+
+        Self {
+            graph,
+            adjacency,
+            cycle_of,
+            cycles,
+            layers,
+            architecture,
+        }
+    }
+
+    /// Shortest import chain `from -> to -> ... -> from` inside their shared cycle.
+    fn cycle_path(&self, from: usize, to: usize) -> Vec<usize> {
+        let Some(&cycle) = self.cycle_of.get(&from) else {
+            return Vec::new();
+        };
+        let members: HashSet<usize> = self.cycles[cycle].iter().copied().collect();
+
+        let mut previous: HashMap<usize, usize> = HashMap::new();
+        let mut queue = VecDeque::from([to]);
+        let mut visited = HashSet::from([to]);
+        while let Some(node) = queue.pop_front() {
+            if node == from {
+                break;
+            }
+            for &next in &self.adjacency[node] {
+                if members.contains(&next) && visited.insert(next) {
+                    previous.insert(next, node);
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        let mut path = vec![from];
+        let mut current = from;
+        while current != to {
+            match previous.get(&current) {
+                Some(&p) => {
+                    path.push(p);
+                    current = p;
+                }
+                None => return Vec::new(),
+            }
+        }
+        path.push(from);
+        path.reverse();
+        path
+    }
+
+    /// Files whose import diagnostics can change when `file` changes.
+    fn neighbors(&self, file: usize) -> HashSet<usize> {
+        let mut related: HashSet<usize> = self
+            .graph
+            .imports_of(file)
+            .map(|e| e.to)
+            .chain(self.graph.importers_of(file).map(|e| e.from))
+            .collect();
+        if let Some(&cycle) = self.cycle_of.get(&file) {
+            related.extend(self.cycles[cycle].iter().copied());
+        }
+        related
+    }
+}
+
+fn display_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string_lossy().to_string())
+}
+
+/// Range covering a whole line, used when only the line is known.
+fn line_range(line: u32) -> Range {
+    Range {
+        start: Position { line, character: 0 },
+        end: Position {
+            line,
+            character: u32::MAX,
+        },
+    }
+}
+
+fn new_diagnostic(range: Range, severity: Severity, code: &str, message: String) -> Diagnostic {
+    Diagnostic {
+        range,
+        severity: Some(severity.into()),
+        code: Some(NumberOrString::String(code.to_string())),
+        source: Some(DIAGNOSTIC_SOURCE.to_string()),
+        message,
+        ..Default::default()
+    }
+}
+
+/// Workspace-wide analyses behind the per-file diagnostics, kept between
+/// publish passes. A reindex or configuration change drops them; after an
+/// edit only the edited file's imports are resolved again and only its
+/// symbols are reclassified for the liveness sweep.
+#[derive(Default)]
+pub struct WorkspaceFindings {
+    imports: Option<Arc<ImportFindings>>,
+    /// Files reparsed since `imports` was built
+    changed: HashSet<PathBuf>,
+    entry_points: Option<Arc<EntryPoints>>,
+    roots: RootCache,
+    liveness: Option<Arc<Liveness>>,
+    /// `(node_count, edge_count)` of the graph `imports` and `liveness` saw;
+    /// a difference nobody reported comes from the file watcher
+    imports_stamp: (usize, usize),
+    liveness_stamp: (usize, usize),
+}
+
+fn graph_stamp(graph: &CodeGraph) -> (usize, usize) {
+    (graph.node_count(), graph.edge_count())
+}
+
+// ==========================================
+// Publishing
+// ==========================================
+
+impl CodeGraphBackend {
+    /// Replace the diagnostics configuration and republish everything.
+    pub async fn update_diagnostics_config(&self, config: DiagnosticsConfig) {
+        *self.diagnostics_config.write().await = config;
+        self.invalidate_workspace_findings().await;
+        self.publish_all_diagnostics().await;
+    }
+
+    /// Drop the cached workspace findings, e.g. after a reindex.
+    pub(crate) async fn invalidate_workspace_findings(&self) {
+        *self.workspace_findings.lock().await = WorkspaceFindings::default();
+    }
+
+    /// Note that `path` was reparsed, so its share of the cached findings
+    /// is refreshed before the next publish pass.
+    pub(crate) async fn workspace_file_changed(&self, path: &Path) {
+        let mut findings = self.workspace_findings.lock().await;
+        findings.changed.insert(path.to_path_buf());
+        findings.roots.file_changed(path);
+        findings.liveness = None;
+    }
+
+    /// Publish diagnostics for every document in the configured scope and
+    /// clear them everywhere else.
+    pub async fn publish_all_diagnostics(&self) {
+        let targets = self.diagnostics_targets().await;
+        let stale: Vec<Url> = self
+            .published_diagnostics
+            .iter()
+            .map(|entry| entry.key().clone())
+            .filter(|uri| !targets.contains(uri))
+            .collect();
+        for uri in stale {
+            self.clear_diagnostics(&uri).await;
+        }
+
+        let targets: Vec<Url> = targets.into_iter().collect();
+        self.publish_diagnostics(&targets).await;
+    }
+
+    /// Recompute diagnostics after `uri` was saved: the file itself, files it
+    /// imports or is imported by, and files with import-cycle findings that the
+    /// change may have resolved.
+    pub async fn publish_diagnostics_after_save(&self, uri: &Url) {
+        let config = self.diagnostics_config.read().await.clone();
+        if !config.enabled {
+            return;
+        }
+        let in_scope = self.diagnostics_targets().await;
+
+        let mut targets: HashSet<Url> = HashSet::new();
+        if in_scope.contains(uri) {
+            targets.insert(uri.clone());
+        }
+
+        if config.import_cycles.enabled || config.layer_violations.enabled {
+            let findings = self.import_findings().await;
+            if let Some(file) = uri
+                .to_file_path()
+                .ok()
+                .and_then(|p| findings.graph.index_of(&p))
+            {
+                for neighbor in findings.neighbors(file) {
+                    if let Ok(neighbor_uri) = Url::from_file_path(&findings.graph.files[neighbor]) {
+                        targets.insert(neighbor_uri);
+                    }
+                }
+            }
+            for entry in self.published_diagnostics.iter() {
+                let has_cycle = entry.value().iter().any(|d| {
+                    d.code == Some(NumberOrString::String(codes::IMPORT_CYCLE.to_string()))
+                });
+                if has_cycle {
+                    targets.insert(entry.key().clone());
+                }
+            }
+        }
+
+        let targets: Vec<Url> = targets
+            .into_iter()
+            .filter(|t| in_scope.contains(t))
+            .collect();
+        self.publish_diagnostics(&targets).await;
+    }
+
+    /// Compute and publish diagnostics for the given documents.
+    pub async fn publish_diagnostics(&self, uris: &[Url]) {
+        let config = self.diagnostics_config.read().await.clone();
+        if !config.enabled {
+            for uri in uris {
+                self.clear_diagnostics(uri).await;
+            }
+            return;
+        }
+
+        let findings = if config.import_cycles.enabled || config.layer_violations.enabled {
+            Some(self.import_findings().await)
+        } else {
+            None
+        };
+        let liveness = if config.unused_code.enabled {
+            Some(self.unused_code_liveness().await)
+        } else {
+            None
+        };
+
+        for uri in uris {
+            let diagnostics = self
+                .compute_diagnostics(uri, &config, findings.as_deref(), liveness.as_deref())
+                .await;
+            if diagnostics.is_empty() {
+                self.clear_diagnostics(uri).await;
+            } else {
+                self.published_diagnostics
+                    .insert(uri.clone(), diagnostics.clone());
+                self.client
+                    .publish_diagnostics(uri.clone(), diagnostics, None)
+                    .await;
+            }
+        }
+    }
+
+    /// Remove any CodeGraph diagnostics shown for `uri`.
+    pub async fn clear_diagnostics(&self, uri: &Url) {
+        if self.published_diagnostics.remove(uri).is_some() {
+            self.client
+                .publish_diagnostics(uri.clone(), Vec::new(), None)
+                .await;
+        }
+    }
+
+    /// Documents that should carry diagnostics under the current scope.
+    async fn diagnostics_targets(&self) -> HashSet<Url> {
+        let scope = self.diagnostics_config.read().await.scope;
+        let mut targets: HashSet<Url> = self
+            .open_documents
+            .iter()
+            .map(|entry| entry.key().clone())
+            .collect();
+        if scope == DiagnosticsScope::Workspace {
+            targets.extend(self.file_cache.iter().map(|entry| entry.key().clone()));
+        }
+        targets
+    }
+
+    /// Import analysis of the current graph, rebuilt only for files reparsed
+    /// since the last call.
+    pub(crate) async fn import_findings(&self) -> Arc<ImportFindings> {
+        let mut findings = self.workspace_findings.lock().await;
+        let architecture = match &findings.imports {
+            Some(imports) => Arc::clone(&imports.architecture),
+            None => Arc::new(self.load_architecture().await.0),
+        };
+        let graph = self.graph.read().await;
+        let stamp = graph_stamp(&graph);
+
+        let import_graph = match findings.imports.take() {
+            Some(imports) if !findings.changed.is_empty() => {
+                let mut import_graph = imports.graph.clone();
+                let mut updated = true;
+                for path in findings.changed.drain() {
+                    let raw = graph
+                        .query()
+                        .node_type(NodeType::CodeFile)
+                        .property("path", path.to_string_lossy().to_string())
+                        .execute()
+                        .unwrap_or_default()
+                        .into_iter()
+                        .flat_map(|file_id| file_imports(&graph, file_id, &path))
+                        .collect::<Vec<_>>();
+                    updated &= import_graph.update_file(&path, &raw);
+                }
+                if updated {
+                    import_graph
+                } else {
+                    ImportGraph::build(&graph)
+                }
+            }
+            Some(imports) if findings.imports_stamp == stamp => {
+                findings.imports = Some(Arc::clone(&imports));
+                return imports;
+            }
+            _ => {
+                findings.changed.clear();
+                ImportGraph::build(&graph)
+            }
+        };
+
+        let imports = Arc::new(ImportFindings::new(import_graph, architecture));
+        findings.imports = Some(Arc::clone(&imports));
+        findings.imports_stamp = stamp;
+        imports
+    }
+
+    pub(crate) async fn unused_code_liveness(&self) -> Arc<Liveness> {
+        let mut findings = self.workspace_findings.lock().await;
+        let entry_points = match &findings.entry_points {
+            Some(entry_points) => Arc::clone(entry_points),
+            None => {
+                let folders = self.workspace_folders.read().await.clone();
+                let (entry_points, errors) = EntryPoints::load(&folders);
+                for error in &errors {
+                    tracing::warn!("Ignoring entry point config: {}", error);
+                }
+                let entry_points = Arc::new(entry_points);
+                findings.entry_points = Some(Arc::clone(&entry_points));
+                entry_points
+            }
+        };
+        let graph = self.graph.read().await;
+        let stamp = graph_stamp(&graph);
+        if let Some(liveness) = &findings.liveness {
+            if findings.liveness_stamp == stamp {
+                return Arc::clone(liveness);
+            }
+        }
+
+        let liveness = Arc::new(self.cached_liveness(&graph, &entry_points, &mut findings.roots));
+        findings.liveness = Some(Arc::clone(&liveness));
+        findings.liveness_stamp = stamp;
+        liveness
+    }
+
+    async fn compute_diagnostics(
+        &self,
+        uri: &Url,
+        config: &DiagnosticsConfig,
+        findings: Option<&ImportFindings>,
+        liveness: Option<&Liveness>,
+    ) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+
+        if config.complexity.enabled {
+            let params = ComplexityParams {
+                uri: uri.to_string(),
+                line: None,
+                threshold: Some(config.complexity.threshold),
+                include_metrics: Some(false),
+            };
+            if let Ok(response) = self.handle_analyze_complexity(params).await {
+                for func in response
+                    .functions
+                    .iter()
+                    .filter(|f| f.complexity > config.complexity.threshold)
+                {
+                    diagnostics.push(new_diagnostic(
+                        line_range(func.location.range.start.line),
+                        config.complexity.severity,
+                        codes::COMPLEXITY,
+                        format!(
+                            "'{}' has cyclomatic complexity {} (grade {}), above the threshold of {}",
+                            func.name, func.complexity, func.grade, config.complexity.threshold
+                        ),
+                    ));
+                }
+            }
+        }
+
+        if let (Some(liveness), Ok(path)) = (liveness, uri.to_file_path()) {
+            let graph = self.graph.read().await;
+            for item in self
+                .unreachable_items(
+                    &graph,
+                    liveness,
+                    Some(&path),
+                    false,
+                    config.unused_code.min_confidence,
+                )
+                .iter()
+                .filter(|i| i.item_type == "function")
+            {
+                let mut diagnostic = new_diagnostic(
+                    item.location.range,
+                    config.unused_code.severity,
+                    codes::UNUSED_FUNCTION,
+                    format!("Function '{}' is never used: {}", item.name, item.reason),
+                );
+                diagnostic.tags = Some(vec![DiagnosticTag::UNNECESSARY]);
+                diagnostics.push(diagnostic);
+            }
+        }
+
+        if let Some(findings) = findings {
+            diagnostics.extend(self.import_diagnostics(uri, config, findings).await);
+        }
+
+        diagnostics
+    }
+
+    async fn import_diagnostics(
+        &self,
+        uri: &Url,
+        config: &DiagnosticsConfig,
+        findings: &ImportFindings,
+    ) -> Vec<Diagnostic> {
+        let Some(file) = uri
+            .to_file_path()
+            .ok()
+            .and_then(|p| findings.graph.index_of(&p))
+        else {
+            return Vec::new();
+        };
+
+        let text = self.document_text(&findings.graph.files[file]);
+        let import_range = |module: &str| {
+            let line = text
+                .as_deref()
+                .and_then(|t| find_import_line(t, module))
+                .unwrap_or(0);
+            line_range(line)
+        };
+
+        let mut diagnostics = Vec::new();
+        for edge in findings.graph.imports_of(file) {
+            let target_path = &findings.graph.files[edge.to];
+
+            let same_cycle = findings
+                .cycle_of
+                .get(&edge.from)
+                .is_some_and(|c| findings.cycle_of.get(&edge.to) == Some(c));
+            if config.import_cycles.enabled && same_cycle {
+                let path = findings.cycle_path(edge.from, edge.to);
+                let chain: Vec<String> = path
+                    .iter()
+                    .map(|&f| display_name(&findings.graph.files[f]))
+                    .collect();
+                let mut diagnostic = new_diagnostic(
+                    import_range(&edge.module),
+                    config.import_cycles.severity,
+                    codes::IMPORT_CYCLE,
+                    format!("Import cycle: {}", chain.join(" -> ")),
+                );
+                diagnostic.related_information = Some(
+                    path.iter()
+                        .skip(1)
+                        .filter(|&&f| f != file)
+                        .filter_map(|&f| {
+                            let related_uri = Url::from_file_path(&findings.graph.files[f]).ok()?;
+                            Some(DiagnosticRelatedInformation {
+                                location: Location {
+                                    uri: related_uri,
+                                    range: Range::default(),
+                                },
+                                message: "Part of the import cycle".to_string(),
+                            })
+                        })
+                        .collect(),
+                );
+                diagnostics.push(diagnostic);
+            }
+
+            if config.layer_violations.enabled {
+                if let (Some(from_layer), Some(to_layer)) =
+                    (&findings.layers[edge.from], &findings.layers[edge.to])
+                {
+                    if let Some(reason) = findings.architecture.violation(
+                        &findings.graph.files[file],
+                        from_layer,
+                        to_layer,
+                    ) {
+                        diagnostics.push(new_diagnostic(
+                            import_range(&edge.module),
+                            config.layer_violations.severity,
+                            codes::LAYER_VIOLATION,
+                            format!(
+                                "Layer violation: '{}' layer imports '{}' from the '{}' layer ({})",
+                                from_layer,
+                                display_name(target_path),
+                                to_layer,
+                                reason
+                            ),
+                        ));
+                    }
+                }
+            }
+        }
+
+        diagnostics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import_graph::RawImport;
+    use std::path::PathBuf;
+
+    fn findings(files: &[&str], imports: &[(&str, &str)]) -> ImportFindings {
+        let files = files.iter().map(PathBuf::from).collect();
+        let imports: Vec<RawImport> = imports
+            .iter()
+            .map(|(f, m)| (PathBuf::from(f), m.to_string(), Vec::new()))
+            .collect();
+        ImportFindings::new(
+            ImportGraph::from_imports(files, &imports),
+            Arc::new(Architecture::new(vec![PathBuf::from("/w")], Vec::new())),
+        )
+    }
+
+    #[test]
+    fn test_config_defaults() {
+        let config = DiagnosticsConfig::default();
+        assert!(config.enabled);
+        assert_eq!(config.scope, DiagnosticsScope::OpenFiles);
+        assert_eq!(config.complexity.threshold, 10);
+        assert_eq!(config.unused_code.severity, Severity::Hint);
+        assert_eq!(config.import_cycles.severity, Severity::Warning);
+    }
+
+    #[test]
+    fn test_config_from_settings() {
+        let settings = serde_json::json!({
+            "codegraph": {
+                "diagnostics": {
+                    "scope": "workspace",
+                    "complexity": { "threshold": 15, "severity": "information" },
+                    "layerViolations": { "enabled": false }
+                }
+            }
+        });
+        let config = DiagnosticsConfig::from_settings(&settings).unwrap();
+        assert_eq!(config.scope, DiagnosticsScope::Workspace);
+        assert_eq!(config.complexity.threshold, 15);
+        assert_eq!(config.complexity.severity, Severity::Information);
+        assert!(config.complexity.enabled);
+        assert!(!config.layer_violations.enabled);
+        assert!(config.import_cycles.enabled);
+
+        let init_options = serde_json::json!({ "diagnostics": { "enabled": false } });
+        assert!(
+            !DiagnosticsConfig::from_settings(&init_options)
+                .unwrap()
+                .enabled
+        );
+        assert!(DiagnosticsConfig::from_settings(&serde_json::json!({})).is_none());
+    }
+
+    #[test]
+    fn test_layer_dependency_rules() {
+        assert!(layer_dependency_allowed("controller", "service"));
+        assert!(layer_dependency_allowed("service", "domain"));
+        assert!(layer_dependency_allowed("repository", "domain"));
+        assert!(!layer_dependency_allowed("domain", "controller"));
+        assert!(!layer_dependency_allowed("domain", "repository"));
+        assert!(!layer_dependency_allowed("service", "handler"));
+        assert!(!layer_dependency_allowed("utility", "service"));
+        assert!(!layer_dependency_allowed("service", "test"));
+        assert!(layer_dependency_allowed("test", "test_support"));
+    }
+
+    #[test]
+    fn test_cycle_path() {
+        let f = findings(
+            &["/w/a.py", "/w/b.py", "/w/c.py", "/w/d.py"],
+            &[
+                ("/w/a.py", "b"),
+                ("/w/b.py", "c"),
+                ("/w/c.py", "a"),
+                ("/w/c.py", "d"),
+            ],
+        );
+        let a = f.graph.index_of(Path::new("/w/a.py")).unwrap();
+        let b = f.graph.index_of(Path::new("/w/b.py")).unwrap();
+        let c = f.graph.index_of(Path::new("/w/c.py")).unwrap();
+        let d = f.graph.index_of(Path::new("/w/d.py")).unwrap();
+
+        assert_eq!(f.cycle_path(a, b), vec![a, b, c, a]);
+        assert!(!f.cycle_of.contains_key(&d));
+        assert!(f.neighbors(d).contains(&c));
+    }
+
+    #[test]
+    fn test_layers_use_workspace_relative_paths() {
+        let f = findings(
+            &["/w/src/domain/user.py", "/w/src/controllers/api.py"],
+            &[("/w/src/domain/user.py", "src.controllers.api")],
+        );
+        let user = f
+            .graph
+            .index_of(Path::new("/w/src/domain/user.py"))
+            .unwrap();
+        let api = f
+            .graph
+            .index_of(Path::new("/w/src/controllers/api.py"))
+            .unwrap();
+        assert_eq!(f.layers[user].as_deref(), Some("domain"));
+        assert_eq!(f.layers[api].as_deref(), Some("controller"));
+        assert!(f
+            .architecture
+            .violation(
+                Path::new("/w/src/domain/user.py"),
+                f.layers[user].as_deref().unwrap(),
+                f.layers[api].as_deref().unwrap()
+            )
+            .is_some());
+    }
+}
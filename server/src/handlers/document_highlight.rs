@@ -0,0 +1,128 @@
+//! Document Highlight Handler - Occurrences of the symbol under the cursor.
+//!
+//! Highlights come from the occurrence index, so the definition and every use
+//! in the file are marked, including uses inside other function bodies. Call
+//! sites recorded as incoming `Calls` edges are added when the lexer did not
+//! see them as occurrences of the same name (e.g. calls through an alias).
+
+use crate::backend::CodeGraphBackend;
+use crate::occurrences::{self, OccurrenceKind};
+use codegraph::{Direction, EdgeType};
+use tower_lsp::jsonrpc::Result;
+use tower_lsp::lsp_types::{DocumentHighlight, DocumentHighlightKind, DocumentHighlightParams};
+
+/// Highlight kind for an occurrence: definitions and assignments are writes,
+/// plain uses are reads, and calls are shown as text so they stand apart
+/// from reads.
+pub fn highlight_kind(kind: OccurrenceKind) -> DocumentHighlightKind {
+    match kind {
+        OccurrenceKind::Definition | OccurrenceKind::Write => DocumentHighlightKind::WRITE,
+        OccurrenceKind::Read => DocumentHighlightKind::READ,
+        OccurrenceKind::Call => DocumentHighlightKind::TEXT,
+    }
+}
+
+impl CodeGraphBackend {
+    /// Handle textDocument/documentHighlight.
+    pub async fn handle_document_highlight(
+        &self,
+        params: DocumentHighlightParams,
+    ) -> Result<Option<Vec<DocumentHighlight>>> {
+        let position = params.text_document_position_params.position;
+        let path = params
+            .text_document_position_params
+            .text_document
+            .uri
+            .to_file_path()
+            .map_err(|_| tower_lsp::jsonrpc::Error::invalid_params("Invalid URI"))?;
+
+        if self.occurrence_index.file_occurrences(&path).is_empty() {
+            if let Some(text) = self.document_text(&path) {
+                self.occurrence_index.index_file(&path, &text);
+            }
+        }
+        let Some(occurrence) = self.occurrence_index.find_at(&path, position) else {
+            return Ok(None);
+        };
+
+        let graph = self.graph.read().await;
+        let target = occurrences::resolve(&graph, &self.symbol_index, &path, &occurrence);
+
+        // Resolved symbols highlight occurrences with the same target; anything
+        // else (locals, parameters) falls back to the same name in this file.
+        let mut highlights: Vec<DocumentHighlight> = self
+            .occurrence_index
+            .file_occurrences(&path)
+            .into_iter()
+            .filter(|o| o.name == occurrence.name)
+            .filter(|o| {
+                target.is_none()
+                    || occurrences::resolve(&graph, &self.symbol_index, &path, o) == target
+            })
+            .map(|o| DocumentHighlight {
+                range: o.range,
+                kind: Some(highlight_kind(o.kind)),
+            })
+            .collect();
+
+        if let Some(target) = target {
+            let callers = self
+                .get_connected_edges(&graph, target, Direction::Incoming)
+                .into_iter()
+                .filter(|(source, _, edge_type)| {
+                    *edge_type == EdgeType::Calls
+                        && self.symbol_index.find_file_for_node(*source).as_deref()
+                            == Some(path.as_path())
+                });
+            for (caller, _, _) in callers {
+                for range in self.find_call_site_ranges(&graph, caller, target) {
+                    // Whole-line fallbacks are too coarse to highlight.
+                    let precise =
+                        range.start.line == range.end.line && range.end.character != u32::MAX;
+                    let seen = highlights.iter().any(|h| {
+                        h.range.start <= range.start && range.start < h.range.end
+                            || range.start <= h.range.start && h.range.start < range.end
+                    });
+                    if precise && !seen {
+                        highlights.push(DocumentHighlight {
+                            range,
+                            kind: Some(DocumentHighlightKind::TEXT),
+                        });
+                    }
+                }
+            }
+        }
+
+        if highlights.is_empty() {
+            Ok(None)
+        } else {
+            highlights.sort_by_key(|h| (h.range.start.line, h.range.start.character));
+            Ok(Some(highlights))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_highlight_kind() {
+        assert_eq!(
+            highlight_kind(OccurrenceKind::Definition),
+            DocumentHighlightKind::WRITE
+        );
+        assert_eq!(
+            highlight_kind(OccurrenceKind::Write),
+            DocumentHighlightKind::WRITE
+        );
+        assert_eq!(
+            highlight_kind(OccurrenceKind::Read),
+            DocumentHighlightKind::READ
+        );
+        assert_eq!(
+            highlight_kind(OccurrenceKind::Call),
+            DocumentHighlightKind::TEXT
+        );
+    }
+}
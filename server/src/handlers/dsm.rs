@@ -0,0 +1,326 @@
+//! Dependency Matrix Handler - Workspace-wide design structure matrix.
+//!
+//! `codegraph/getDependencyMatrix` collapses files into directories or
+//! packages and counts the imports and calls between every pair of units.
+//! Each unit gets Robert Martin's package metrics: afferent coupling (Ca, the
+//! units depending on it), efferent coupling (Ce, the units it depends on),
+//! instability `I = Ce / (Ca + Ce)`, abstractness `A` (share of interfaces,
+//! traits and abstract classes among its types) and the distance from the
+//! main sequence `D = |A + I - 1|`.
+
+use crate::backend::CodeGraphBackend;
+use crate::handlers::cycles::{group_files, CycleGranularity};
+use crate::import_graph::ImportGraph;
+use codegraph::{Direction, EdgeType, NodeType};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use tower_lsp::jsonrpc::Result;
+use tower_lsp::lsp_types::Url;
+
+/// Units further than this from the main sequence are reported.
+const DISTANCE_THRESHOLD: f64 = 0.7;
+
+// ==========================================
+// Matrix Types
+// ==========================================
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DependencyMatrixParams {
+    /// directory (default), package or file
+    pub granularity: Option<CycleGranularity>,
+    /// Count calls between units as well as imports (default true)
+    pub include_calls: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DependencyMatrixResponse {
+    pub granularity: CycleGranularity,
+    /// Rows and columns of the matrix, in order
+    pub units: Vec<MatrixUnit>,
+    /// Non-empty cells; `from` and `to` index into `units`
+    pub cells: Vec<MatrixCell>,
+    pub summary: MatrixSummary,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MatrixUnit {
+    /// Path relative to the workspace folder
+    pub name: String,
+    pub uri: String,
+    pub files: usize,
+    #[serde(flatten)]
+    pub metrics: UnitMetrics,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnitMetrics {
+    /// Ca: units that depend on this one
+    pub afferent: usize,
+    /// Ce: units this one depends on
+    pub efferent: usize,
+    /// Ce / (Ca + Ce), 0 = stable, 1 = unstable
+    pub instability: f64,
+    /// Abstract types / all types, 0 when the unit defines no types
+    pub abstractness: f64,
+    /// |A + I - 1|, 0 = on the main sequence
+    pub distance: f64,
+    pub abstract_types: usize,
+    pub total_types: usize,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MatrixCell {
+    pub from: usize,
+    pub to: usize,
+    /// Import statements in `from` that resolve to files in `to`
+    pub imports: usize,
+    /// Call edges from functions in `from` to functions in `to`
+    pub calls: usize,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MatrixSummary {
+    pub total_units: usize,
+    pub total_dependencies: usize,
+    pub average_distance: f64,
+    /// Stable and concrete units, painful to change
+    pub zone_of_pain: Vec<String>,
+    /// Abstract units nothing depends on
+    pub zone_of_uselessness: Vec<String>,
+}
+
+// ==========================================
+// Package Metrics
+// ==========================================
+
+/// Martin's metrics for every unit, given the matrix cells and the
+/// `(abstract, total)` type counts of each unit.
+pub fn unit_metrics(cells: &[MatrixCell], types: &[(usize, usize)]) -> Vec<UnitMetrics> {
+    let mut afferent = vec![0; types.len()];
+    let mut efferent = vec![0; types.len()];
+    for cell in cells.iter().filter(|c| c.from != c.to) {
+        efferent[cell.from] += 1;
+        afferent[cell.to] += 1;
+    }
+
+    types
+        .iter()
+        .enumerate()
+        .map(|(unit, &(abstract_types, total_types))| {
+            let (ca, ce) = (afferent[unit], efferent[unit]);
+            let instability = if ca + ce > 0 {
+                ce as f64 / (ca + ce) as f64
+            } else {
+                0.0
+            };
+            let abstractness = if total_types > 0 {
+                abstract_types as f64 / total_types as f64
+            } else {
+                0.0
+            };
+            UnitMetrics {
+                afferent: ca,
+                efferent: ce,
+                instability,
+                abstractness,
+                distance: (abstractness + instability - 1.0).abs(),
+                abstract_types,
+                total_types,
+            }
+        })
+        .collect()
+}
+
+fn is_abstract_type(node: &codegraph::Node) -> bool {
+    node.node_type == NodeType::Interface
+        || node.properties.get_bool("is_abstract") == Some(true)
+        || node.properties.get_string("is_abstract") == Some("true")
+}
+
+impl CodeGraphBackend {
+    /// Build the design structure matrix of the workspace.
+    pub async fn handle_get_dependency_matrix(
+        &self,
+        params: DependencyMatrixParams,
+    ) -> Result<DependencyMatrixResponse> {
+        let granularity = params.granularity.unwrap_or(CycleGranularity::Directory);
+        let include_calls = params.include_calls.unwrap_or(true);
+        let folders = self.workspace_folders.read().await.clone();
+        let graph = self.graph.read().await;
+
+        let imports = ImportGraph::build(&graph);
+        let (units, unit_of) = group_files(&imports.files, granularity, &folders);
+        let file_unit: HashMap<&Path, usize> = imports
+            .files
+            .iter()
+            .zip(&unit_of)
+            .map(|(file, &unit)| (file.as_path(), unit))
+            .collect();
+
+        // (imports, calls) per pair of units
+        let mut counts: BTreeMap<(usize, usize), (usize, usize)> = BTreeMap::new();
+        for edge in &imports.edges {
+            let (from, to) = (unit_of[edge.from], unit_of[edge.to]);
+            if from != to {
+                counts.entry((from, to)).or_default().0 += 1;
+            }
+        }
+
+        let mut types = vec![(0, 0); units.len()];
+        let mut files_per_unit = vec![0; units.len()];
+        for (file, &unit) in &file_unit {
+            files_per_unit[unit] += 1;
+            for node_id in self.symbol_index.get_file_symbols(file) {
+                let Ok(node) = graph.get_node(node_id) else {
+                    continue;
+                };
+                if matches!(node.node_type, NodeType::Class | NodeType::Interface) {
+                    types[unit].1 += 1;
+                    if is_abstract_type(node) {
+                        types[unit].0 += 1;
+                    }
+                }
+                if !include_calls || node.node_type != NodeType::Function {
+                    continue;
+                }
+                for (_, target, edge_type) in
+                    self.get_connected_edges(&graph, node_id, Direction::Outgoing)
+                {
+                    if edge_type != EdgeType::Calls {
+                        continue;
+                    }
+                    let Some(to) = self
+                        .symbol_index
+                        .find_file_for_node(target)
+                        .and_then(|f| file_unit.get(f.as_path()).copied())
+                    else {
+                        continue;
+                    };
+                    if to != unit {
+                        counts.entry((unit, to)).or_default().1 += 1;
+                    }
+                }
+            }
+        }
+        let cells: Vec<MatrixCell> = counts
+            .into_iter()
+            .map(|((from, to), (imports, calls))| MatrixCell {
+                from,
+                to,
+                imports,
+                calls,
+            })
+            .collect();
+        let metrics = unit_metrics(&cells, &types);
+
+        let display = |path: &Path| {
+            folders
+                .iter()
+                .find_map(|f| path.strip_prefix(f).ok())
+                .map(|rel| rel.display().to_string())
+                .filter(|rel| !rel.is_empty())
+                .unwrap_or_else(|| {
+                    path.file_name()
+                        .map(|n| n.to_string_lossy().to_string())
+                        .unwrap_or_else(|| path.display().to_string())
+                })
+        };
+        let units: Vec<MatrixUnit> = units
+            .iter()
+            .zip(metrics)
+            .enumerate()
+            .map(|(i, (path, metrics))| MatrixUnit {
+                name: display(path),
+                uri: Url::from_file_path(path)
+                    .map(|u| u.to_string())
+                    .unwrap_or_else(|_| path.display().to_string()),
+                files: files_per_unit[i],
+                metrics,
+            })
+            .collect();
+
+        let average_distance = if units.is_empty() {
+            0.0
+        } else {
+            units.iter().map(|u| u.metrics.distance).sum::<f64>() / units.len() as f64
+        };
+        let far = |u: &&MatrixUnit| u.metrics.distance > DISTANCE_THRESHOLD;
+        let zone_of_pain = units
+            .iter()
+            .filter(far)
+            .filter(|u| u.metrics.abstractness + u.metrics.instability < 1.0)
+            .filter(|u| u.metrics.afferent > 0)
+            .map(|u| u.name.clone())
+            .collect();
+        let zone_of_uselessness = units
+            .iter()
+            .filter(far)
+            .filter(|u| u.metrics.abstractness + u.metrics.instability > 1.0)
+            .map(|u| u.name.clone())
+            .collect();
+
+        Ok(DependencyMatrixResponse {
+            granularity,
+            summary: MatrixSummary {
+                total_units: units.len(),
+                total_dependencies: cells.len(),
+                average_distance,
+                zone_of_pain,
+                zone_of_uselessness,
+            },
+            units,
+            cells,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cell(from: usize, to: usize, imports: usize, calls: usize) -> MatrixCell {
+        MatrixCell {
+            from,
+            to,
+            imports,
+            calls,
+        }
+    }
+
+    #[test]
+    fn test_unit_metrics() {
+        // 0: app -> 1: domain, 2: db; 2: db -> 1: domain
+        let cells = [cell(0, 1, 3, 5), cell(0, 2, 1, 0), cell(2, 1, 2, 2)];
+        let types = [(0, 2), (3, 4), (0, 0)];
+        let metrics = unit_metrics(&cells, &types);
+
+        assert_eq!((metrics[0].afferent, metrics[0].efferent), (0, 2));
+        assert_eq!(metrics[0].instability, 1.0);
+        assert_eq!(metrics[0].distance, 0.0);
+
+        // Depended on by both, depends on nothing, mostly abstract.
+        assert_eq!((metrics[1].afferent, metrics[1].efferent), (2, 0));
+        assert_eq!(metrics[1].instability, 0.0);
+        assert_eq!(metrics[1].abstractness, 0.75);
+        assert!((metrics[1].distance - 0.25).abs() < 1e-9);
+
+        assert_eq!(metrics[2].instability, 0.5);
+        assert_eq!(metrics[2].abstractness, 0.0);
+        assert_eq!(metrics[2].distance, 0.5);
+    }
+
+    #[test]
+    fn test_isolated_unit() {
+        let metrics = unit_metrics(&[], &[(1, 1)]);
+        assert_eq!(metrics[0].instability, 0.0);
+        assert_eq!(metrics[0].abstractness, 1.0);
+        assert_eq!(metrics[0].distance, 0.0);
+    }
+}
@@ -0,0 +1,493 @@
+//! Duplicate Code Handler - Clone detection over function bodies.
+//!
+//! `codegraph/findDuplicates` tokenizes the source of every function the
+//! graph knows about and normalizes the tokens: identifiers, strings and
+//! numbers collapse to placeholders, and syntax that differs between
+//! languages only in spelling (`def`/`fn`/`func`, `and`/`&&`, braces vs.
+//! colons) is unified. Functions with the same normalized token sequence are
+//! exact clones; near duplicates are found by MinHash over token shingles and
+//! confirmed with the Jaccard similarity of their shingle sets.
+
+use crate::algorithms::weakly_connected_components;
+use crate::backend::CodeGraphBackend;
+use crate::handlers::test_selection::{is_test_file, TestRunner};
+use crate::lexer::{tokenize, Language, TokenKind};
+use codegraph::{NodeId, NodeType};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use tower_lsp::jsonrpc::Result;
+use tower_lsp::lsp_types::{Range, Url};
+
+/// Tokens per shingle.
+const SHINGLE_SIZE: usize = 5;
+
+/// MinHash signature length, split into `BANDS` bands for bucketing.
+const SIGNATURE_SIZE: usize = 64;
+const BANDS: usize = 16;
+
+const DEFAULT_MIN_LINES: u32 = 5;
+const DEFAULT_MIN_TOKENS: usize = 40;
+const DEFAULT_SIMILARITY: f64 = 0.8;
+
+// ==========================================
+// Duplicate Types
+// ==========================================
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FindDuplicatesParams {
+    /// Only report groups with a member in this file
+    pub uri: Option<String>,
+    /// Minimum similarity of near duplicates, 0.0-1.0 (default 0.8)
+    pub min_similarity: Option<f64>,
+    /// Ignore functions shorter than this many lines (default 5)
+    pub min_lines: Option<u32>,
+    /// Ignore functions with fewer normalized tokens (default 40)
+    pub min_tokens: Option<usize>,
+    /// Include functions in test files (default false)
+    pub include_tests: Option<bool>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CloneKind {
+    /// Same normalized token sequence
+    Exact,
+    /// Similar token shingles
+    Near,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FindDuplicatesResponse {
+    pub groups: Vec<CloneGroup>,
+    pub summary: DuplicatesSummary,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CloneGroup {
+    pub kind: CloneKind,
+    /// Lowest similarity between two members confirmed as clones
+    pub similarity: f64,
+    pub members: Vec<CloneMember>,
+    /// Lines that would go away if every member but the longest were
+    /// replaced by a shared function
+    pub duplicated_lines: u32,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CloneMember {
+    pub name: String,
+    pub uri: String,
+    pub range: Range,
+    pub lines: u32,
+    pub language: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicatesSummary {
+    pub functions_analyzed: usize,
+    pub total_groups: usize,
+    pub exact_groups: usize,
+    pub duplicated_functions: usize,
+    pub duplicated_lines: u32,
+}
+
+// ==========================================
+// Fingerprinting
+// ==========================================
+
+/// Token stream with identifiers, literals and language-specific spelling
+/// normalized away.
+pub fn normalize_tokens(source: &str, language: Language) -> Vec<String> {
+    let mut normalized = Vec::new();
+    for token in tokenize(source, language) {
+        let text = token.text.as_str();
+        let mapped = match token.kind {
+            TokenKind::String => "$str",
+            TokenKind::Number => "$num",
+            TokenKind::Identifier | TokenKind::Keyword => match text {
+                "def" | "fn" | "func" | "function" => "fn",
+                "let" | "var" | "const" | "mut" | "pub" | "export" => continue,
+                "None" | "null" | "nil" | "undefined" => "$nil",
+                "True" | "False" | "true" | "false" => "$bool",
+                "self" | "this" => "self",
+                "and" => "&&",
+                "or" => "||",
+                "not" => "!",
+                "elif" => {
+                    normalized.push("else".to_string());
+                    "if"
+                }
+                _ if token.kind == TokenKind::Keyword => text,
+                _ => "$id",
+            },
+            TokenKind::Operator => match text {
+                "{" | "}" | ";" | ":" => continue,
+                "===" => "==",
+                "!==" => "!=",
+                _ => text,
+            },
+        };
+        normalized.push(mapped.to_string());
+    }
+    normalized
+}
+
+fn hash_of<T: Hash + ?Sized>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Hashes of every run of `SHINGLE_SIZE` consecutive tokens.
+pub fn shingles(tokens: &[String]) -> HashSet<u64> {
+    if tokens.len() < SHINGLE_SIZE {
+        return HashSet::from([hash_of(tokens)]);
+    }
+    tokens.windows(SHINGLE_SIZE).map(hash_of).collect()
+}
+
+pub fn jaccard(a: &HashSet<u64>, b: &HashSet<u64>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 1.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
+/// SplitMix64 finalizer, used to derive independent hash functions.
+fn mix(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+fn minhash(shingles: &HashSet<u64>) -> [u64; SIGNATURE_SIZE] {
+    let mut signature = [u64::MAX; SIGNATURE_SIZE];
+    for &shingle in shingles {
+        for (seed, slot) in signature.iter_mut().enumerate() {
+            *slot = (*slot).min(mix(shingle ^ mix(seed as u64 + 1)));
+        }
+    }
+    signature
+}
+
+/// Fingerprint of one function body.
+#[derive(Debug)]
+pub struct Fingerprint {
+    /// Hash of the whole normalized token sequence
+    pub exact: u64,
+    pub shingles: HashSet<u64>,
+}
+
+impl Fingerprint {
+    pub fn new(tokens: &[String]) -> Self {
+        Self {
+            exact: hash_of(tokens),
+            shingles: shingles(tokens),
+        }
+    }
+}
+
+/// A group of clones: member indices, lowest confirmed similarity and
+/// whether all members are exact clones.
+pub type Clones = (Vec<usize>, f64, bool);
+
+/// Group fingerprints whose similarity is at least `min_similarity`.
+pub fn find_clones(fingerprints: &[Fingerprint], min_similarity: f64) -> Vec<Clones> {
+    let mut similar: HashMap<(usize, usize), f64> = HashMap::new();
+
+    let mut exact: HashMap<u64, Vec<usize>> = HashMap::new();
+    for (i, fingerprint) in fingerprints.iter().enumerate() {
+        exact.entry(fingerprint.exact).or_default().push(i);
+    }
+    for members in exact.values() {
+        for pair in members.windows(2) {
+            similar.insert((pair[0], pair[1]), 1.0);
+        }
+    }
+
+    if min_similarity < 1.0 {
+        let rows = SIGNATURE_SIZE / BANDS;
+        let mut buckets: HashMap<(usize, u64), Vec<usize>> = HashMap::new();
+        for (i, fingerprint) in fingerprints.iter().enumerate() {
+            let signature = minhash(&fingerprint.shingles);
+            for (band, values) in signature.chunks(rows).enumerate() {
+                buckets.entry((band, hash_of(values))).or_default().push(i);
+            }
+        }
+        for members in buckets.values() {
+            for (n, &a) in members.iter().enumerate() {
+                for &b in &members[n + 1..] {
+                    if similar.contains_key(&(a, b))
+                        || fingerprints[a].exact == fingerprints[b].exact
+                    {
+                        continue;
+                    }
+                    let similarity = jaccard(&fingerprints[a].shingles, &fingerprints[b].shingles);
+                    if similarity >= min_similarity {
+                        similar.insert((a, b), similarity);
+                    }
+                }
+            }
+        }
+    }
+
+    let mut adjacency = vec![Vec::new(); fingerprints.len()];
+    for &(a, b) in similar.keys() {
+        adjacency[a].push(b);
+    }
+    let mut include = vec![false; fingerprints.len()];
+    for &(a, b) in similar.keys() {
+        include[a] = true;
+        include[b] = true;
+    }
+
+    weakly_connected_components(&adjacency, &include)
+        .into_iter()
+        .map(|members| {
+            let similarity = similar
+                .iter()
+                .filter(|((a, _), _)| members.binary_search(a).is_ok())
+                .map(|(_, &s)| s)
+                .fold(1.0, f64::min);
+            let all_exact = members
+                .iter()
+                .all(|&m| fingerprints[m].exact == fingerprints[members[0]].exact);
+            (members, similarity, all_exact)
+        })
+        .collect()
+}
+
+// ==========================================
+// Handler
+// ==========================================
+
+/// A function selected for fingerprinting.
+struct Candidate {
+    node_id: NodeId,
+    name: String,
+    file: std::path::PathBuf,
+    language: Language,
+}
+
+impl CodeGraphBackend {
+    /// Find groups of duplicated functions across the workspace.
+    pub async fn handle_find_duplicates(
+        &self,
+        params: FindDuplicatesParams,
+    ) -> Result<FindDuplicatesResponse> {
+        let focus = match &params.uri {
+            Some(uri) => Some(
+                Url::parse(uri)
+                    .map_err(|_| tower_lsp::jsonrpc::Error::invalid_params("Invalid URI"))?
+                    .to_file_path()
+                    .map_err(|_| tower_lsp::jsonrpc::Error::invalid_params("Invalid file path"))?,
+            ),
+            None => None,
+        };
+        let min_similarity = params
+            .min_similarity
+            .unwrap_or(DEFAULT_SIMILARITY)
+            .clamp(0.0, 1.0);
+        let min_lines = params.min_lines.unwrap_or(DEFAULT_MIN_LINES);
+        let min_tokens = params.min_tokens.unwrap_or(DEFAULT_MIN_TOKENS);
+        let include_tests = params.include_tests.unwrap_or(false);
+
+        let candidates: Vec<Candidate> = {
+            let graph = self.graph.read().await;
+            graph
+                .query()
+                .node_type(NodeType::Function)
+                .execute()
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|node_id| {
+                    let node = graph.get_node(node_id).ok()?;
+                    let start = node.properties.get_int("line_start")?;
+                    let end = node.properties.get_int("line_end")?;
+                    if ((end - start + 1) as u32) < min_lines {
+                        return None;
+                    }
+                    let file = self.symbol_index.find_file_for_node(node_id)?;
+                    let language = Language::from_path(&file)?;
+                    let is_test = TestRunner::for_language(language).is_some_and(|runner| {
+                        runner != TestRunner::Cargo && is_test_file(runner, &file)
+                    });
+                    if is_test && !include_tests {
+                        return None;
+                    }
+                    Some(Candidate {
+                        node_id,
+                        name: node.properties.get_string("name")?.to_string(),
+                        file,
+                        language,
+                    })
+                })
+                .collect()
+        };
+
+        let mut analyzed = Vec::new();
+        let mut fingerprints = Vec::new();
+        for candidate in candidates {
+            let Ok(Some(source)) = self.get_node_source_code(candidate.node_id).await else {
+                continue;
+            };
+            let tokens = normalize_tokens(&source, candidate.language);
+            if tokens.len() < min_tokens {
+                continue;
+            }
+            fingerprints.push(Fingerprint::new(&tokens));
+            analyzed.push(candidate);
+        }
+
+        let graph = self.graph.read().await;
+        let mut groups: Vec<CloneGroup> = find_clones(&fingerprints, min_similarity)
+            .into_iter()
+            .filter(|(members, _, _)| {
+                focus
+                    .as_ref()
+                    .is_none_or(|f| members.iter().any(|&m| &analyzed[m].file == f))
+            })
+            .map(|(members, similarity, exact)| {
+                let members: Vec<CloneMember> = members
+                    .iter()
+                    .filter_map(|&m| {
+                        let candidate = &analyzed[m];
+                        let location = self.node_to_location(&graph, candidate.node_id).ok()?;
+                        Some(CloneMember {
+                            name: candidate.name.clone(),
+                            uri: location.uri.to_string(),
+                            lines: location.range.end.line - location.range.start.line + 1,
+                            range: location.range,
+                            language: format!("{:?}", candidate.language).to_lowercase(),
+                        })
+                    })
+                    .collect();
+                let total: u32 = members.iter().map(|m| m.lines).sum();
+                let longest = members.iter().map(|m| m.lines).max().unwrap_or(0);
+                CloneGroup {
+                    kind: if exact {
+                        CloneKind::Exact
+                    } else {
+                        CloneKind::Near
+                    },
+                    similarity,
+                    members,
+                    duplicated_lines: total - longest,
+                }
+            })
+            .filter(|group| group.members.len() > 1)
+            .collect();
+        groups.sort_by(|a, b| {
+            b.duplicated_lines
+                .cmp(&a.duplicated_lines)
+                .then_with(|| b.members.len().cmp(&a.members.len()))
+        });
+
+        Ok(FindDuplicatesResponse {
+            summary: DuplicatesSummary {
+                functions_analyzed: analyzed.len(),
+                total_groups: groups.len(),
+                exact_groups: groups.iter().filter(|g| g.kind == CloneKind::Exact).count(),
+                duplicated_functions: groups.iter().map(|g| g.members.len()).sum(),
+                duplicated_lines: groups.iter().map(|g| g.duplicated_lines).sum(),
+            },
+            groups,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PY_SUM: &str = r#"
+def total_price(items, tax):
+    result = 0
+    for item in items:
+        if item.price > 0 and not item.free:
+            result += item.price * tax
+    return result
+"#;
+
+    const PY_RENAMED: &str = r#"
+def sum_costs(rows, rate):
+    acc = 1
+    for row in rows:
+        if row.cost > 0 and not row.skip:
+            acc += row.cost * rate
+    return acc
+"#;
+
+    const TS_SUM: &str = r#"
+function totalPrice(items, tax) {
+    let result = 0;
+    for (const item of items) {
+        if (item.price > 0 && !item.free) {
+            result += item.price * tax;
+        }
+    }
+    return result;
+}
+"#;
+
+    fn fingerprint(source: &str, language: Language) -> Fingerprint {
+        Fingerprint::new(&normalize_tokens(source, language))
+    }
+
+    #[test]
+    fn test_renamed_clone_is_exact() {
+        let a = normalize_tokens(PY_SUM, Language::Python);
+        let b = normalize_tokens(PY_RENAMED, Language::Python);
+        assert_eq!(a, b);
+        assert!(a.contains(&"$id".to_string()));
+        assert!(a.contains(&"&&".to_string()));
+    }
+
+    #[test]
+    fn test_cross_language_similarity() {
+        let py = fingerprint(PY_SUM, Language::Python);
+        let ts = fingerprint(TS_SUM, Language::TypeScript);
+        let similarity = jaccard(&py.shingles, &ts.shingles);
+        assert!(similarity > 0.3, "similarity {similarity}");
+        assert!(similarity < 1.0);
+    }
+
+    #[test]
+    fn test_find_clones_groups() {
+        let unrelated = r#"
+def parse(text):
+    parts = text.split(",")
+    while parts:
+        head = parts.pop()
+        yield head.strip()
+"#;
+        let fingerprints = vec![
+            fingerprint(PY_SUM, Language::Python),
+            fingerprint(unrelated, Language::Python),
+            fingerprint(PY_RENAMED, Language::Python),
+        ];
+        let clones = find_clones(&fingerprints, 0.8);
+        assert_eq!(clones, vec![(vec![0, 2], 1.0, true)]);
+
+        // A one-token change is a near duplicate, not an exact one.
+        let edited = PY_RENAMED.replace("acc += row.cost * rate", "acc -= row.cost * rate");
+        let fingerprints = vec![
+            fingerprint(PY_SUM, Language::Python),
+            fingerprint(&edited, Language::Python),
+        ];
+        let clones = find_clones(&fingerprints, 0.5);
+        assert_eq!(clones.len(), 1);
+        let (members, similarity, exact) = &clones[0];
+        assert_eq!(members, &vec![0, 1]);
+        assert!(!exact);
+        assert!(*similarity >= 0.5 && *similarity < 1.0);
+    }
+}
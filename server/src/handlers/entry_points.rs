@@ -0,0 +1,1040 @@
+//! Entry Points - The root set of reachability-based dead code detection.
+//!
+//! A symbol is a root when something outside the call graph can invoke it:
+//! `main`, the public API, tests, framework entry points, or code that runs
+//! at module level. Framework entry points are found by recognizers for
+//! decorators, attributes and naming rules that the runtime relies on, such as
+//! Flask and FastAPI routes, pytest fixtures, `#[tokio::main]`, Go `init` and
+//! symbols re-exported by a TypeScript `index.ts` barrel. Methods the runtime
+//! calls on an instance, such as constructors, Python `__dunder__` methods and
+//! trait implementations, are roots of their own kind that live and die with
+//! their class.
+//!
+//! A workspace folder can extend the roots in `.codegraph/entrypoints.toml`:
+//!
+//! ```toml
+//! symbols = ["handle_*", "cli.commands.*"]  # name globs, always roots
+//! files = ["scripts/**"]                    # everything defined here is a root
+//! public_api = false                        # exported symbols are not roots (default true)
+//! decorators = ["celery.task", "*.command"] # extra Python/TypeScript decorators
+//! attributes = ["wasm_bindgen"]             # extra Rust attributes
+//! disable = ["fastapi"]                     # built-in recognizers to turn off
+//! ```
+
+use crate::error::{LspError, LspResult};
+use crate::handlers::architecture::{matches_path, path_patterns};
+use crate::handlers::test_selection::{is_test_file, is_test_function, TestRunner};
+use crate::index;
+use crate::lexer::{matching_close, tokenize, Language, TokenKind};
+use glob::Pattern;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Location of the entry point config, relative to a workspace folder.
+pub const ENTRY_POINTS_CONFIG: &str = ".codegraph/entrypoints.toml";
+
+/// Contents of `.codegraph/entrypoints.toml`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EntryPointsConfig {
+    /// Globs over symbol names, matched against both the short and the
+    /// qualified name
+    #[serde(default)]
+    pub symbols: Vec<String>,
+    /// Globs relative to the workspace folder
+    #[serde(default)]
+    pub files: Vec<String>,
+    /// Whether exported symbols are roots (default true); turn off for
+    /// applications that nobody links against
+    pub public_api: Option<bool>,
+    /// Globs over decorator paths, without the `@`
+    #[serde(default)]
+    pub decorators: Vec<String>,
+    /// Globs over Rust attribute paths
+    #[serde(default)]
+    pub attributes: Vec<String>,
+    /// Names of built-in recognizers to turn off
+    #[serde(default)]
+    pub disable: Vec<String>,
+}
+
+/// Why a symbol is part of the root set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RootKind {
+    Main,
+    PublicApi,
+    Test,
+    Framework,
+    Allowlist,
+    /// Called from module-level code rather than from another symbol
+    ModuleCode,
+    /// Called by the runtime on instances of its class: constructors,
+    /// `__dunder__` methods, trait and interface implementations
+    Runtime,
+}
+
+/// What the root checks know about a function or class definition.
+#[derive(Debug, Clone, Copy)]
+pub struct Definition<'a> {
+    /// Qualified name as stored in the graph
+    pub name: &'a str,
+    pub file: &'a Path,
+    /// Source line the definition starts on
+    pub line: &'a str,
+    /// Source lines directly above the definition
+    pub preceding: &'a [&'a str],
+    /// The parser marked the symbol as exported
+    pub exported: bool,
+    /// An `index.ts` barrel re-exports the symbol
+    pub reexported: bool,
+}
+
+// ==========================================
+// Recognizers
+// ==========================================
+
+/// How a recognizer matches a definition.
+#[derive(Debug, Clone)]
+enum Rule {
+    /// Python or TypeScript decorators, by dotted path
+    Decorator(Vec<Pattern>),
+    /// Rust attributes, by path
+    Attribute(Vec<Pattern>),
+    /// Naming conventions the runtime looks for
+    Name(fn(&Definition) -> bool),
+    /// Symbols re-exported by a barrel file
+    Barrel,
+}
+
+/// A way to recognize symbols that a framework or runtime invokes.
+#[derive(Debug, Clone)]
+pub struct Recognizer {
+    pub name: String,
+    pub kind: RootKind,
+    rule: Rule,
+}
+
+impl Recognizer {
+    fn new(name: &str, kind: RootKind, rule: Rule) -> Self {
+        Self {
+            name: name.to_string(),
+            kind,
+            rule,
+        }
+    }
+
+    fn globs(paths: &[&str]) -> Vec<Pattern> {
+        paths
+            .iter()
+            .map(|p| Pattern::new(p).expect("built-in recognizer glob"))
+            .collect()
+    }
+
+    /// Recognizers that apply unless a folder disables them.
+    pub fn builtins() -> Vec<Self> {
+        let decorators = |name, paths: &[&str]| {
+            Self::new(
+                name,
+                RootKind::Framework,
+                Rule::Decorator(Self::globs(paths)),
+            )
+        };
+        let attributes =
+            |name, kind, paths: &[&str]| Self::new(name, kind, Rule::Attribute(Self::globs(paths)));
+        vec![
+            decorators(
+                "flask",
+                &[
+                    "*.route",
+                    "*.before_request",
+                    "*.after_request",
+                    "*.teardown_request",
+                    "*.errorhandler",
+                    "*.context_processor",
+                ],
+            ),
+            decorators(
+                "fastapi",
+                &[
+                    "*.get",
+                    "*.post",
+                    "*.put",
+                    "*.patch",
+                    "*.delete",
+                    "*.head",
+                    "*.options",
+                    "*.websocket",
+                    "*.api_route",
+                    "*.on_event",
+                    "*.middleware",
+                    "*.exception_handler",
+                ],
+            ),
+            decorators(
+                "pytest",
+                &["pytest.fixture", "fixture", "pytest.hookimpl", "hookimpl"],
+            ),
+            attributes("rust-test", RootKind::Test, &["test", "*::test", "bench"]),
+            attributes("rust-main", RootKind::Main, &["*::main"]),
+            attributes(
+                "rust-ffi",
+                RootKind::Framework,
+                &["no_mangle", "export_name"],
+            ),
+            Self::new("go-init", RootKind::Framework, Rule::Name(is_go_init)),
+            Self::new("go-test", RootKind::Test, Rule::Name(is_go_test)),
+            Self::new("ts-barrel", RootKind::PublicApi, Rule::Barrel),
+            Self::new(
+                "python-dunder",
+                RootKind::Runtime,
+                Rule::Name(is_python_dunder),
+            ),
+            Self::new(
+                "ts-constructor",
+                RootKind::Runtime,
+                Rule::Name(is_ts_constructor),
+            ),
+            Self::new(
+                "rust-trait-impl",
+                RootKind::Runtime,
+                Rule::Name(is_rust_trait_impl),
+            ),
+            Self::new(
+                "go-interface",
+                RootKind::Runtime,
+                Rule::Name(is_go_interface_method),
+            ),
+        ]
+    }
+
+    fn matches(&self, definition: &Definition, annotations: &[String]) -> bool {
+        let language = Language::from_path(definition.file);
+        match &self.rule {
+            Rule::Decorator(patterns) => {
+                matches!(language, Some(Language::Python | Language::TypeScript))
+                    && annotations
+                        .iter()
+                        .any(|a| patterns.iter().any(|p| p.matches(a)))
+            }
+            Rule::Attribute(patterns) => {
+                language == Some(Language::Rust)
+                    && annotations
+                        .iter()
+                        .any(|a| patterns.iter().any(|p| p.matches(a)))
+            }
+            Rule::Name(check) => check(definition),
+            Rule::Barrel => definition.reexported,
+        }
+    }
+}
+
+fn is_go_init(definition: &Definition) -> bool {
+    Language::from_path(definition.file) == Some(Language::Go)
+        && index::short_name(definition.name) == "init"
+}
+
+fn is_go_test(definition: &Definition) -> bool {
+    let short = index::short_name(definition.name);
+    is_test_file(TestRunner::Go, definition.file)
+        && ["Test", "Benchmark", "Fuzz", "Example"]
+            .iter()
+            .any(|prefix| {
+                short
+                    .strip_prefix(prefix)
+                    .is_some_and(|rest| !rest.starts_with(|c: char| c.is_lowercase()))
+            })
+}
+
+fn is_python_dunder(definition: &Definition) -> bool {
+    let short = index::short_name(definition.name);
+    Language::from_path(definition.file) == Some(Language::Python)
+        && short.len() > 4
+        && short.starts_with("__")
+        && short.ends_with("__")
+}
+
+fn is_ts_constructor(definition: &Definition) -> bool {
+    Language::from_path(definition.file) == Some(Language::TypeScript)
+        && index::short_name(definition.name) == "constructor"
+}
+
+/// Whether a Rust function is defined in an `impl Trait for Type` block,
+/// going by the nearest less indented line above it.
+fn is_rust_trait_impl(definition: &Definition) -> bool {
+    if Language::from_path(definition.file) != Some(Language::Rust) {
+        return false;
+    }
+    let indent = |line: &str| line.len() - line.trim_start().len();
+    let own = indent(definition.line);
+    definition
+        .preceding
+        .iter()
+        .rev()
+        .filter(|line| !line.trim().is_empty() && indent(line) < own)
+        // Skip the `where` clause of a multi-line header.
+        .find(|line| {
+            let line = line.trim();
+            !(line.starts_with("where") || line.starts_with('{') || line.ends_with(','))
+        })
+        .is_some_and(|header| {
+            let header = header.trim_start();
+            let header = header.strip_prefix("unsafe ").unwrap_or(header);
+            header.starts_with("impl") && header.contains(" for ")
+        })
+}
+
+/// Methods of standard library interfaces, which Go types satisfy implicitly.
+const GO_INTERFACE_METHODS: &[&str] = &[
+    "String",
+    "GoString",
+    "Format",
+    "Error",
+    "Unwrap",
+    "Is",
+    "As",
+    "Read",
+    "Write",
+    "Close",
+    "Seek",
+    "ReadFrom",
+    "WriteTo",
+    "Len",
+    "Less",
+    "Swap",
+    "Push",
+    "Pop",
+    "ServeHTTP",
+    "MarshalJSON",
+    "UnmarshalJSON",
+    "MarshalText",
+    "UnmarshalText",
+    "Scan",
+    "Value",
+];
+
+fn is_go_interface_method(definition: &Definition) -> bool {
+    Language::from_path(definition.file) == Some(Language::Go)
+        && definition.line.trim_start().starts_with("func (")
+        && GO_INTERFACE_METHODS.contains(&index::short_name(definition.name))
+}
+
+/// Annotations that never make a symbol reachable on their own.
+const INERT_ANNOTATIONS: &[&str] = &[
+    // Python
+    "staticmethod",
+    "classmethod",
+    "property",
+    "abstractmethod",
+    "abc.abstractmethod",
+    "overload",
+    "typing.overload",
+    "override",
+    "typing.override",
+    "cache",
+    "functools.cache",
+    "lru_cache",
+    "functools.lru_cache",
+    "cached_property",
+    "functools.cached_property",
+    "wraps",
+    "functools.wraps",
+    "dataclass",
+    "dataclasses.dataclass",
+    // Rust
+    "allow",
+    "warn",
+    "deny",
+    "expect",
+    "cfg",
+    "cfg_attr",
+    "doc",
+    "derive",
+    "inline",
+    "cold",
+    "must_use",
+    "deprecated",
+    "track_caller",
+    "rustfmt::skip",
+];
+
+/// Path of a decorator (`@app.route(...)`) or attribute (`#[tokio::main]`).
+fn annotation_path(line: &str) -> Option<String> {
+    let line = line.trim_start();
+    let rest = match line.strip_prefix('@') {
+        Some(rest) => rest,
+        None => {
+            let inner = line.strip_prefix("#[")?;
+            inner.strip_prefix("unsafe(").unwrap_or(inner)
+        }
+    };
+    let path: String = rest
+        .chars()
+        .take_while(|c| c.is_alphanumeric() || matches!(c, '_' | '.' | ':'))
+        .collect();
+    (!path.is_empty()).then_some(path)
+}
+
+/// Decorators and attributes directly above a definition, nearest first.
+pub fn annotations(preceding: &[&str]) -> Vec<String> {
+    let mut found = Vec::new();
+    // Brackets opened above and closed below: still inside a multi-line
+    // argument list.
+    let mut depth = 0i32;
+    for line in preceding.iter().rev() {
+        let trimmed = line.trim();
+        let opens = trimmed.matches(['(', '[']).count() as i32;
+        let closes = trimmed.matches([')', ']']).count() as i32;
+        depth += closes - opens;
+        if depth > 0 {
+            continue;
+        }
+        if depth < 0 {
+            break;
+        }
+        if let Some(path) = annotation_path(trimmed) {
+            found.push(path);
+        } else if !(trimmed.starts_with("//")
+            || (trimmed.starts_with('#') && !trimmed.starts_with("#[")))
+        {
+            break;
+        }
+    }
+    found
+}
+
+/// Modules a barrel file re-exports from, with the names it re-exports or
+/// `None` for `export * from`.
+pub fn barrel_exports(text: &str) -> Vec<(String, Option<Vec<String>>)> {
+    let tokens = tokenize(text, Language::TypeScript);
+    let mut exports = Vec::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        if !tokens[i].is("export") {
+            i += 1;
+            continue;
+        }
+        let mut j = i + 1;
+        if tokens.get(j).is_some_and(|t| t.is("type")) {
+            j += 1;
+        }
+        let names = match tokens.get(j) {
+            Some(t) if t.is("*") => {
+                j += 1;
+                // `export * as ns from` exposes the module as a namespace.
+                if tokens.get(j).is_some_and(|t| t.is("as")) {
+                    j += 2;
+                }
+                None
+            }
+            Some(t) if t.is("{") => {
+                let Some(close) = matching_close(&tokens, j) else {
+                    break;
+                };
+                let mut names = Vec::new();
+                let mut expect_name = true;
+                for token in &tokens[j + 1..close] {
+                    if token.is(",") {
+                        expect_name = true;
+                    } else if expect_name && token.kind == TokenKind::Identifier {
+                        if token.text != "type" {
+                            names.push(token.text.clone());
+                            expect_name = false;
+                        }
+                    } else {
+                        expect_name = false;
+                    }
+                }
+                j = close + 1;
+                Some(names)
+            }
+            _ => {
+                i = j;
+                continue;
+            }
+        };
+        if tokens.get(j).is_some_and(|t| t.is("from")) {
+            if let Some(module) = tokens.get(j + 1).filter(|t| t.kind == TokenKind::String) {
+                let module = module.text.trim_matches(['"', '\'', '`']).to_string();
+                exports.push((module, names));
+            }
+        }
+        i = j;
+    }
+    exports
+}
+
+/// Whether a file is a TypeScript or JavaScript barrel (`index.ts`).
+pub fn is_barrel_file(file: &Path) -> bool {
+    file.file_stem().is_some_and(|s| s == "index")
+        && Language::from_path(file) == Some(Language::TypeScript)
+}
+
+fn symbol_patterns(globs: &[String]) -> std::result::Result<Vec<Pattern>, String> {
+    globs
+        .iter()
+        .map(|g| Pattern::new(g).map_err(|e| format!("invalid symbol glob '{g}': {e}")))
+        .collect()
+}
+
+fn matches_symbol(patterns: &[Pattern], name: &str) -> bool {
+    let short = index::short_name(name);
+    patterns.iter().any(|p| p.matches(name) || p.matches(short))
+}
+
+/// Compiled entry point config of one workspace folder.
+#[derive(Debug)]
+pub struct EntryPointRules {
+    root: PathBuf,
+    symbols: Vec<Pattern>,
+    files: Vec<Pattern>,
+    public_api: bool,
+    recognizers: Vec<Recognizer>,
+}
+
+impl EntryPointRules {
+    /// Validate a config and compile its globs.
+    pub fn new(root: PathBuf, config: EntryPointsConfig) -> std::result::Result<Self, String> {
+        let mut recognizers = Recognizer::builtins();
+        for name in &config.disable {
+            if !recognizers.iter().any(|r| &r.name == name) {
+                return Err(format!("unknown recognizer '{name}'"));
+            }
+        }
+        recognizers.retain(|r| !config.disable.contains(&r.name));
+        if !config.decorators.is_empty() {
+            recognizers.push(Recognizer::new(
+                "decorators",
+                RootKind::Framework,
+                Rule::Decorator(symbol_patterns(&config.decorators)?),
+            ));
+        }
+        if !config.attributes.is_empty() {
+            recognizers.push(Recognizer::new(
+                "attributes",
+                RootKind::Framework,
+                Rule::Attribute(symbol_patterns(&config.attributes)?),
+            ));
+        }
+
+        Ok(Self {
+            root,
+            symbols: symbol_patterns(&config.symbols)?,
+            files: path_patterns(&config.files)?,
+            public_api: config.public_api.unwrap_or(true),
+            recognizers,
+        })
+    }
+
+    /// Read `.codegraph/entrypoints.toml` from a workspace folder, if present.
+    pub fn load(root: &Path) -> LspResult<Option<Self>> {
+        let path = root.join(ENTRY_POINTS_CONFIG);
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let invalid = |message: String| LspError::Config(format!("{}: {message}", path.display()));
+        let config: EntryPointsConfig =
+            toml::from_str(&text).map_err(|e| invalid(e.message().to_string()))?;
+        Self::new(root.to_path_buf(), config)
+            .map(Some)
+            .map_err(invalid)
+    }
+
+    fn allows(&self, definition: &Definition) -> bool {
+        matches_symbol(&self.symbols, definition.name)
+            || definition
+                .file
+                .strip_prefix(&self.root)
+                .is_ok_and(|relative| matches_path(&self.files, relative))
+    }
+}
+
+/// Whether a definition is visible outside its package.
+pub fn is_public_api(definition: &Definition) -> bool {
+    if definition.exported {
+        return true;
+    }
+    let line = definition.line.trim_start();
+    let short = index::short_name(definition.name);
+    match Language::from_path(definition.file) {
+        Some(Language::Rust) => line.starts_with("pub ") && !line.starts_with("pub(crate)"),
+        Some(Language::TypeScript) => line.starts_with("export "),
+        Some(Language::Go) => short.starts_with(|c: char| c.is_uppercase()),
+        // Package `__init__.py` files define what a Python package exposes.
+        Some(Language::Python) => {
+            definition
+                .file
+                .file_name()
+                .is_some_and(|n| n == "__init__.py")
+                && !short.starts_with('_')
+        }
+        Some(Language::C) | None => false,
+    }
+}
+
+/// Whether a definition is a test, or a helper in a file only test runners load.
+fn is_test_root(definition: &Definition) -> bool {
+    let Some(runner) = Language::from_path(definition.file).and_then(TestRunner::for_language)
+    else {
+        return false;
+    };
+    match runner {
+        // Jest tests are anonymous callbacks, so the whole file is a root.
+        TestRunner::Jest => is_test_file(runner, definition.file),
+        _ => is_test_function(
+            runner,
+            definition.file,
+            definition.name,
+            definition.preceding,
+        ),
+    }
+}
+
+/// The root set rules for all workspace folders.
+#[derive(Debug)]
+pub struct EntryPoints {
+    rules: Vec<EntryPointRules>,
+    symbols: Vec<Pattern>,
+    /// Recognizers of folders without a config
+    builtins: Vec<Recognizer>,
+}
+
+impl Default for EntryPoints {
+    fn default() -> Self {
+        Self {
+            rules: Vec::new(),
+            symbols: Vec::new(),
+            builtins: Recognizer::builtins(),
+        }
+    }
+}
+
+impl EntryPoints {
+    /// Load the config of every workspace folder. Invalid configs are
+    /// returned as errors alongside the folders that loaded.
+    pub fn load(folders: &[PathBuf]) -> (Self, Vec<LspError>) {
+        let mut rules = Vec::new();
+        let mut errors = Vec::new();
+        for folder in folders {
+            match EntryPointRules::load(folder) {
+                Ok(Some(r)) => rules.push(r),
+                Ok(None) => {}
+                Err(e) => errors.push(e),
+            }
+        }
+        (
+            Self {
+                rules,
+                ..Self::default()
+            },
+            errors,
+        )
+    }
+
+    /// Add symbol globs that are roots in every folder.
+    pub fn with_symbols(mut self, globs: &[String]) -> std::result::Result<Self, String> {
+        self.symbols.extend(symbol_patterns(globs)?);
+        Ok(self)
+    }
+
+    /// Config of the folder containing `file`.
+    fn rules_for(&self, file: &Path) -> Option<&EntryPointRules> {
+        self.rules
+            .iter()
+            .filter(|r| file.starts_with(&r.root))
+            .max_by_key(|r| r.root.components().count())
+    }
+
+    /// Why a definition is a root, or `None` when it is only live if
+    /// something reaches it.
+    pub fn root_kind(&self, definition: &Definition) -> Option<RootKind> {
+        let rules = self.rules_for(definition.file);
+        let recognizers = rules.map_or(&self.builtins, |r| &r.recognizers);
+        let annotations = annotations(definition.preceding);
+        if index::short_name(definition.name) == "main" {
+            Some(RootKind::Main)
+        } else if matches_symbol(&self.symbols, definition.name)
+            || rules.is_some_and(|r| r.allows(definition))
+        {
+            Some(RootKind::Allowlist)
+        } else if is_test_root(definition) {
+            Some(RootKind::Test)
+        } else if let Some(recognizer) = recognizers
+            .iter()
+            .find(|r| r.matches(definition, &annotations))
+        {
+            Some(recognizer.kind)
+        } else if rules.is_none_or(|r| r.public_api) && is_public_api(definition) {
+            Some(RootKind::PublicApi)
+        } else {
+            None
+        }
+    }
+
+    /// A decorator or attribute above a definition that no recognizer knows
+    /// and that may register the symbol with a framework.
+    pub fn unrecognized_annotation(&self, definition: &Definition) -> Option<String> {
+        annotations(definition.preceding).into_iter().find(|a| {
+            !INERT_ANNOTATIONS.contains(&a.as_str())
+                && ![".setter", ".getter", ".deleter"]
+                    .iter()
+                    .any(|s| a.ends_with(s))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn definition<'a>(name: &'a str, file: &'a Path, line: &'a str) -> Definition<'a> {
+        Definition {
+            name,
+            file,
+            line,
+            preceding: &[],
+            exported: false,
+            reexported: false,
+        }
+    }
+
+    #[test]
+    fn test_public_api_by_language() {
+        let rs = Path::new("/ws/src/lib.rs");
+        assert!(is_public_api(&definition("parse", rs, "pub fn parse() {")));
+        assert!(!is_public_api(&definition(
+            "parse",
+            rs,
+            "pub(crate) fn parse() {"
+        )));
+        assert!(!is_public_api(&definition("parse", rs, "fn parse() {")));
+
+        let ts = Path::new("/ws/src/app.ts");
+        assert!(is_public_api(&definition(
+            "render",
+            ts,
+            "export function render() {"
+        )));
+        assert!(!is_public_api(&definition(
+            "render",
+            ts,
+            "function render() {"
+        )));
+
+        let go = Path::new("/ws/pkg/api.go");
+        assert!(is_public_api(&definition("Serve", go, "func Serve() {")));
+        assert!(!is_public_api(&definition("serve", go, "func serve() {")));
+
+        let init = Path::new("/ws/pkg/__init__.py");
+        assert!(is_public_api(&definition("load", init, "def load():")));
+        assert!(!is_public_api(&definition("_load", init, "def _load():")));
+        assert!(!is_public_api(&definition(
+            "load",
+            Path::new("/ws/pkg/util.py"),
+            "def load():"
+        )));
+    }
+
+    #[test]
+    fn test_root_kinds() {
+        let root = PathBuf::from("/ws");
+        let config: EntryPointsConfig = toml::from_str(
+            r#"
+            symbols = ["cmd_*"]
+            files = ["scripts"]
+            public_api = false
+            "#,
+        )
+        .unwrap();
+        let entry_points = EntryPoints {
+            rules: vec![EntryPointRules::new(root, config).unwrap()],
+            ..EntryPoints::default()
+        }
+        .with_symbols(&["plugin::*".to_string()])
+        .unwrap();
+
+        let app = Path::new("/ws/src/app.rs");
+        let kind = |name: &str, file: &Path, line: &str| {
+            entry_points.root_kind(&definition(name, file, line))
+        };
+        assert_eq!(kind("main", app, "fn main() {"), Some(RootKind::Main));
+        assert_eq!(
+            kind("cmd_build", app, "fn cmd_build() {"),
+            Some(RootKind::Allowlist)
+        );
+        assert_eq!(
+            kind("plugin::load", app, "fn load() {"),
+            Some(RootKind::Allowlist)
+        );
+        assert_eq!(
+            kind("helper", Path::new("/ws/scripts/gen.rs"), "fn helper() {"),
+            Some(RootKind::Allowlist)
+        );
+        // public_api = false: exported but otherwise unreferenced code is dead.
+        assert_eq!(kind("parse", app, "pub fn parse() {"), None);
+        assert_eq!(kind("parse", app, "fn parse() {"), None);
+
+        let test = Definition {
+            preceding: &["#[test]"],
+            ..definition("tests::test_parse", app, "fn test_parse() {")
+        };
+        assert_eq!(entry_points.root_kind(&test), Some(RootKind::Test));
+    }
+
+    #[test]
+    fn test_annotations() {
+        let preceding = [
+            "x = 1",
+            "",
+            "@app.route(",
+            "    \"/users\",",
+            "    methods=[\"GET\"],",
+            ")",
+            "# comment",
+            "@login_required",
+        ];
+        assert_eq!(annotations(&preceding), vec!["login_required", "app.route"]);
+        assert_eq!(
+            annotations(&["fn other() {}", "#[unsafe(no_mangle)]", "/// Docs"]),
+            vec!["no_mangle"]
+        );
+        assert_eq!(
+            annotations(&["#[tokio::main(flavor = \"multi_thread\")]"]),
+            vec!["tokio::main"]
+        );
+        assert!(annotations(&["    return x"]).is_empty());
+    }
+
+    #[test]
+    fn test_framework_recognizers() {
+        let entry_points = EntryPoints::default();
+        let kind = |name: &str, file: &str, preceding: &[&str]| {
+            entry_points.root_kind(&Definition {
+                preceding,
+                ..definition(name, Path::new(file), "")
+            })
+        };
+        let framework = Some(RootKind::Framework);
+        assert_eq!(
+            kind("users", "/ws/app.py", &["@app.route(\"/\")"]),
+            framework
+        );
+        assert_eq!(
+            kind("users", "/ws/api.py", &["@router.get(\"/\")"]),
+            framework
+        );
+        assert_eq!(
+            kind("db", "/ws/conftest.py", &["@pytest.fixture"]),
+            framework
+        );
+        assert_eq!(kind("export", "/ws/ffi.rs", &["#[no_mangle]"]), framework);
+        assert_eq!(
+            kind("run", "/ws/bin.rs", &["#[tokio::main]"]),
+            Some(RootKind::Main)
+        );
+        assert_eq!(kind("init", "/ws/pkg/a.go", &[]), framework);
+        assert_eq!(
+            kind("BenchmarkParse", "/ws/pkg/a_test.go", &[]),
+            Some(RootKind::Test)
+        );
+        // Exported, but `go test` does not run it as a benchmark.
+        assert_eq!(
+            kind("Benchmarkparse", "/ws/pkg/a_test.go", &[]),
+            Some(RootKind::PublicApi)
+        );
+        // Names alone no longer make an entry point.
+        assert_eq!(kind("handleClick", "/ws/ui.ts", &[]), None);
+        assert_eq!(
+            kind("cached", "/ws/app.py", &["@functools.lru_cache"]),
+            None
+        );
+
+        let reexported = Definition {
+            reexported: true,
+            ..definition("render", Path::new("/ws/src/render.ts"), "")
+        };
+        assert_eq!(
+            entry_points.root_kind(&reexported),
+            Some(RootKind::PublicApi)
+        );
+
+        let task = Definition {
+            preceding: &["@celery.task", "@staticmethod"],
+            ..definition("send", Path::new("/ws/tasks.py"), "")
+        };
+        assert_eq!(entry_points.root_kind(&task), None);
+        assert_eq!(
+            entry_points.unrecognized_annotation(&task),
+            Some("celery.task".to_string())
+        );
+    }
+
+    #[test]
+    fn test_runtime_recognizers() {
+        let entry_points = EntryPoints::default();
+        let kind = |name: &str, file: &str, line: &str, preceding: &[&str]| {
+            entry_points.root_kind(&Definition {
+                preceding,
+                ..definition(name, Path::new(file), line)
+            })
+        };
+        let runtime = Some(RootKind::Runtime);
+        assert_eq!(
+            kind(
+                "Parser.__init__",
+                "/ws/app.py",
+                "    def __init__(self):",
+                &[]
+            ),
+            runtime
+        );
+        assert_eq!(
+            kind(
+                "Parser.__eq__",
+                "/ws/app.py",
+                "    def __eq__(self, o):",
+                &[]
+            ),
+            runtime
+        );
+        assert_eq!(
+            kind("Parser._reset", "/ws/app.py", "    def _reset(self):", &[]),
+            None
+        );
+        assert_eq!(
+            kind("Parser.constructor", "/ws/app.ts", "  constructor() {", &[]),
+            runtime
+        );
+
+        let impl_display = ["struct Span;", "", "impl fmt::Display for Span {"];
+        assert_eq!(
+            kind(
+                "Span.fmt",
+                "/ws/span.rs",
+                "    fn fmt(&self) {",
+                &impl_display
+            ),
+            runtime
+        );
+        let impl_generic = [
+            "impl<T> From<T> for Span",
+            "where",
+            "    T: Into<u32>,",
+            "{",
+            "    /// Docs",
+        ];
+        assert_eq!(
+            kind(
+                "Span.from",
+                "/ws/span.rs",
+                "    fn from(t: T) {",
+                &impl_generic
+            ),
+            runtime
+        );
+        let inherent = ["impl Span {", "    fn len(&self) {}"];
+        assert_eq!(
+            kind(
+                "Span.width",
+                "/ws/span.rs",
+                "    fn width(&self) {",
+                &inherent
+            ),
+            None
+        );
+
+        assert_eq!(
+            kind(
+                "buffer.Write",
+                "/ws/pkg/buf.go",
+                "func (b *buffer) Write(p []byte) (int, error) {",
+                &[]
+            ),
+            runtime
+        );
+        // Not a method, so it satisfies no interface.
+        assert_eq!(
+            kind("Close", "/ws/pkg/buf.go", "func Close() {", &[]),
+            Some(RootKind::PublicApi)
+        );
+    }
+
+    #[test]
+    fn test_configured_recognizers() {
+        let config: EntryPointsConfig = toml::from_str(
+            r#"
+            decorators = ["celery.task"]
+            attributes = ["wasm_bindgen"]
+            disable = ["flask"]
+            "#,
+        )
+        .unwrap();
+        let entry_points = EntryPoints {
+            rules: vec![EntryPointRules::new(PathBuf::from("/ws"), config).unwrap()],
+            ..EntryPoints::default()
+        };
+        let kind = |file: &str, preceding: &[&str]| {
+            entry_points.root_kind(&Definition {
+                preceding,
+                ..definition("f", Path::new(file), "")
+            })
+        };
+        assert_eq!(
+            kind("/ws/tasks.py", &["@celery.task"]),
+            Some(RootKind::Framework)
+        );
+        assert_eq!(
+            kind("/ws/lib.rs", &["#[wasm_bindgen]"]),
+            Some(RootKind::Framework)
+        );
+        assert_eq!(kind("/ws/app.py", &["@app.route(\"/\")"]), None);
+        // Folders without a config keep the built-ins.
+        assert_eq!(
+            kind("/other/app.py", &["@app.route(\"/\")"]),
+            Some(RootKind::Framework)
+        );
+
+        let unknown = EntryPointsConfig {
+            disable: vec!["django".to_string()],
+            ..Default::default()
+        };
+        assert!(EntryPointRules::new(PathBuf::from("/ws"), unknown).is_err());
+    }
+
+    #[test]
+    fn test_barrel_exports() {
+        let text = r#"
+            export * from './render';
+            export { parse, format as fmt, type Options } from "./parse";
+            export type { Token } from './lexer';
+            export * as util from './util';
+            export const local = 1;
+        "#;
+        assert_eq!(
+            barrel_exports(text),
+            vec![
+                ("./render".to_string(), None),
+                (
+                    "./parse".to_string(),
+                    Some(vec!["parse".to_string(), "format".to_string()])
+                ),
+                ("./lexer".to_string(), Some(vec!["Token".to_string()])),
+                ("./util".to_string(), None),
+            ]
+        );
+        assert!(is_barrel_file(Path::new("/ws/src/index.ts")));
+        assert!(!is_barrel_file(Path::new("/ws/src/index.py")));
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        let config = EntryPointsConfig {
+            symbols: vec!["[".to_string()],
+            ..Default::default()
+        };
+        assert!(EntryPointRules::new(PathBuf::from("/ws"), config).is_err());
+        assert!(toml::from_str::<EntryPointsConfig>("roots = []").is_err());
+    }
+}
@@ -0,0 +1,164 @@
+//! Graph Query Handler - Ad-hoc queries in a Cypher-like language.
+//!
+//! `codegraph/query` parses a query with [`crate::query`] and runs it against
+//! the code graph with a row limit and a timeout. Besides the properties the
+//! parsers store (`name`, `signature`, `complexity`, ...) and `id`/`kind`,
+//! every node has `file` (path relative to its workspace folder), `path`
+//! (absolute), `language`, `line` (1-based start line) and `isTest`.
+
+use crate::backend::CodeGraphBackend;
+use crate::handlers::test_selection::{is_test_file, TestRunner};
+use crate::lexer::Language;
+use crate::query::{self, Cell, Executor, Query};
+use codegraph::{CodeGraph, NodeId};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tower_lsp::jsonrpc::{Error, Result};
+
+const DEFAULT_LIMIT: usize = 100;
+const MAX_LIMIT: usize = 10_000;
+const DEFAULT_TIMEOUT_MS: u64 = 5_000;
+/// Queries hold the graph read lock, which stalls reindexing while they run.
+const MAX_TIMEOUT_MS: u64 = 10_000;
+
+// ==========================================
+// Query Types
+// ==========================================
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphQueryParams {
+    /// e.g. `MATCH (f:Function)-[:Calls]->(g) WHERE g.file STARTS WITH 'db/' RETURN f`
+    pub query: String,
+    /// Maximum rows (default 100, at most 10000); a `LIMIT` can lower it
+    pub limit: Option<usize>,
+    /// Stop searching after this many milliseconds (default 5000, at most 10000)
+    pub timeout_ms: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphQueryResponse {
+    pub columns: Vec<String>,
+    /// Node columns are `{id, name, kind, uri, range}`, property columns the
+    /// property value or null
+    pub rows: Vec<Vec<Value>>,
+    pub truncated: bool,
+    pub timed_out: bool,
+    pub elapsed_ms: u64,
+}
+
+impl CodeGraphBackend {
+    /// Run an ad-hoc graph query.
+    pub async fn handle_graph_query(&self, params: GraphQueryParams) -> Result<GraphQueryResponse> {
+        let started = Instant::now();
+        let parsed = query::parse(&params.query).map_err(Error::invalid_params)?;
+        let limit = params.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+        let timeout = params
+            .timeout_ms
+            .unwrap_or(DEFAULT_TIMEOUT_MS)
+            .min(MAX_TIMEOUT_MS);
+        let folders = self.workspace_folders.read().await.clone();
+        let graph = Arc::clone(&self.graph).read_owned().await;
+
+        // The search is CPU-bound and may run until the deadline, so keep it
+        // off the async runtime.
+        let backend = self.clone();
+        tokio::task::spawn_blocking(move || {
+            backend.run_graph_query(&graph, &parsed, &folders, limit, started, timeout)
+        })
+        .await
+        .map_err(|_| Error::internal_error())
+    }
+
+    fn run_graph_query(
+        &self,
+        graph: &CodeGraph,
+        parsed: &Query,
+        folders: &[PathBuf],
+        limit: usize,
+        started: Instant,
+        timeout: u64,
+    ) -> GraphQueryResponse {
+        let file_of = |node_id: NodeId| -> Option<PathBuf> {
+            graph
+                .get_node(node_id)
+                .ok()
+                .and_then(|n| n.properties.get_string("path"))
+                .map(PathBuf::from)
+                .or_else(|| self.symbol_index.find_file_for_node(node_id))
+        };
+        let property = |node_id: NodeId, key: &str| -> Option<Value> {
+            match key {
+                "file" => {
+                    let file = file_of(node_id)?;
+                    let relative = folders
+                        .iter()
+                        .find_map(|f| file.strip_prefix(f).ok())
+                        .unwrap_or(&file);
+                    Some(Value::from(relative.to_string_lossy().replace('\\', "/")))
+                }
+                "path" => file_of(node_id).map(|f| Value::from(f.to_string_lossy().to_string())),
+                "language" => {
+                    let language = Language::from_path(&file_of(node_id)?)?;
+                    Some(Value::from(format!("{language:?}").to_lowercase()))
+                }
+                "line" => graph
+                    .get_node(node_id)
+                    .ok()?
+                    .properties
+                    .get_int("line_start")
+                    .map(Value::from),
+                "isTest" => {
+                    let node = graph.get_node(node_id).ok()?;
+                    let in_test_file = file_of(node_id).is_some_and(|file| {
+                        Language::from_path(&file)
+                            .and_then(TestRunner::for_language)
+                            .is_some_and(|runner| {
+                                runner != TestRunner::Cargo && is_test_file(runner, &file)
+                            })
+                    });
+                    Some(Value::Bool(in_test_file || Self::is_test_node(node)))
+                }
+                _ => query::stored_property(graph, node_id, key),
+            }
+        };
+
+        let executor = Executor::new(graph, &property, started + Duration::from_millis(timeout));
+        let result = executor.execute(parsed, limit);
+
+        let rows = result
+            .rows
+            .into_iter()
+            .map(|row| {
+                row.into_iter()
+                    .map(|cell| match cell {
+                        Cell::Value(value) => value,
+                        Cell::Node(node_id) => {
+                            let node = graph.get_node(node_id).ok();
+                            let location = self.node_to_location(graph, node_id).ok();
+                            json!({
+                                "id": node_id.to_string(),
+                                "name": node.and_then(|n| n.properties.get_string("name")),
+                                "kind": node.map(|n| format!("{:?}", n.node_type)),
+                                "uri": location.as_ref().map(|l| l.uri.to_string()),
+                                "range": location.map(|l| l.range),
+                            })
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        GraphQueryResponse {
+            columns: result.columns,
+            rows,
+            truncated: result.truncated,
+            timed_out: result.timed_out,
+            elapsed_ms: started.elapsed().as_millis() as u64,
+        }
+    }
+}
@@ -0,0 +1,548 @@
+//! Key Symbols Handler - Symbol importance from graph centrality.
+//!
+//! PageRank and betweenness centrality over the call and import graph answer
+//! "what are the core abstractions of this repo". `codegraph/getKeySymbols`
+//! lists the highest ranked symbols, and workspace symbols, AI context and
+//! the unused-code list use the same scores for ordering.
+//!
+//! The projection has a node per function, class, interface and type plus one
+//! per file. Calls, instantiation, inheritance and references link symbols,
+//! methods point at their class, resolved imports link files and each file
+//! points at the symbols it defines, so an imported file lends weight to its
+//! contents. Scores are recomputed in the background once the graph has
+//! stopped changing, warm-starting PageRank from the previous result; until
+//! then requests are served the previous scores.
+
+use crate::algorithms::{betweenness_centrality, pagerank};
+use crate::backend::CodeGraphBackend;
+use crate::handlers::test_selection::{is_test_file, TestRunner};
+use crate::import_graph::ImportGraph;
+use crate::lexer::Language;
+use codegraph::{CodeGraph, Direction, EdgeType, NodeId, NodeType};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tower_lsp::jsonrpc::{Error, Result};
+use tower_lsp::lsp_types::{Range, Url};
+
+const DEFAULT_LIMIT: usize = 20;
+
+/// Quiet period after the last graph change before ranks are recomputed.
+const REFRESH_DEBOUNCE: Duration = Duration::from_millis(500);
+
+const DAMPING: f64 = 0.85;
+const MAX_ITERATIONS: usize = 100;
+const TOLERANCE: f64 = 1e-8;
+
+/// Betweenness is estimated from this many source nodes on larger graphs.
+const BETWEENNESS_SOURCES: usize = 256;
+
+/// Share of PageRank in the combined score; betweenness makes up the rest.
+const PAGERANK_WEIGHT: f64 = 0.7;
+
+const SYMBOL_TYPES: &[NodeType] = &[
+    NodeType::Function,
+    NodeType::Class,
+    NodeType::Interface,
+    NodeType::Type,
+];
+
+const SYMBOL_EDGES: &[EdgeType] = &[
+    EdgeType::Calls,
+    EdgeType::Invokes,
+    EdgeType::Instantiates,
+    EdgeType::Extends,
+    EdgeType::Implements,
+    EdgeType::Uses,
+    EdgeType::References,
+];
+
+// ==========================================
+// Ranking
+// ==========================================
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SymbolScore {
+    /// PageRank; all nodes of the projection sum to 1
+    pub pagerank: f64,
+    /// Betweenness relative to the most central node (0-1)
+    pub betweenness: f64,
+    /// Weighted blend of PageRank relative to the highest and betweenness (0-1)
+    pub score: f64,
+}
+
+/// Centrality of every symbol in the graph.
+#[derive(Debug, Default)]
+pub struct SymbolRanks {
+    pub scores: HashMap<NodeId, SymbolScore>,
+    /// Edges in the projection
+    pub edges: usize,
+    /// PageRank iterations of the last computation
+    pub iterations: usize,
+    /// PageRank by `kind:path:name`, which survives the new node IDs of a
+    /// reindex, for warm-starting the next computation
+    by_key: HashMap<String, f64>,
+    /// `(node_count, edge_count)` of the graph the scores belong to
+    stamp: (usize, usize),
+}
+
+impl SymbolRanks {
+    /// Combined score of a symbol, 0 for nodes that are not ranked.
+    pub fn score(&self, node_id: NodeId) -> f64 {
+        self.scores.get(&node_id).map_or(0.0, |s| s.score)
+    }
+
+    /// Sort node IDs by descending score, keeping the order of ties.
+    pub fn sort_by_importance(&self, node_ids: &mut [NodeId]) {
+        node_ids.sort_by(|a, b| self.score(*b).total_cmp(&self.score(*a)));
+    }
+}
+
+/// The latest [`SymbolRanks`], replaced by a background refresh after the
+/// graph changed.
+#[derive(Debug, Default)]
+pub struct SymbolRankCache {
+    /// Only locked to clone or swap the `Arc`
+    ranks: Mutex<Arc<SymbolRanks>>,
+    /// Bumped by every change that needs new ranks
+    generation: AtomicU64,
+    /// Whether a refresh task is running
+    pending: AtomicBool,
+}
+
+impl SymbolRankCache {
+    fn current(&self) -> Arc<SymbolRanks> {
+        Arc::clone(&self.ranks.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    fn store(&self, ranks: SymbolRanks) {
+        *self.ranks.lock().unwrap_or_else(|e| e.into_inner()) = Arc::new(ranks);
+    }
+}
+
+/// The graph projected for ranking, detached from the graph lock.
+struct RankProjection {
+    nodes: Vec<NodeId>,
+    /// `kind:path:name` per node, see [`SymbolRanks::by_key`]
+    keys: Vec<String>,
+    /// Nodes before this index are files
+    symbols_start: usize,
+    adjacency: Vec<Vec<usize>>,
+    stamp: (usize, usize),
+}
+
+impl RankProjection {
+    fn rank(self, previous: &SymbolRanks) -> SymbolRanks {
+        let Self {
+            nodes,
+            keys,
+            symbols_start,
+            adjacency,
+            stamp,
+        } = self;
+        let initial: Option<Vec<f64>> = (!previous.by_key.is_empty()).then(|| {
+            let fallback = 1.0 / nodes.len().max(1) as f64;
+            keys.iter()
+                .map(|k| previous.by_key.get(k).copied().unwrap_or(fallback))
+                .collect()
+        });
+        let (scores, iterations) = centrality_scores(&adjacency, initial.as_deref());
+
+        SymbolRanks {
+            scores: nodes[symbols_start..]
+                .iter()
+                .zip(&scores[symbols_start..])
+                .map(|(&node_id, &score)| (node_id, score))
+                .collect(),
+            edges: adjacency.iter().map(Vec::len).sum(),
+            iterations,
+            by_key: keys
+                .into_iter()
+                .zip(&scores)
+                .map(|(key, score)| (key, score.pagerank))
+                .collect(),
+            stamp,
+        }
+    }
+}
+
+/// PageRank, betweenness and combined score for every node of a projection,
+/// plus the number of PageRank iterations.
+pub fn centrality_scores(
+    adjacency: &[Vec<usize>],
+    initial: Option<&[f64]>,
+) -> (Vec<SymbolScore>, usize) {
+    let (ranks, iterations) = pagerank(adjacency, DAMPING, initial, MAX_ITERATIONS, TOLERANCE);
+    let betweenness = betweenness_centrality(adjacency, BETWEENNESS_SOURCES);
+    let max_rank = ranks.iter().copied().fold(0.0, f64::max);
+    let max_betweenness = betweenness.iter().copied().fold(0.0, f64::max);
+
+    let scores = ranks
+        .iter()
+        .zip(&betweenness)
+        .map(|(&rank, &between)| {
+            let rank_share = if max_rank > 0.0 { rank / max_rank } else { 0.0 };
+            let between_share = if max_betweenness > 0.0 {
+                between / max_betweenness
+            } else {
+                0.0
+            };
+            SymbolScore {
+                pagerank: rank,
+                betweenness: between_share,
+                score: PAGERANK_WEIGHT * rank_share + (1.0 - PAGERANK_WEIGHT) * between_share,
+            }
+        })
+        .collect();
+    (scores, iterations)
+}
+
+// ==========================================
+// Key Symbol Types
+// ==========================================
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeySymbolsParams {
+    /// Number of symbols to return (default 20)
+    pub limit: Option<usize>,
+    /// Only these node kinds, e.g. `["Class", "Interface"]`
+    pub kinds: Option<Vec<String>>,
+    /// Directory or file to restrict the list to (ranks stay workspace-wide)
+    pub uri: Option<String>,
+    /// Include test functions (default false)
+    pub include_tests: Option<bool>,
+    /// `score` (default), `pagerank` or `betweenness`
+    pub sort_by: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeySymbolsResponse {
+    /// Most important first
+    pub symbols: Vec<KeySymbol>,
+    pub summary: KeySymbolsSummary,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeySymbol {
+    pub node_id: String,
+    pub name: String,
+    pub kind: String,
+    pub uri: String,
+    pub range: Range,
+    #[serde(flatten)]
+    pub scores: SymbolScore,
+    /// Distinct callers
+    pub callers: usize,
+    /// Distinct callees
+    pub callees: usize,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeySymbolsSummary {
+    pub symbols_ranked: usize,
+    pub edges: usize,
+    pub iterations: usize,
+}
+
+impl CodeGraphBackend {
+    /// The latest symbol ranks. Never computes: if `graph` changed since they
+    /// were ranked, a background refresh is scheduled and the previous ranks
+    /// are returned meanwhile.
+    pub(crate) fn symbol_ranks(&self, graph: &CodeGraph) -> Arc<SymbolRanks> {
+        let ranks = self.rank_cache.current();
+        if ranks.stamp != (graph.node_count(), graph.edge_count())
+            && !self.rank_cache.pending.load(Ordering::SeqCst)
+        {
+            self.schedule_rank_refresh();
+        }
+        ranks
+    }
+
+    /// Recompute the symbol ranks once the graph has not changed for
+    /// [`REFRESH_DEBOUNCE`].
+    pub(crate) fn schedule_rank_refresh(&self) {
+        self.rank_cache.generation.fetch_add(1, Ordering::SeqCst);
+        if self.rank_cache.pending.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let backend = self.clone();
+        tokio::spawn(async move { backend.refresh_symbol_ranks().await });
+    }
+
+    async fn refresh_symbol_ranks(&self) {
+        loop {
+            let generation = self.rank_cache.generation.load(Ordering::SeqCst);
+            tokio::time::sleep(REFRESH_DEBOUNCE).await;
+            if self.rank_cache.generation.load(Ordering::SeqCst) != generation {
+                continue;
+            }
+
+            // Project under the read lock, then release it for the ranking.
+            let graph = Arc::clone(&self.graph).read_owned().await;
+            let backend = self.clone();
+            let previous = self.rank_cache.current();
+            let ranked = tokio::task::spawn_blocking(move || {
+                let projection = backend.rank_projection(&graph);
+                drop(graph);
+                projection.rank(&previous)
+            })
+            .await;
+            match ranked {
+                Ok(ranks) => self.rank_cache.store(ranks),
+                Err(e) => tracing::warn!("Symbol ranking failed: {}", e),
+            }
+
+            // Run again for changes made while ranking, unless a new refresh
+            // task took over after the flag was cleared.
+            self.rank_cache.pending.store(false, Ordering::SeqCst);
+            if self.rank_cache.generation.load(Ordering::SeqCst) == generation
+                || self.rank_cache.pending.swap(true, Ordering::SeqCst)
+            {
+                return;
+            }
+        }
+    }
+
+    fn rank_projection(&self, graph: &CodeGraph) -> RankProjection {
+        let mut nodes: Vec<NodeId> = Vec::new();
+        let mut keys: Vec<String> = Vec::new();
+        let mut index: HashMap<NodeId, usize> = HashMap::new();
+        let mut file_index: HashMap<PathBuf, usize> = HashMap::new();
+
+        for file_id in graph
+            .query()
+            .node_type(NodeType::CodeFile)
+            .execute()
+            .unwrap_or_default()
+        {
+            let Some(path) = graph
+                .get_node(file_id)
+                .ok()
+                .and_then(|n| n.properties.get_string("path"))
+            else {
+                continue;
+            };
+            file_index.insert(PathBuf::from(path), nodes.len());
+            keys.push(format!("CodeFile:{path}"));
+            index.insert(file_id, nodes.len());
+            nodes.push(file_id);
+        }
+        let symbols_start = nodes.len();
+        for &node_type in SYMBOL_TYPES {
+            for node_id in graph
+                .query()
+                .node_type(node_type)
+                .execute()
+                .unwrap_or_default()
+            {
+                let Ok(node) = graph.get_node(node_id) else {
+                    continue;
+                };
+                let path = self
+                    .symbol_index
+                    .find_file_for_node(node_id)
+                    .map(|p| p.display().to_string())
+                    .unwrap_or_default();
+                let name = node.properties.get_string("name").unwrap_or("");
+                keys.push(format!("{node_type:?}:{path}:{name}"));
+                index.insert(node_id, nodes.len());
+                nodes.push(node_id);
+            }
+        }
+
+        let mut adjacency: Vec<Vec<usize>> = vec![Vec::new(); nodes.len()];
+        for from in symbols_start..nodes.len() {
+            for (_, target, edge_type) in
+                self.get_connected_edges(graph, nodes[from], Direction::Outgoing)
+            {
+                let Some(&to) = index.get(&target) else {
+                    continue;
+                };
+                if to < symbols_start {
+                    continue;
+                }
+                if SYMBOL_EDGES.contains(&edge_type) {
+                    adjacency[from].push(to);
+                } else if edge_type == EdgeType::Contains {
+                    // A method makes its class more important, not the reverse.
+                    adjacency[to].push(from);
+                }
+            }
+        }
+        for (path, &file) in &file_index {
+            for symbol in self.symbol_index.get_file_symbols(path) {
+                if let Some(&to) = index.get(&symbol) {
+                    adjacency[file].push(to);
+                }
+            }
+        }
+        let imports = ImportGraph::build(graph);
+        for edge in &imports.edges {
+            if let (Some(&from), Some(&to)) = (
+                file_index.get(&imports.files[edge.from]),
+                file_index.get(&imports.files[edge.to]),
+            ) {
+                adjacency[from].push(to);
+            }
+        }
+        for (node, successors) in adjacency.iter_mut().enumerate() {
+            successors.retain(|&s| s != node);
+            successors.sort_unstable();
+            successors.dedup();
+        }
+
+        RankProjection {
+            nodes,
+            keys,
+            symbols_start,
+            adjacency,
+            stamp: (graph.node_count(), graph.edge_count()),
+        }
+    }
+
+    /// The most central symbols of the workspace.
+    pub async fn handle_get_key_symbols(
+        &self,
+        params: KeySymbolsParams,
+    ) -> Result<KeySymbolsResponse> {
+        let scope = match &params.uri {
+            Some(uri) => Some(
+                Url::parse(uri)
+                    .map_err(|_| Error::invalid_params("Invalid URI"))?
+                    .to_file_path()
+                    .map_err(|_| Error::invalid_params("Invalid file path"))?,
+            ),
+            None => None,
+        };
+        let metric: fn(&SymbolScore) -> f64 = match params.sort_by.as_deref() {
+            None | Some("score") => |s| s.score,
+            Some("pagerank") => |s| s.pagerank,
+            Some("betweenness") => |s| s.betweenness,
+            Some(other) => {
+                return Err(Error::invalid_params(format!(
+                    "Unknown sortBy '{other}', expected score, pagerank or betweenness"
+                )))
+            }
+        };
+        let kinds: Option<Vec<String>> = params
+            .kinds
+            .map(|k| k.iter().map(|k| k.to_lowercase()).collect());
+        let include_tests = params.include_tests.unwrap_or(false);
+        let limit = params.limit.unwrap_or(DEFAULT_LIMIT);
+
+        let graph = self.graph.read().await;
+        let ranks = self.symbol_ranks(&graph);
+
+        let mut candidates: Vec<(NodeId, SymbolScore)> = ranks
+            .scores
+            .iter()
+            .filter(|(&node_id, _)| {
+                let Ok(node) = graph.get_node(node_id) else {
+                    return false;
+                };
+                if kinds
+                    .as_ref()
+                    .is_some_and(|k| !k.contains(&format!("{:?}", node.node_type).to_lowercase()))
+                {
+                    return false;
+                }
+                let file = self.symbol_index.find_file_for_node(node_id);
+                if let Some(scope) = &scope {
+                    if !file.as_ref().is_some_and(|f| f.starts_with(scope)) {
+                        return false;
+                    }
+                }
+                include_tests
+                    || !(Self::is_test_node(node)
+                        || file.is_some_and(|file| {
+                            Language::from_path(&file)
+                                .and_then(TestRunner::for_language)
+                                .is_some_and(|runner| {
+                                    runner != TestRunner::Cargo && is_test_file(runner, &file)
+                                })
+                        }))
+            })
+            .map(|(&node_id, &score)| (node_id, score))
+            .collect();
+        candidates.sort_by(|a, b| {
+            metric(&b.1)
+                .total_cmp(&metric(&a.1))
+                .then_with(|| a.0.cmp(&b.0))
+        });
+        candidates.truncate(limit);
+
+        let symbols = candidates
+            .into_iter()
+            .filter_map(|(node_id, scores)| {
+                let node = graph.get_node(node_id).ok()?;
+                let location = self.node_to_location(&graph, node_id).ok()?;
+                let calls = |direction| {
+                    self.get_connected_edges(&graph, node_id, direction)
+                        .into_iter()
+                        .filter(|(_, _, t)| *t == EdgeType::Calls)
+                        .map(|(source, target, _)| if source == node_id { target } else { source })
+                        .collect::<HashSet<_>>()
+                        .len()
+                };
+                Some(KeySymbol {
+                    node_id: node_id.to_string(),
+                    name: node.properties.get_string("name").unwrap_or("").to_string(),
+                    kind: format!("{:?}", node.node_type),
+                    uri: location.uri.to_string(),
+                    range: location.range,
+                    scores,
+                    callers: calls(Direction::Incoming),
+                    callees: calls(Direction::Outgoing),
+                })
+            })
+            .collect();
+
+        Ok(KeySymbolsResponse {
+            symbols,
+            summary: KeySymbolsSummary {
+                symbols_ranked: ranks.scores.len(),
+                edges: ranks.edges,
+                iterations: ranks.iterations,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_centrality_scores_rank_hub_first() {
+        // 0, 1 and 2 call 3 (a hub), 3 calls 4 through 5.
+        let adjacency = vec![vec![3], vec![3], vec![3], vec![5], vec![], vec![4]];
+        let (scores, _) = centrality_scores(&adjacency, None);
+        assert!(scores[3].score > scores[0].score);
+        assert!(scores[3].pagerank > scores[1].pagerank);
+        assert!(scores.iter().all(|s| (0.0..=1.0).contains(&s.score)));
+        assert_eq!(scores[3].betweenness, 1.0);
+    }
+
+    #[test]
+    fn test_sort_by_importance_keeps_ties_in_order() {
+        let score = |score| SymbolScore {
+            score,
+            ..Default::default()
+        };
+        let ranks = SymbolRanks {
+            scores: HashMap::from([(1, score(0.2)), (2, score(0.9)), (3, score(0.2))]),
+            ..Default::default()
+        };
+        let mut ids = vec![3, 1, 7, 2];
+        ranks.sort_by_importance(&mut ids);
+        assert_eq!(ids, vec![2, 3, 1, 7]);
+    }
+}
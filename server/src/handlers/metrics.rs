@@ -2,9 +2,12 @@
 
 use crate::backend::CodeGraphBackend;
 use crate::handlers::ai_context::LocationInfo;
+use crate::handlers::cohesion::{ClassCohesion, GodClassThresholds};
+use crate::handlers::dead_code::DeadCluster;
+use crate::lexer::{tokenize, Language, TokenKind};
 use codegraph::{Direction, EdgeType, NodeId, NodeType};
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use tower_lsp::jsonrpc::Result;
 use tower_lsp::lsp_types::Url;
 
@@ -40,6 +43,10 @@ pub struct FunctionComplexity {
     pub grade: char,
     pub location: LocationInfo,
     pub details: ComplexityDetails,
+    /// Halstead measures of the function's tokens, if its source was readable
+    pub halstead: Option<HalsteadMetrics>,
+    /// Maintainability index, 0-100 (higher is better)
+    pub maintainability_index: Option<f64>,
 }
 
 #[derive(Debug, Serialize, Clone)]
@@ -57,6 +64,29 @@ pub struct ComplexityDetails {
     pub lines_of_code: u32,
 }
 
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HalsteadMetrics {
+    /// n1: distinct operators
+    pub distinct_operators: u32,
+    /// n2: distinct operands
+    pub distinct_operands: u32,
+    /// N1: total operators
+    pub total_operators: u32,
+    /// N2: total operands
+    pub total_operands: u32,
+    /// n = n1 + n2
+    pub vocabulary: u32,
+    /// N = N1 + N2
+    pub length: u32,
+    /// V = N * log2(n)
+    pub volume: f64,
+    /// D = (n1 / 2) * (N2 / n2)
+    pub difficulty: f64,
+    /// E = D * V
+    pub effort: f64,
+}
+
 // LocationInfo is imported from ai_context module
 
 #[derive(Debug, Serialize)]
@@ -67,6 +97,11 @@ pub struct FileSummary {
     pub max_complexity: u32,
     pub functions_above_threshold: u32,
     pub overall_grade: char,
+    /// Average maintainability index of the functions with readable source
+    pub average_maintainability_index: Option<f64>,
+    pub min_maintainability_index: Option<f64>,
+    pub total_halstead_volume: f64,
+    pub total_halstead_effort: f64,
 }
 
 // ==========================================
@@ -84,12 +119,16 @@ pub struct UnusedCodeParams {
     pub include_tests: Option<bool>,
     /// Minimum confidence threshold (0.0-1.0)
     pub confidence: Option<f64>,
+    /// Extra symbol name globs to treat as entry points
+    pub entry_points: Option<Vec<String>>,
 }
 
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct UnusedCodeResponse {
     pub unused_items: Vec<UnusedItem>,
+    /// Groups of unreachable functions and classes, largest first
+    pub dead_clusters: Vec<DeadCluster>,
     pub summary: UnusedSummary,
 }
 
@@ -106,6 +145,8 @@ pub struct UnusedItem {
     pub reason: String,
     /// Whether it's safe to remove without breaking external consumers
     pub safe_to_remove: bool,
+    /// Graph centrality of the symbol (0-1); 0 for imports and variables
+    pub importance: f64,
 }
 
 #[derive(Debug, Serialize)]
@@ -173,6 +214,8 @@ pub struct CohesionMetrics {
     pub cohesion_type: String,
     /// Percentage of internal references vs external
     pub internal_reference_ratio: f64,
+    /// LCOM4 of each class in the file, worst first
+    pub classes: Vec<ClassCohesion>,
 }
 
 #[derive(Debug, Serialize)]
@@ -188,10 +231,118 @@ pub struct ArchViolation {
 // Complexity Calculation
 // ==========================================
 
+/// Keywords that name a value rather than act on one.
+const OPERAND_KEYWORDS: &[&str] = &[
+    "true",
+    "false",
+    "True",
+    "False",
+    "None",
+    "null",
+    "undefined",
+    "nil",
+    "self",
+    "Self",
+    "this",
+];
+
+/// Halstead measures of a token stream. Identifiers, literals and value
+/// keywords are operands; other keywords and punctuation are operators,
+/// with bracket pairs counted once at the opening bracket.
+pub fn halstead_metrics(source: &str, language: Language) -> HalsteadMetrics {
+    let mut operators: HashMap<String, u32> = HashMap::new();
+    let mut operands: HashMap<String, u32> = HashMap::new();
+    for token in tokenize(source, language) {
+        let is_operand = match token.kind {
+            TokenKind::Identifier | TokenKind::Number | TokenKind::String => true,
+            TokenKind::Keyword => OPERAND_KEYWORDS.contains(&token.text.as_str()),
+            TokenKind::Operator => false,
+        };
+        if is_operand {
+            *operands.entry(token.text).or_default() += 1;
+        } else if !matches!(token.text.as_str(), ")" | "]" | "}") {
+            *operators.entry(token.text).or_default() += 1;
+        }
+    }
+
+    let n1 = operators.len() as u32;
+    let n2 = operands.len() as u32;
+    let total_operators: u32 = operators.values().sum();
+    let total_operands: u32 = operands.values().sum();
+    let vocabulary = n1 + n2;
+    let length = total_operators + total_operands;
+    let volume = if vocabulary > 1 {
+        length as f64 * (vocabulary as f64).log2()
+    } else {
+        0.0
+    };
+    let difficulty = if n2 > 0 {
+        (n1 as f64 / 2.0) * (total_operands as f64 / n2 as f64)
+    } else {
+        0.0
+    };
+    HalsteadMetrics {
+        distinct_operators: n1,
+        distinct_operands: n2,
+        total_operators,
+        total_operands,
+        vocabulary,
+        length,
+        volume,
+        difficulty,
+        effort: difficulty * volume,
+    }
+}
+
+/// Maintainability index rescaled to 0-100, as reported by Visual Studio:
+/// `max(0, (171 - 5.2 ln V - 0.23 CC - 16.2 ln LOC) * 100 / 171)`.
+pub fn maintainability_index(volume: f64, complexity: u32, lines_of_code: u32) -> f64 {
+    let raw = 171.0
+        - 5.2 * volume.max(1.0).ln()
+        - 0.23 * complexity as f64
+        - 16.2 * (lines_of_code.max(1) as f64).ln();
+    (raw * 100.0 / 171.0).clamp(0.0, 100.0)
+}
+
+/// Halstead measures and maintainability index of a function node, given
+/// the lines of its file.
+pub(crate) fn function_source_metrics(
+    lines: &[&str],
+    node: &codegraph::Node,
+    language: Language,
+    complexity: u32,
+) -> Option<(HalsteadMetrics, f64)> {
+    let start = node.properties.get_int("line_start")? as usize;
+    let end = node.properties.get_int("line_end").unwrap_or(start as i64) as usize;
+    if start == 0 || end < start || end > lines.len() {
+        return None;
+    }
+    let halstead = halstead_metrics(&lines[start - 1..end].join("\n"), language);
+    let index = maintainability_index(halstead.volume, complexity, (end - start + 1) as u32);
+    Some((halstead, index))
+}
+
+/// Maintainability and Halstead totals of a set of functions:
+/// (average index, minimum index, total volume, total effort).
+pub(crate) fn source_metrics_summary(
+    functions: &[FunctionComplexity],
+) -> (Option<f64>, Option<f64>, f64, f64) {
+    let indices: Vec<f64> = functions
+        .iter()
+        .filter_map(|f| f.maintainability_index)
+        .collect();
+    let average = (!indices.is_empty()).then(|| indices.iter().sum::<f64>() / indices.len() as f64);
+    let min = indices.iter().copied().reduce(f64::min);
+    let halstead = functions.iter().filter_map(|f| f.halstead.as_ref());
+    let volume = halstead.clone().map(|h| h.volume).sum();
+    let effort = halstead.map(|h| h.effort).sum();
+    (average, min, volume, effort)
+}
+
 impl CodeGraphBackend {
     /// Calculate cyclomatic complexity grade from score
     /// Uses same thresholds as upstream codegraph-parser-api ComplexityMetrics::grade()
-    fn complexity_grade(complexity: u32) -> char {
+    pub(crate) fn complexity_grade(complexity: u32) -> char {
         match complexity {
             1..=5 => 'A',   // Simple, low risk
             6..=10 => 'B',  // Moderate complexity
@@ -202,7 +353,7 @@ impl CodeGraphBackend {
     }
 
     /// Calculate overall file grade from average complexity
-    fn file_grade(avg_complexity: f64) -> char {
+    pub(crate) fn file_grade(avg_complexity: f64) -> char {
         match avg_complexity as u32 {
             0..=5 => 'A',
             6..=10 => 'B',
@@ -215,7 +366,9 @@ impl CodeGraphBackend {
     /// Get complexity details from a function node
     /// Primary: Uses AST-based complexity from upstream codegraph parsers (v0.3.0+)
     /// Fallback: Returns base complexity of 1 if no upstream data available
-    fn get_complexity_from_node(node: &codegraph::Node) -> (u32, ComplexityDetails, char) {
+    pub(crate) fn get_complexity_from_node(
+        node: &codegraph::Node,
+    ) -> (u32, ComplexityDetails, char) {
         let start = node.properties.get_int("line_start").unwrap_or(0) as u32;
         let end = node.properties.get_int("line_end").unwrap_or(0) as u32;
         let lines_of_code = end.saturating_sub(start) + 1;
@@ -266,6 +419,9 @@ impl CodeGraphBackend {
             .map_err(|_| tower_lsp::jsonrpc::Error::invalid_params("Invalid file path"))?;
 
         let threshold = params.threshold.unwrap_or(10);
+        let text = self.document_text(&path).unwrap_or_default();
+        let lines: Vec<&str> = text.lines().collect();
+        let language = Language::from_path(&path);
         let graph = self.graph.read().await;
 
         // Get all function nodes in this file
@@ -296,6 +452,9 @@ impl CodeGraphBackend {
 
                 // Get complexity from upstream codegraph parsers (AST-based)
                 let (complexity, details, grade) = Self::get_complexity_from_node(node);
+                let source_metrics = language.and_then(|language| {
+                    function_source_metrics(&lines, node, language, complexity)
+                });
 
                 let location = self
                     .node_to_location(&graph, node_id)
@@ -310,6 +469,8 @@ impl CodeGraphBackend {
                         range: location.range,
                     },
                     details,
+                    maintainability_index: source_metrics.as_ref().map(|(_, mi)| *mi),
+                    halstead: source_metrics.map(|(halstead, _)| halstead),
                 });
             }
         }
@@ -348,6 +509,16 @@ impl CodeGraphBackend {
             );
         }
 
+        let hard_to_maintain = functions
+            .iter()
+            .filter(|f| f.maintainability_index.is_some_and(|mi| mi < 20.0))
+            .count();
+        if hard_to_maintain > 0 {
+            recommendations.push(format!(
+                "{hard_to_maintain} function(s) have a maintainability index below 20. Shorten them and reduce the number of distinct operations."
+            ));
+        }
+
         let high_nesting: Vec<_> = functions
             .iter()
             .filter(|f| f.details.nesting_depth > 4)
@@ -359,24 +530,37 @@ impl CodeGraphBackend {
             ));
         }
 
+        let (average_maintainability_index, min_maintainability_index, volume, effort) =
+            source_metrics_summary(&functions);
+
         Ok(ComplexityResponse {
-            functions,
             file_summary: FileSummary {
                 total_functions,
                 average_complexity,
                 max_complexity,
                 functions_above_threshold,
                 overall_grade: Self::file_grade(average_complexity),
+                average_maintainability_index,
+                min_maintainability_index,
+                total_halstead_volume: volume,
+                total_halstead_effort: effort,
             },
+            functions,
             recommendations,
         })
     }
 
-    /// Find unused code in the codebase
+    /// Find unused code in the codebase.
+    ///
+    /// Functions and classes are dead when they cannot be reached from any
+    /// entry point, even if dead code still calls them.
     pub async fn handle_find_unused_code(
         &self,
         params: UnusedCodeParams,
     ) -> Result<UnusedCodeResponse> {
+        let entry_points = self
+            .load_entry_points(params.entry_points.as_deref().unwrap_or_default())
+            .await?;
         let graph = self.graph.read().await;
         let min_confidence = params.confidence.unwrap_or(0.7);
         let include_tests = params.include_tests.unwrap_or(false);
@@ -384,135 +568,72 @@ impl CodeGraphBackend {
         let mut unused_items: Vec<UnusedItem> = Vec::new();
         let mut functions_count = 0u32;
         let mut classes_count = 0u32;
-        let imports_count = 0u32;
-        let variables_count = 0u32;
+        let mut imports_count = 0u32;
+        let mut variables_count = 0u32;
         let mut total_lines = 0u32;
 
-        // Get nodes based on scope
-        let node_ids: Vec<NodeId> = if let Some(uri) = &params.uri {
-            let uri_parsed = Url::parse(uri)
-                .map_err(|_| tower_lsp::jsonrpc::Error::invalid_params("Invalid URI"))?;
-            let path = uri_parsed
-                .to_file_path()
-                .map_err(|_| tower_lsp::jsonrpc::Error::invalid_params("Invalid file path"))?;
-            self.symbol_index.get_file_symbols(&path)
-        } else {
-            // Workspace scope - get all function nodes
-            graph
-                .query()
-                .node_type(NodeType::Function)
-                .execute()
-                .unwrap_or_default()
+        let scope_path = match &params.uri {
+            Some(uri) => Some(
+                Url::parse(uri)
+                    .map_err(|_| tower_lsp::jsonrpc::Error::invalid_params("Invalid URI"))?
+                    .to_file_path()
+                    .map_err(|_| tower_lsp::jsonrpc::Error::invalid_params("Invalid file path"))?,
+            ),
+            None => None,
         };
 
-        for node_id in node_ids {
-            if let Ok(node) = graph.get_node(node_id) {
-                // Skip non-function/class nodes for now
-                if node.node_type != NodeType::Function && node.node_type != NodeType::Class {
-                    continue;
-                }
-
-                let name = node.properties.get_string("name").unwrap_or("").to_string();
-
-                // Skip test functions unless requested
-                if !include_tests
-                    && (name.starts_with("test_")
-                        || name.ends_with("_test")
-                        || name.contains("Test"))
-                {
-                    continue;
-                }
+        let liveness = self.liveness(&graph, &entry_points);
+        for item in self.unreachable_items(
+            &graph,
+            &liveness,
+            scope_path.as_deref(),
+            include_tests,
+            min_confidence,
+        ) {
+            match item.item_type.as_str() {
+                "class" => classes_count += 1,
+                _ => functions_count += 1,
+            }
+            if item.confidence > 0.8 {
+                total_lines += item.location.range.end.line - item.location.range.start.line + 1;
+            }
+            unused_items.push(item);
+        }
 
-                // Check if node has any incoming calls
-                let incoming = self.get_connected_edges(&graph, node_id, Direction::Incoming);
-                let has_callers = incoming
-                    .iter()
-                    .any(|(_, _, edge_type)| *edge_type == EdgeType::Calls);
-
-                if !has_callers {
-                    // Determine if this might be exported or an entry point
-                    let is_exported = node.properties.get_bool("exported").unwrap_or(false)
-                        || node
-                            .properties
-                            .get_string("visibility")
-                            .map(|v| v == "public")
-                            .unwrap_or(false);
-
-                    let is_entry = Self::is_entry_point(&name);
-                    let is_handler = name.contains("handle")
-                        || name.contains("Handler")
-                        || name.starts_with("on");
-                    let is_lifecycle = ["init", "setup", "teardown", "cleanup", "main", "run"]
-                        .iter()
-                        .any(|k| name.to_lowercase().contains(k));
-
-                    // Calculate confidence
-                    let confidence = if is_exported {
-                        0.4 // Low confidence - might be used externally
-                    } else if is_entry || is_handler || is_lifecycle {
-                        0.3 // Very low - likely an entry point
-                    } else {
-                        0.9 // High confidence - truly unused
-                    };
-
-                    if confidence >= min_confidence {
-                        let item_type = match node.node_type {
-                            NodeType::Function => "function",
-                            NodeType::Class => "class",
-                            _ => "unknown",
-                        };
-
-                        let reason = if is_exported {
-                            "Exported but no internal callers found"
-                        } else if is_entry {
-                            "Possible entry point with no callers"
-                        } else {
-                            "No callers found in codebase"
-                        };
-
-                        let start_line = node.properties.get_int("line_start").unwrap_or(0) as u32;
-                        let end_line = node.properties.get_int("line_end").unwrap_or(0) as u32;
-                        let lines = end_line.saturating_sub(start_line) + 1;
-
-                        if let Ok(location) = self.node_to_location(&graph, node_id) {
-                            unused_items.push(UnusedItem {
-                                item_type: item_type.to_string(),
-                                name: name.clone(),
-                                location: LocationInfo {
-                                    uri: location.uri.to_string(),
-                                    range: location.range,
-                                },
-                                confidence,
-                                reason: reason.to_string(),
-                                safe_to_remove: !is_exported && !is_entry && confidence > 0.8,
-                            });
-
-                            match node.node_type {
-                                NodeType::Function => functions_count += 1,
-                                NodeType::Class => classes_count += 1,
-                                _ => {}
-                            }
+        let dead_clusters = self.dead_clusters(&graph, &liveness, scope_path.as_deref());
 
-                            if confidence > 0.8 {
-                                total_lines += lines;
-                            }
-                        }
-                    }
-                }
+        // Unused imports and module-level variables
+        let files = match &scope_path {
+            Some(path) => vec![path.clone()],
+            None => self.symbol_index.files(),
+        };
+        for item in self.find_unused_bindings(&graph, &files, include_tests) {
+            if item.confidence < min_confidence {
+                continue;
+            }
+            match item.item_type.as_str() {
+                "import" => imports_count += 1,
+                _ => variables_count += 1,
+            }
+            if item.safe_to_remove {
+                total_lines += 1;
             }
+            unused_items.push(item);
         }
 
-        // Sort by confidence descending
+        // Sort by confidence descending, the most central symbols first
         unused_items.sort_by(|a, b| {
             b.confidence
                 .partial_cmp(&a.confidence)
                 .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| b.importance.total_cmp(&a.importance))
         });
 
         let safe_deletions = unused_items.iter().filter(|i| i.safe_to_remove).count() as u32;
 
         Ok(UnusedCodeResponse {
             unused_items,
+            dead_clusters,
             summary: UnusedSummary {
                 total_items: functions_count + classes_count + imports_count + variables_count,
                 by_type: UnusedByType {
@@ -527,20 +648,6 @@ impl CodeGraphBackend {
         })
     }
 
-    /// Check if a function name suggests it's an entry point
-    fn is_entry_point(name: &str) -> bool {
-        let lower = name.to_lowercase();
-        lower == "main"
-            || lower == "run"
-            || lower == "start"
-            || lower == "init"
-            || lower == "setup"
-            || lower.starts_with("export")
-            || lower.ends_with("handler")
-            || lower.ends_with("listener")
-            || lower.ends_with("callback")
-    }
-
     /// Analyze module coupling and cohesion
     pub async fn handle_analyze_coupling(
         &self,
@@ -669,6 +776,32 @@ impl CodeGraphBackend {
             );
         }
 
+        let classes = self.classes_cohesion(
+            &graph,
+            std::slice::from_ref(&path),
+            2,
+            GodClassThresholds::default(),
+        );
+        for class in &classes {
+            if class.is_god_class {
+                violations.push(ArchViolation {
+                    violation_type: "god_class".to_string(),
+                    severity: "warning".to_string(),
+                    description: format!(
+                        "`{}` has {} methods, fan-out {} and LCOM4 {}",
+                        class.name, class.methods, class.fan_out, class.lcom4
+                    ),
+                    suggestion: class
+                        .suggestions
+                        .get(1)
+                        .cloned()
+                        .unwrap_or_else(|| "Split the class along its method groups".to_string()),
+                });
+            } else if class.lcom4 > 1 {
+                recommendations.extend(class.suggestions.iter().cloned());
+            }
+        }
+
         if afferent > 10 {
             recommendations.push(format!(
                 "Many modules ({afferent}) depend on this one. Changes here have wide impact."
@@ -687,6 +820,7 @@ impl CodeGraphBackend {
                 score: internal_ratio,
                 cohesion_type: cohesion_type.to_string(),
                 internal_reference_ratio: internal_ratio,
+                classes,
             },
             violations,
             recommendations,
@@ -711,6 +845,34 @@ mod tests {
         assert_eq!(CodeGraphBackend::complexity_grade(51), 'F');
     }
 
+    #[test]
+    fn test_halstead_metrics() {
+        // a = b + c * b
+        let h = halstead_metrics("a = b + c * b", Language::Python);
+        assert_eq!((h.distinct_operators, h.total_operators), (3, 3));
+        assert_eq!((h.distinct_operands, h.total_operands), (3, 4));
+        assert_eq!((h.vocabulary, h.length), (6, 7));
+        assert!((h.volume - 7.0 * 6f64.log2()).abs() < 1e-9);
+        assert!((h.difficulty - 1.5 * 4.0 / 3.0).abs() < 1e-9);
+        assert!((h.effort - h.difficulty * h.volume).abs() < 1e-9);
+
+        // Bracket pairs count once.
+        let h = halstead_metrics("f(x)[0]", Language::TypeScript);
+        assert_eq!(h.total_operators, 2);
+    }
+
+    #[test]
+    fn test_maintainability_index() {
+        // A trivial one-liner is as maintainable as it gets.
+        assert_eq!(
+            maintainability_index(1.0, 1, 1),
+            (171.0 - 0.23) * 100.0 / 171.0
+        );
+        // A long, dense function bottoms out at zero.
+        assert_eq!(maintainability_index(1e9, 80, 2000), 0.0);
+        assert!(maintainability_index(500.0, 8, 40) < maintainability_index(100.0, 2, 10));
+    }
+
     #[test]
     fn test_file_grade() {
         assert_eq!(CodeGraphBackend::file_grade(3.0), 'A');
@@ -719,16 +881,4 @@ mod tests {
         assert_eq!(CodeGraphBackend::file_grade(20.0), 'D');
         assert_eq!(CodeGraphBackend::file_grade(30.0), 'F');
     }
-
-    #[test]
-    fn test_is_entry_point() {
-        assert!(CodeGraphBackend::is_entry_point("main"));
-        assert!(CodeGraphBackend::is_entry_point("Main"));
-        assert!(CodeGraphBackend::is_entry_point("clickHandler"));
-        assert!(CodeGraphBackend::is_entry_point("submitHandler"));
-        assert!(CodeGraphBackend::is_entry_point("eventListener"));
-        assert!(CodeGraphBackend::is_entry_point("onClickCallback"));
-        assert!(!CodeGraphBackend::is_entry_point("calculateSum"));
-        assert!(!CodeGraphBackend::is_entry_point("processData"));
-    }
 }
@@ -1,11 +1,49 @@
 //! Custom LSP request handlers for CodeGraph-specific features.
 
 pub mod ai_context;
+pub mod architecture;
+pub mod code_actions;
+pub mod cohesion;
+pub mod completion;
+pub mod complexity_report;
 pub mod custom;
+pub mod cycles;
+pub mod dead_code;
+pub mod diagnostics;
+pub mod document_highlight;
+pub mod dsm;
+pub mod duplicates;
+pub mod entry_points;
+pub mod graph_query;
+pub mod key_symbols;
 pub mod metrics;
 pub mod navigation;
+pub mod paths;
+pub mod semantic_tokens;
+pub mod signature_help;
+pub mod test_selection;
+pub mod unused;
 
 pub use ai_context::*;
+pub use architecture::*;
+pub use code_actions::*;
+pub use cohesion::*;
+pub use completion::*;
+pub use complexity_report::*;
 pub use custom::*;
+pub use cycles::*;
+pub use dead_code::*;
+pub use diagnostics::*;
+pub use document_highlight::*;
+pub use dsm::*;
+pub use duplicates::*;
+pub use entry_points::*;
+pub use graph_query::*;
+pub use key_symbols::*;
 pub use metrics::*;
 pub use navigation::*;
+pub use paths::*;
+pub use semantic_tokens::*;
+pub use signature_help::*;
+pub use test_selection::*;
+pub use unused::*;
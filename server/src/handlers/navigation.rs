@@ -122,13 +122,17 @@ impl CodeGraphBackend {
         params: WorkspaceSymbolsParams,
     ) -> Result<WorkspaceSymbolsResponse> {
         let graph = self.graph.read().await;
+        let ranks = self.symbol_ranks(&graph);
 
+        // Most important symbols first
         let node_ids = if let Some(query) = &params.query {
             if query.is_empty() {
                 // Return top-level symbols (modules, files)
                 self.symbol_index.get_by_type("Module")
             } else {
-                self.symbol_index.search_by_name(query)
+                let mut matches = self.symbol_index.search_by_name(query);
+                ranks.sort_by_importance(&mut matches);
+                matches
             }
         } else {
             // Return all symbols (limited)
@@ -136,6 +140,7 @@ impl CodeGraphBackend {
             all.extend(self.symbol_index.get_by_type("Function"));
             all.extend(self.symbol_index.get_by_type("Class"));
             all.extend(self.symbol_index.get_by_type("Module"));
+            ranks.sort_by_importance(&mut all);
             all.truncate(100); // Limit results
             all
         };
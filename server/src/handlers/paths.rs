@@ -0,0 +1,195 @@
+//! Path Finder Handler - Routes between two symbols.
+//!
+//! `codegraph/findPaths` answers "how does this handler end up calling that
+//! database function". It projects the part of the graph reachable from the
+//! source within `maxLength` hops over the allowed edge types onto a local
+//! adjacency list and runs Yen's K shortest simple paths on it. Every hop
+//! carries its location and the edge that led to it.
+
+use crate::algorithms::k_shortest_paths;
+use crate::backend::CodeGraphBackend;
+use codegraph::{CodeGraph, Direction, EdgeType, NodeId};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use tower_lsp::jsonrpc::{Error, Result};
+use tower_lsp::lsp_types::{Position, Range, Url};
+
+const DEFAULT_MAX_LENGTH: usize = 8;
+const DEFAULT_MAX_PATHS: usize = 3;
+
+/// Stop expanding the search once this many symbols are reachable.
+const MAX_SEARCH_NODES: usize = 50_000;
+
+// ==========================================
+// Path Types
+// ==========================================
+
+/// A symbol given by node ID or by a position in a document.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SymbolRef {
+    pub node_id: Option<String>,
+    pub uri: Option<String>,
+    pub position: Option<Position>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FindPathsParams {
+    pub source: SymbolRef,
+    pub target: SymbolRef,
+    /// Edge types a path may follow (default `["Calls"]`)
+    pub edge_types: Option<Vec<EdgeType>>,
+    /// Maximum number of hops (default 8)
+    pub max_length: Option<usize>,
+    /// Shortest path plus up to K-1 alternatives (default 3)
+    pub max_paths: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FindPathsResponse {
+    /// Shortest first; empty when the target is unreachable
+    pub paths: Vec<SymbolPath>,
+    /// The search stopped expanding before exhausting `maxLength`
+    pub truncated: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SymbolPath {
+    /// Number of edges
+    pub length: usize,
+    /// From source to target
+    pub hops: Vec<PathHop>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PathHop {
+    pub node_id: String,
+    pub name: String,
+    pub kind: String,
+    pub uri: String,
+    pub range: Range,
+    /// Edge from the previous hop, `None` for the source
+    pub via: Option<EdgeType>,
+}
+
+impl CodeGraphBackend {
+    fn resolve_symbol_ref(&self, graph: &CodeGraph, symbol: &SymbolRef) -> Result<NodeId> {
+        if let Some(node_id) = &symbol.node_id {
+            let node_id: NodeId = node_id
+                .parse()
+                .map_err(|_| Error::invalid_params("Invalid node ID"))?;
+            graph
+                .get_node(node_id)
+                .map_err(|_| Error::invalid_params(format!("Unknown node ID {node_id}")))?;
+            return Ok(node_id);
+        }
+        let (Some(uri), Some(position)) = (&symbol.uri, symbol.position) else {
+            return Err(Error::invalid_params(
+                "A symbol needs a nodeId or a uri and position",
+            ));
+        };
+        let path = Url::parse(uri)
+            .map_err(|_| Error::invalid_params("Invalid URI"))?
+            .to_file_path()
+            .map_err(|_| Error::invalid_params("Invalid file path"))?;
+        self.find_node_at_position(graph, &path, position)?
+            .ok_or_else(|| Error::invalid_params(format!("No symbol at {uri}:{}", position.line)))
+    }
+
+    fn path_hop(&self, graph: &CodeGraph, node_id: NodeId, via: Option<EdgeType>) -> PathHop {
+        let node = graph.get_node(node_id).ok();
+        let location = self.node_to_location(graph, node_id).ok();
+        PathHop {
+            node_id: node_id.to_string(),
+            name: node
+                .and_then(|n| n.properties.get_string("name"))
+                .unwrap_or("")
+                .to_string(),
+            kind: node
+                .map(|n| format!("{:?}", n.node_type))
+                .unwrap_or_default(),
+            uri: location
+                .as_ref()
+                .map(|l| l.uri.to_string())
+                .unwrap_or_default(),
+            range: location.map(|l| l.range).unwrap_or_default(),
+            via,
+        }
+    }
+
+    /// Find the shortest path and alternatives between two symbols.
+    pub async fn handle_find_paths(&self, params: FindPathsParams) -> Result<FindPathsResponse> {
+        let edge_types = params.edge_types.unwrap_or_else(|| vec![EdgeType::Calls]);
+        let max_length = params.max_length.unwrap_or(DEFAULT_MAX_LENGTH);
+        let max_paths = params.max_paths.unwrap_or(DEFAULT_MAX_PATHS);
+        let graph = self.graph.read().await;
+
+        let source = self.resolve_symbol_ref(&graph, &params.source)?;
+        let target = self.resolve_symbol_ref(&graph, &params.target)?;
+
+        // Project everything within `max_length` hops of the source.
+        let mut nodes = vec![source];
+        let mut index: HashMap<NodeId, usize> = HashMap::from([(source, 0)]);
+        let mut adjacency: Vec<Vec<usize>> = vec![Vec::new()];
+        let mut edge_of: HashMap<(usize, usize), EdgeType> = HashMap::new();
+        let mut queue = VecDeque::from([(source, 0)]);
+        let mut truncated = false;
+        while let Some((node_id, depth)) = queue.pop_front() {
+            if depth == max_length || node_id == target {
+                continue;
+            }
+            let from = index[&node_id];
+            for (_, next, edge_type) in
+                self.get_connected_edges(&graph, node_id, Direction::Outgoing)
+            {
+                if !edge_types.contains(&edge_type) {
+                    continue;
+                }
+                let to = match index.get(&next) {
+                    Some(&to) => to,
+                    None if nodes.len() >= MAX_SEARCH_NODES => {
+                        truncated = true;
+                        continue;
+                    }
+                    None => {
+                        index.insert(next, nodes.len());
+                        nodes.push(next);
+                        adjacency.push(Vec::new());
+                        queue.push_back((next, depth + 1));
+                        nodes.len() - 1
+                    }
+                };
+                if edge_of.insert((from, to), edge_type).is_none() {
+                    adjacency[from].push(to);
+                }
+            }
+        }
+
+        let paths = match index.get(&target) {
+            Some(&target) => k_shortest_paths(&adjacency, 0, target, max_paths, max_length),
+            None => Vec::new(),
+        };
+        let paths = paths
+            .into_iter()
+            .map(|path| SymbolPath {
+                length: path.len() - 1,
+                hops: path
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &node)| {
+                        let via = i
+                            .checked_sub(1)
+                            .and_then(|prev| edge_of.get(&(path[prev], node)).copied());
+                        self.path_hop(&graph, nodes[node], via)
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Ok(FindPathsResponse { paths, truncated })
+    }
+}
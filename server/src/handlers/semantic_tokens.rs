@@ -0,0 +1,386 @@
+//! Semantic Tokens Handler - Graph facts as token modifiers.
+//!
+//! Plain syntax colouring is left to the editor grammar. This handler only
+//! emits tokens for identifiers that resolve to a graph symbol, typed by the
+//! symbol's node type and carrying modifiers the grammar cannot know: the
+//! symbol is unused, defined outside the workspace, a test, deprecated, or
+//! lives in a different architectural layer than the file being edited.
+
+use crate::backend::CodeGraphBackend;
+use crate::handlers::dead_code::Liveness;
+use crate::handlers::entry_points::RootKind;
+use crate::handlers::test_selection::{is_test_file, TestRunner};
+use crate::lexer::Language;
+use crate::occurrences::{self, OccurrenceKind};
+use codegraph::{CodeGraph, Node, NodeId, NodeType};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tower_lsp::jsonrpc::Result;
+use tower_lsp::lsp_types::{
+    Range, SemanticToken, SemanticTokenModifier, SemanticTokenType, SemanticTokens,
+    SemanticTokensLegend, SemanticTokensParams, SemanticTokensResult,
+};
+
+/// Token types, in legend order.
+const TOKEN_TYPES: &[SemanticTokenType] = &[
+    SemanticTokenType::NAMESPACE,
+    SemanticTokenType::CLASS,
+    SemanticTokenType::INTERFACE,
+    SemanticTokenType::TYPE,
+    SemanticTokenType::FUNCTION,
+    SemanticTokenType::METHOD,
+    SemanticTokenType::VARIABLE,
+];
+
+/// Token modifier bits, in legend order.
+pub mod modifiers {
+    pub const DECLARATION: u32 = 1 << 0;
+    pub const DEPRECATED: u32 = 1 << 1;
+    pub const UNUSED: u32 = 1 << 2;
+    pub const EXTERNAL: u32 = 1 << 3;
+    pub const TEST: u32 = 1 << 4;
+    pub const CROSS_LAYER: u32 = 1 << 5;
+}
+
+/// Legend advertised in the server capabilities.
+pub fn legend() -> SemanticTokensLegend {
+    SemanticTokensLegend {
+        token_types: TOKEN_TYPES.to_vec(),
+        token_modifiers: vec![
+            SemanticTokenModifier::DECLARATION,
+            SemanticTokenModifier::DEPRECATED,
+            SemanticTokenModifier::new("unused"),
+            SemanticTokenModifier::new("external"),
+            SemanticTokenModifier::new("test"),
+            SemanticTokenModifier::new("crossLayer"),
+        ],
+    }
+}
+
+/// Legend index of the token type for a symbol.
+fn token_type(node_type: NodeType, name: &str) -> Option<u32> {
+    let token_type = match node_type {
+        NodeType::Module => SemanticTokenType::NAMESPACE,
+        NodeType::Class => SemanticTokenType::CLASS,
+        NodeType::Interface => SemanticTokenType::INTERFACE,
+        NodeType::Type => SemanticTokenType::TYPE,
+        NodeType::Function if name.contains('.') || name.contains("::") => {
+            SemanticTokenType::METHOD
+        }
+        NodeType::Function => SemanticTokenType::FUNCTION,
+        NodeType::Variable => SemanticTokenType::VARIABLE,
+        NodeType::CodeFile | NodeType::Generic => return None,
+    };
+    TOKEN_TYPES
+        .iter()
+        .position(|t| *t == token_type)
+        .map(|i| i as u32)
+}
+
+/// Whether the lines around a definition mark it deprecated: a
+/// `#[deprecated]` attribute, a `@deprecated` decorator or JSDoc tag, or a Go
+/// `// Deprecated:` comment directly above it.
+pub fn has_deprecation_marker<S: AsRef<str>>(lines: &[S], definition_line: usize) -> bool {
+    let is_annotation = |line: &str| {
+        let line = line.trim_start();
+        ["#", "@", "//", "/*", "*"]
+            .iter()
+            .any(|p| line.starts_with(p))
+    };
+    let mentions = |line: &str| line.to_lowercase().contains("deprecated");
+
+    if lines.get(definition_line).is_some_and(|l| {
+        let l = l.as_ref().to_lowercase();
+        l.contains("@deprecated") || l.contains("#[deprecated")
+    }) {
+        return true;
+    }
+    lines[..definition_line.min(lines.len())]
+        .iter()
+        .rev()
+        .take_while(|l| is_annotation(l.as_ref()))
+        .any(|l| mentions(l.as_ref()))
+}
+
+/// Delta-encode absolute single-line tokens as required by the protocol.
+pub fn encode(mut tokens: Vec<(Range, u32, u32)>) -> Vec<SemanticToken> {
+    tokens.sort_by_key(|(range, _, _)| (range.start.line, range.start.character));
+    tokens.dedup_by_key(|(range, _, _)| range.start);
+
+    let mut data = Vec::with_capacity(tokens.len());
+    let mut previous = (0u32, 0u32);
+    for (range, token_type, token_modifiers_bitset) in tokens {
+        let delta_line = range.start.line - previous.0;
+        let delta_start = if delta_line == 0 {
+            range.start.character - previous.1
+        } else {
+            range.start.character
+        };
+        data.push(SemanticToken {
+            delta_line,
+            delta_start,
+            length: range.end.character.saturating_sub(range.start.character),
+            token_type,
+            token_modifiers_bitset,
+        });
+        previous = (range.start.line, range.start.character);
+    }
+    data
+}
+
+impl CodeGraphBackend {
+    /// Handle textDocument/semanticTokens/full.
+    pub async fn handle_semantic_tokens_full(
+        &self,
+        params: SemanticTokensParams,
+    ) -> Result<Option<SemanticTokensResult>> {
+        let path = params
+            .text_document
+            .uri
+            .to_file_path()
+            .map_err(|_| tower_lsp::jsonrpc::Error::invalid_params("Invalid URI"))?;
+
+        let folders = self.workspace_folders.read().await.clone();
+        let liveness = self.unused_code_liveness().await;
+        let graph = self.graph.read().await;
+        let file_layer = Self::detect_file_layer(&path, &folders);
+        let liveness = SymbolLiveness::new(&liveness);
+
+        let mut facts: HashMap<NodeId, Option<(u32, u32)>> = HashMap::new();
+        let mut sources: HashMap<PathBuf, Vec<String>> = HashMap::new();
+        let mut tokens = Vec::new();
+
+        for occurrence in self.occurrence_index.file_occurrences(&path) {
+            if occurrence.range.start.line != occurrence.range.end.line {
+                continue;
+            }
+            let Some(target) = occurrences::resolve(&graph, &self.symbol_index, &path, &occurrence)
+            else {
+                continue;
+            };
+            let symbol = *facts.entry(target).or_insert_with(|| {
+                self.symbol_facts(
+                    &graph,
+                    target,
+                    &liveness,
+                    file_layer.as_deref(),
+                    &folders,
+                    &mut sources,
+                )
+            });
+            if let Some((token_type, mut bits)) = symbol {
+                if occurrence.kind == OccurrenceKind::Definition {
+                    bits |= modifiers::DECLARATION;
+                }
+                tokens.push((occurrence.range, token_type, bits));
+            }
+        }
+
+        Ok(Some(SemanticTokensResult::Tokens(SemanticTokens {
+            result_id: None,
+            data: encode(tokens),
+        })))
+    }
+
+    /// Token type and modifiers of a symbol, independent of where it is used.
+    fn symbol_facts(
+        &self,
+        graph: &CodeGraph,
+        node_id: NodeId,
+        liveness: &SymbolLiveness,
+        file_layer: Option<&str>,
+        folders: &[PathBuf],
+        sources: &mut HashMap<PathBuf, Vec<String>>,
+    ) -> Option<(u32, u32)> {
+        let node = graph.get_node(node_id).ok()?;
+        let name = node.properties.get_string("name").unwrap_or("");
+        let token_type = token_type(node.node_type, name)?;
+        let file = self.symbol_index.find_file_for_node(node_id);
+
+        let mut bits = 0;
+
+        let is_test = liveness.root(node_id) == Some(RootKind::Test)
+            || file.as_deref().is_some_and(|f| {
+                Language::from_path(f)
+                    .and_then(TestRunner::for_language)
+                    .is_some_and(|runner| runner != TestRunner::Cargo && is_test_file(runner, f))
+            });
+        if is_test {
+            bits |= modifiers::TEST;
+        }
+
+        let is_external = node.properties.get_string("external") == Some("true")
+            || node.properties.get_bool("external") == Some(true);
+        if is_external {
+            bits |= modifiers::EXTERNAL;
+        }
+
+        if !is_test && liveness.is_dead(node_id) {
+            bits |= modifiers::UNUSED;
+        }
+
+        if let Some(file) = &file {
+            if self.is_deprecated(node, node_id, file, sources) {
+                bits |= modifiers::DEPRECATED;
+            }
+            let layer = Self::detect_file_layer(file, folders);
+            if file_layer.is_some() && layer.is_some() && layer.as_deref() != file_layer {
+                bits |= modifiers::CROSS_LAYER;
+            }
+        }
+
+        Some((token_type, bits))
+    }
+
+    fn is_deprecated(
+        &self,
+        node: &Node,
+        node_id: NodeId,
+        file: &Path,
+        sources: &mut HashMap<PathBuf, Vec<String>>,
+    ) -> bool {
+        if node.properties.get_bool("deprecated") == Some(true)
+            || node.properties.get_string("deprecated") == Some("true")
+        {
+            return true;
+        }
+        if node.properties.get_string("doc").is_some_and(|doc| {
+            let doc = doc.trim_start().to_lowercase();
+            doc.starts_with("deprecated") || doc.contains(".. deprecated::")
+        }) {
+            return true;
+        }
+
+        let Some(range) = self.symbol_index.get_node_range(file, node_id) else {
+            return false;
+        };
+        let lines = sources.entry(file.to_path_buf()).or_insert_with(|| {
+            self.document_text(file)
+                .map(|text| text.lines().map(str::to_string).collect())
+                .unwrap_or_default()
+        });
+        has_deprecation_marker(lines, range.start_line.saturating_sub(1) as usize)
+    }
+}
+
+/// The shared liveness result, looked up by node.
+struct SymbolLiveness<'a> {
+    liveness: &'a Liveness,
+    index: HashMap<NodeId, usize>,
+}
+
+impl<'a> SymbolLiveness<'a> {
+    fn new(liveness: &'a Liveness) -> Self {
+        let index = liveness
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(i, &id)| (id, i))
+            .collect();
+        Self { liveness, index }
+    }
+
+    fn root(&self, node_id: NodeId) -> Option<RootKind> {
+        self.index
+            .get(&node_id)
+            .and_then(|&i| self.liveness.roots[i])
+    }
+
+    /// Unreachable from every entry point, with no annotation that a
+    /// framework might register it through.
+    fn is_dead(&self, node_id: NodeId) -> bool {
+        self.index
+            .get(&node_id)
+            .is_some_and(|&i| !self.liveness.live[i] && self.liveness.annotations[i].is_none())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower_lsp::lsp_types::Position;
+
+    fn range(line: u32, start: u32, end: u32) -> Range {
+        Range {
+            start: Position {
+                line,
+                character: start,
+            },
+            end: Position {
+                line,
+                character: end,
+            },
+        }
+    }
+
+    #[test]
+    fn test_encode_relative_positions() {
+        let data = encode(vec![
+            (range(2, 10, 14), 4, 0),
+            (range(0, 4, 8), 4, modifiers::DECLARATION),
+            (range(2, 2, 5), 1, modifiers::UNUSED),
+        ]);
+        let flat: Vec<(u32, u32, u32, u32, u32)> = data
+            .iter()
+            .map(|t| {
+                (
+                    t.delta_line,
+                    t.delta_start,
+                    t.length,
+                    t.token_type,
+                    t.token_modifiers_bitset,
+                )
+            })
+            .collect();
+        assert_eq!(
+            flat,
+            vec![
+                (0, 4, 4, 4, modifiers::DECLARATION),
+                (2, 2, 3, 1, modifiers::UNUSED),
+                (0, 8, 4, 4, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_token_type_indices_match_legend() {
+        let legend = legend();
+        let function = token_type(NodeType::Function, "helper").unwrap();
+        let method = token_type(NodeType::Function, "Parser.parse").unwrap();
+        assert_eq!(
+            legend.token_types[function as usize],
+            SemanticTokenType::FUNCTION
+        );
+        assert_eq!(
+            legend.token_types[method as usize],
+            SemanticTokenType::METHOD
+        );
+        assert_eq!(token_type(NodeType::CodeFile, "a.py"), None);
+        assert_eq!(legend.token_modifiers.len(), 6);
+        assert_eq!(legend.token_modifiers[5].as_str(), "crossLayer");
+    }
+
+    #[test]
+    fn test_deprecation_markers() {
+        let rust = ["#[deprecated(note = \"use bar\")]", "pub fn foo() {}"];
+        assert!(has_deprecation_marker(&rust, 1));
+
+        let python = ["@deprecated(\"use bar\")", "@cache", "def foo():"];
+        assert!(has_deprecation_marker(&python, 2));
+
+        let go = [
+            "// Foo does things.",
+            "//",
+            "// Deprecated: use Bar.",
+            "func Foo() {}",
+        ];
+        assert!(has_deprecation_marker(&go, 3));
+
+        let ts = ["/** @deprecated use bar */ function foo() {}"];
+        assert!(has_deprecation_marker(&ts, 0));
+
+        // A deprecated symbol further up does not leak past code lines.
+        let other = ["#[deprecated]", "fn old() {}", "fn foo() {}"];
+        assert!(!has_deprecation_marker(&other, 2));
+    }
+}
@@ -0,0 +1,469 @@
+//! Signature Help Handler - Parameter hints from graph signatures.
+//!
+//! While the cursor is inside a call's argument list, the callee is looked up
+//! by name in the symbol index and its recorded `signature` property is shown
+//! with the argument under the cursor highlighted. When several functions
+//! share the name, all of them are listed, nearest first: the one the call
+//! resolves to, then functions in files the current file imports, then the
+//! same directory, then the rest of the workspace.
+
+use crate::backend::CodeGraphBackend;
+use crate::index;
+use crate::lexer::{self, Language, Token, TokenKind};
+use crate::occurrences::{self, Occurrence, OccurrenceKind};
+use codegraph::{CodeGraph, NodeId, NodeType};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tower_lsp::jsonrpc::Result;
+use tower_lsp::lsp_types::{
+    Documentation, MarkupContent, MarkupKind, ParameterInformation, ParameterLabel, Position,
+    SignatureHelp, SignatureHelpParams, SignatureInformation,
+};
+
+/// Maximum number of signatures listed for one call.
+const MAX_SIGNATURES: usize = 10;
+
+/// The call whose argument list contains the cursor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallContext {
+    /// Index of the callee name token.
+    pub name_index: usize,
+    /// 0-indexed argument the cursor is in.
+    pub active_argument: u32,
+    /// Whether the callee is invoked as a method (`obj.name(`), so a
+    /// receiver parameter in its signature is not passed explicitly.
+    pub is_method_call: bool,
+}
+
+/// Find the innermost call whose argument list is still open at `position`.
+///
+/// Brackets that do not open a call (tuples, lists, object literals) are
+/// stepped over, so `f([1, 2], |` still reports the second argument of `f`.
+pub fn call_context(
+    tokens: &[Token],
+    position: Position,
+    language: Language,
+) -> Option<CallContext> {
+    let before = tokens.partition_point(|t| t.start < position);
+    let tokens = &tokens[..before];
+
+    let mut depth = 0usize;
+    let mut commas = 0u32;
+    for i in (0..tokens.len()).rev() {
+        let token = &tokens[i];
+        if token.kind != TokenKind::Operator {
+            continue;
+        }
+        match token.text.as_str() {
+            ")" | "]" | "}" => depth += 1,
+            "(" | "[" | "{" if depth > 0 => depth -= 1,
+            "," if depth == 0 => commas += 1,
+            "(" => {
+                if let Some(name_index) = callee_before(tokens, i) {
+                    if lexer::is_definition_name(tokens, name_index, language) {
+                        return None;
+                    }
+                    let is_method_call = name_index
+                        .checked_sub(1)
+                        .is_some_and(|p| tokens[p].is(".") || tokens[p].is("?."));
+                    return Some(CallContext {
+                        name_index,
+                        active_argument: commas,
+                        is_method_call,
+                    });
+                }
+                commas = 0;
+            }
+            "[" | "{" => commas = 0,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Index of the identifier naming the call opened at `open`, if it is a call.
+fn callee_before(tokens: &[Token], open: usize) -> Option<usize> {
+    let mut i = open.checked_sub(1)?;
+    // Rust turbofish: `name::<T>(`
+    if tokens[i].is(">") {
+        let mut depth = 0i32;
+        loop {
+            if tokens[i].is(">") {
+                depth += 1;
+            } else if tokens[i].is("<") {
+                depth -= 1;
+                if depth == 0 {
+                    break;
+                }
+            }
+            i = i.checked_sub(1)?;
+        }
+        i = i.checked_sub(1).filter(|&k| tokens[k].is("::"))?;
+        i = i.checked_sub(1)?;
+    }
+    (tokens[i].kind == TokenKind::Identifier).then_some(i)
+}
+
+/// UTF-16 offsets of each parameter in the parameter list of `signature`.
+///
+/// The list is the first parenthesised group after `name`, which skips a Go
+/// method receiver (`func (s *Server) Start(addr string)`).
+pub fn parameter_spans(signature: &str, name: &str) -> Vec<[u32; 2]> {
+    let search_from = signature
+        .find(&format!("{name}("))
+        .or_else(|| signature.find(name))
+        .map(|i| i + name.len())
+        .unwrap_or(0);
+    let Some(open) = signature[search_from..].find('(').map(|i| i + search_from) else {
+        return Vec::new();
+    };
+
+    let mut spans = Vec::new();
+    let mut depth = 0i32;
+    let mut start = open + 1;
+    let mut prev = '\0';
+    for (i, c) in signature[open + 1..].char_indices() {
+        let i = i + open + 1;
+        match c {
+            '(' | '[' | '{' | '<' => depth += 1,
+            '>' if prev == '-' || prev == '=' => {}
+            ')' | ']' | '}' | '>' if depth > 0 => depth -= 1,
+            ')' => {
+                spans.push((start, i));
+                break;
+            }
+            ',' if depth == 0 => {
+                spans.push((start, i));
+                start = i + 1;
+            }
+            _ => {}
+        }
+        prev = c;
+    }
+
+    let utf16 = |byte: usize| signature[..byte].encode_utf16().count() as u32;
+    spans
+        .into_iter()
+        .filter_map(|(start, end)| {
+            let text = &signature[start..end];
+            let trimmed = text.trim();
+            if trimmed.is_empty() {
+                return None;
+            }
+            let offset = start + (text.len() - text.trim_start().len());
+            Some([utf16(offset), utf16(offset + trimmed.len())])
+        })
+        .collect()
+}
+
+/// Whether a parameter is the method receiver (`self`, `&mut self`, `cls`).
+fn is_receiver(parameter: &str) -> bool {
+    let p = parameter.trim().trim_start_matches('&');
+    let p = if p.starts_with('\'') {
+        p.split_once(' ').map(|(_, rest)| rest).unwrap_or(p)
+    } else {
+        p
+    };
+    let p = p.trim_start_matches("mut ").trim();
+    p == "self" || p.starts_with("self:") || p == "cls"
+}
+
+impl CodeGraphBackend {
+    /// Handle textDocument/signatureHelp.
+    pub async fn handle_signature_help(
+        &self,
+        params: SignatureHelpParams,
+    ) -> Result<Option<SignatureHelp>> {
+        let position = params.text_document_position_params.position;
+        let path = params
+            .text_document_position_params
+            .text_document
+            .uri
+            .to_file_path()
+            .map_err(|_| tower_lsp::jsonrpc::Error::invalid_params("Invalid URI"))?;
+
+        let Some(language) = Language::from_path(&path) else {
+            return Ok(None);
+        };
+        let Some(text) = self.document_text(&path) else {
+            return Ok(None);
+        };
+        let tokens = lexer::tokenize(&text, language);
+        let Some(call) = call_context(&tokens, position, language) else {
+            return Ok(None);
+        };
+        let name_token = &tokens[call.name_index];
+
+        let folders = self.workspace_folders.read().await.clone();
+        let graph = self.graph.read().await;
+        let candidates = self.rank_signature_candidates(&graph, &path, name_token);
+        if candidates.is_empty() {
+            return Ok(None);
+        }
+
+        let signatures: Vec<SignatureInformation> = candidates
+            .iter()
+            .filter_map(|&id| self.signature_information(&graph, id, &call, &folders))
+            .collect();
+        if signatures.is_empty() {
+            return Ok(None);
+        }
+
+        // Keep the signature the user switched to while typing arguments.
+        let active_signature = params
+            .context
+            .and_then(|c| c.active_signature_help)
+            .and_then(|help| help.active_signature)
+            .filter(|&i| (i as usize) < signatures.len())
+            .unwrap_or(0);
+
+        Ok(Some(SignatureHelp {
+            active_parameter: signatures[active_signature as usize].active_parameter,
+            signatures,
+            active_signature: Some(active_signature),
+        }))
+    }
+
+    /// Functions named like the callee, nearest to the calling file first.
+    fn rank_signature_candidates(
+        &self,
+        graph: &CodeGraph,
+        path: &Path,
+        name_token: &Token,
+    ) -> Vec<NodeId> {
+        let mut candidates: Vec<NodeId> = self
+            .symbol_index
+            .find_by_short_name(&name_token.text)
+            .into_iter()
+            .filter(|&id| {
+                graph
+                    .get_node(id)
+                    .is_ok_and(|n| n.node_type == NodeType::Function)
+            })
+            .collect();
+        if candidates.is_empty() {
+            return candidates;
+        }
+
+        let occurrence = Occurrence {
+            name: Arc::from(name_token.text.as_str()),
+            range: name_token.range(),
+            kind: OccurrenceKind::Call,
+        };
+        let resolved = occurrences::resolve(graph, &self.symbol_index, path, &occurrence);
+
+        let imported: HashSet<PathBuf> = if candidates.len() > 1 {
+            Self::file_import_scope(graph, path, &self.symbol_index.files()).1
+        } else {
+            HashSet::new()
+        };
+
+        let dir = path.parent();
+        let mut ranked: Vec<(u8, PathBuf, NodeId)> = candidates
+            .drain(..)
+            .map(|id| {
+                let file = self.symbol_index.find_file_for_node(id).unwrap_or_default();
+                let rank = if Some(id) == resolved {
+                    0
+                } else if file == path {
+                    1
+                } else if imported.contains(&file) {
+                    2
+                } else if file.parent() == dir {
+                    3
+                } else {
+                    4
+                };
+                (rank, file, id)
+            })
+            .collect();
+        ranked.sort();
+        ranked
+            .into_iter()
+            .take(MAX_SIGNATURES)
+            .map(|(_, _, id)| id)
+            .collect()
+    }
+
+    /// Signature of one candidate, with the active parameter for `call`.
+    fn signature_information(
+        &self,
+        graph: &CodeGraph,
+        node_id: NodeId,
+        call: &CallContext,
+        folders: &[PathBuf],
+    ) -> Option<SignatureInformation> {
+        let node = graph.get_node(node_id).ok()?;
+        let name = node.properties.get_string("name")?;
+        let short = index::short_name(name);
+        let label = node
+            .properties
+            .get_string("signature")
+            .filter(|s| !s.is_empty())
+            .map(|s| s.trim().to_string())
+            .unwrap_or_else(|| format!("{short}()"));
+
+        let spans = parameter_spans(&label, short);
+        let skips_receiver = call.is_method_call
+            && spans.first().is_some_and(|&[start, end]| {
+                let utf16: Vec<u16> = label.encode_utf16().collect();
+                is_receiver(&String::from_utf16_lossy(
+                    &utf16[start as usize..end as usize],
+                ))
+            });
+        let active_parameter = call.active_argument + u32::from(skips_receiver);
+
+        let mut documentation = node
+            .properties
+            .get_string("doc")
+            .map(|d| d.to_string())
+            .unwrap_or_default();
+        if let Some(file) = self.symbol_index.find_file_for_node(node_id) {
+            let shown = folders
+                .iter()
+                .find_map(|f| file.strip_prefix(f).ok())
+                .unwrap_or(&file)
+                .display();
+            if !documentation.is_empty() {
+                documentation.push_str("\n\n");
+            }
+            documentation.push_str(&format!("*{shown}*"));
+        }
+
+        Some(SignatureInformation {
+            label,
+            documentation: (!documentation.is_empty()).then_some(Documentation::MarkupContent(
+                MarkupContent {
+                    kind: MarkupKind::Markdown,
+                    value: documentation,
+                },
+            )),
+            parameters: Some(
+                spans
+                    .into_iter()
+                    .map(|span| ParameterInformation {
+                        label: ParameterLabel::LabelOffsets(span),
+                        documentation: None,
+                    })
+                    .collect(),
+            ),
+            active_parameter: Some(active_parameter),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(
+        src: &str,
+        line: u32,
+        character: u32,
+        language: Language,
+    ) -> Option<(String, u32, bool)> {
+        let tokens = lexer::tokenize(src, language);
+        call_context(&tokens, Position { line, character }, language).map(|c| {
+            (
+                tokens[c.name_index].text.clone(),
+                c.active_argument,
+                c.is_method_call,
+            )
+        })
+    }
+
+    #[test]
+    fn test_call_context_arguments() {
+        let src = "x = compute(a, [1, 2], ";
+        assert_eq!(
+            context(src, 0, 12, Language::Python),
+            Some(("compute".to_string(), 0, false))
+        );
+        assert_eq!(
+            context(src, 0, 23, Language::Python),
+            Some(("compute".to_string(), 2, false))
+        );
+        // Inside the list literal the enclosing call is still `compute`.
+        assert_eq!(
+            context(src, 0, 18, Language::Python),
+            Some(("compute".to_string(), 1, false))
+        );
+    }
+
+    #[test]
+    fn test_call_context_nested_and_method() {
+        let src = "outer(a, self.inner(b, ";
+        assert_eq!(
+            context(src, 0, 23, Language::Python),
+            Some(("inner".to_string(), 1, true))
+        );
+        // After the inner call is closed we are back in `outer`.
+        let src = "outer(a, inner(b), ";
+        assert_eq!(
+            context(src, 0, 19, Language::Python),
+            Some(("outer".to_string(), 2, false))
+        );
+    }
+
+    #[test]
+    fn test_call_context_outside_call() {
+        assert_eq!(context("x = (a, b", 0, 9, Language::Python), None);
+        assert_eq!(context("def helper(a, ", 0, 14, Language::Python), None);
+        assert_eq!(
+            context("fn run() {\n    let x = 1;\n", 2, 0, Language::Rust),
+            None
+        );
+    }
+
+    #[test]
+    fn test_call_context_turbofish() {
+        assert_eq!(
+            context("parse::<u32>(input, ", 0, 20, Language::Rust),
+            Some(("parse".to_string(), 1, false))
+        );
+    }
+
+    #[test]
+    fn test_parameter_spans() {
+        let sig = "def f(a, b: Dict[str, int] = None, *args)";
+        let spans = parameter_spans(sig, "f");
+        let labels: Vec<&str> = spans
+            .iter()
+            .map(|[s, e]| &sig[*s as usize..*e as usize])
+            .collect();
+        assert_eq!(labels, vec!["a", "b: Dict[str, int] = None", "*args"]);
+
+        let sig = "fn map<K, V>(m: HashMap<K, V>, f: impl Fn(K) -> V) -> Vec<V>";
+        let spans = parameter_spans(sig, "map");
+        let labels: Vec<&str> = spans
+            .iter()
+            .map(|[s, e]| &sig[*s as usize..*e as usize])
+            .collect();
+        assert_eq!(labels, vec!["m: HashMap<K, V>", "f: impl Fn(K) -> V"]);
+
+        assert!(parameter_spans("def g()", "g").is_empty());
+    }
+
+    #[test]
+    fn test_parameter_spans_go_receiver() {
+        let sig = "func (s *Server) Start(addr string, port int) error";
+        let spans = parameter_spans(sig, "Start");
+        assert_eq!(spans.len(), 2);
+        assert_eq!(
+            &sig[spans[0][0] as usize..spans[0][1] as usize],
+            "addr string"
+        );
+    }
+
+    #[test]
+    fn test_is_receiver() {
+        assert!(is_receiver("self"));
+        assert!(is_receiver("&mut self"));
+        assert!(is_receiver("&'a self"));
+        assert!(is_receiver("self: Box<Self>"));
+        assert!(is_receiver("cls"));
+        assert!(!is_receiver("selfish"));
+        assert!(!is_receiver("x: i32"));
+    }
+}
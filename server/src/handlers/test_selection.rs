@@ -0,0 +1,853 @@
+//! Test Selection Handler - Tests affected by a change, as runner filters.
+//!
+//! `codegraph/selectTests` takes changed files and/or symbols and selects
+//! every test that can reach them: tests that call or reference a changed
+//! symbol through any chain of calls, and tests in files that import a
+//! changed file directly or through other imports. The selection is rendered
+//! as filters for pytest, `cargo test`, `go test -run` and jest.
+
+use crate::backend::CodeGraphBackend;
+use crate::handlers::custom::reverse_reachability;
+use crate::index;
+use crate::lexer::{self, Language, TokenKind};
+use codegraph::{CodeGraph, NodeId, NodeType};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use tower_lsp::jsonrpc::Result;
+use tower_lsp::lsp_types::{Range, Url};
+
+// ==========================================
+// Test Selection Types
+// ==========================================
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SelectTestsParams {
+    /// Changed files as URIs, absolute paths or workspace-relative paths
+    #[serde(default)]
+    pub files: Vec<String>,
+    /// Changed symbols
+    #[serde(default)]
+    pub symbols: Vec<ChangedSymbol>,
+    /// Maximum number of call hops between a test and a change (default unlimited)
+    pub max_depth: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangedSymbol {
+    /// Qualified (`Parser.parse`) or short (`parse`) name
+    pub name: String,
+    /// Restrict to the symbol defined in this file
+    pub uri: Option<String>,
+}
+
+/// Test runner a test belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TestRunner {
+    Pytest,
+    Cargo,
+    Go,
+    Jest,
+}
+
+impl TestRunner {
+    pub fn for_language(language: Language) -> Option<Self> {
+        match language {
+            Language::Python => Some(TestRunner::Pytest),
+            Language::Rust => Some(TestRunner::Cargo),
+            Language::Go => Some(TestRunner::Go),
+            Language::TypeScript => Some(TestRunner::Jest),
+            Language::C => None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SelectTestsResponse {
+    pub tests: Vec<SelectedTest>,
+    pub filters: TestFilters,
+    /// One ready-to-run command per runner
+    pub commands: Vec<String>,
+    pub summary: TestSelectionSummary,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SelectedTest {
+    /// Test name; for jest, the test title or the file when titles are unknown
+    pub name: String,
+    pub uri: String,
+    pub range: Range,
+    pub runner: Option<TestRunner>,
+    /// `call` when the test reaches a change through calls, `import` when its
+    /// file imports a changed file
+    pub reason: String,
+    /// Changed symbol or file, then each hop up to the test
+    pub path: Vec<String>,
+}
+
+#[derive(Debug, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TestFilters {
+    /// pytest node IDs (`tests/test_api.py::TestApi::test_get`)
+    pub pytest: Vec<String>,
+    /// Test names passed to `cargo test -- <names>`
+    pub cargo: Vec<String>,
+    /// `go test` packages with their `-run` regex
+    pub go: Vec<GoTestFilter>,
+    /// Jest runs: whole files together, then each file with its own titles
+    pub jest: Vec<JestFilter>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GoTestFilter {
+    /// Package directory, `./`-relative
+    pub package: String,
+    pub run: String,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JestFilter {
+    /// Test files, workspace-relative
+    pub test_paths: Vec<String>,
+    /// Regex over full test names (describe titles and test title); absent
+    /// when the whole files run
+    pub test_name_pattern: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TestSelectionSummary {
+    pub changed_files: usize,
+    pub changed_symbols: usize,
+    pub tests_selected: usize,
+    pub test_files: usize,
+}
+
+/// A selected test before rendering: runner, workspace-relative file and
+/// qualified name.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TestTarget {
+    pub runner: TestRunner,
+    pub file: String,
+    pub name: String,
+}
+
+// ==========================================
+// Test Detection
+// ==========================================
+
+/// Whether a file is collected by the runner.
+pub fn is_test_file(runner: TestRunner, file: &Path) -> bool {
+    let name = file
+        .file_name()
+        .map(|n| n.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let in_dir = |dir: &str| file.components().any(|c| c.as_os_str() == dir);
+    match runner {
+        TestRunner::Pytest => name.starts_with("test_") || name.ends_with("_test.py"),
+        TestRunner::Cargo => true,
+        TestRunner::Go => name.ends_with("_test.go"),
+        TestRunner::Jest => {
+            name.contains(".test.") || name.contains(".spec.") || in_dir("__tests__")
+        }
+    }
+}
+
+/// Whether a function is a test the runner collects. `attributes` are the
+/// source lines directly above the definition.
+pub fn is_test_function(
+    runner: TestRunner,
+    file: &Path,
+    qualified_name: &str,
+    attributes: &[&str],
+) -> bool {
+    if !is_test_file(runner, file) {
+        return false;
+    }
+    let short = index::short_name(qualified_name);
+    match runner {
+        TestRunner::Pytest => {
+            let class_ok = qualified_name
+                .strip_suffix(short)
+                .map(|owner| owner.trim_end_matches(['.', ':']))
+                .and_then(|owner| owner.rsplit(['.', ':']).next())
+                .is_none_or(|class| class.is_empty() || class.starts_with("Test"));
+            short.starts_with("test") && class_ok
+        }
+        TestRunner::Cargo => attributes
+            .iter()
+            .rev()
+            .take_while(|l| {
+                let l = l.trim_start();
+                l.starts_with("#[") || l.starts_with("//")
+            })
+            .any(|l| {
+                let l = l.trim_start();
+                l.starts_with("#[") && (l.contains("test]") || l.contains("test("))
+            }),
+        TestRunner::Go => short
+            .strip_prefix("Test")
+            .is_some_and(|rest| !rest.starts_with(|c: char| c.is_lowercase())),
+        TestRunner::Jest => false,
+    }
+}
+
+/// Titles of `it(...)` and `test(...)` calls in a JavaScript/TypeScript file.
+pub fn jest_test_titles(source: &str) -> Vec<(String, Range)> {
+    let tokens = lexer::tokenize(source, Language::TypeScript);
+    let mut titles = Vec::new();
+    for window in tokens.windows(3) {
+        let [callee, open, title] = window else {
+            continue;
+        };
+        if (callee.is("it") || callee.is("test"))
+            && open.is("(")
+            && title.kind == TokenKind::String
+            && !title.text.contains("${")
+        {
+            let text = title.text.trim_matches(['"', '\'', '`']).to_string();
+            if !text.is_empty() {
+                titles.push((text, title.range()));
+            }
+        }
+    }
+    titles
+}
+
+// ==========================================
+// Filters
+// ==========================================
+
+fn escape_regex(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "\\^$.|?*+()[]{}".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn shell_quote(arg: &str) -> String {
+    if arg
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "_-./:".contains(c))
+    {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+impl TestFilters {
+    /// Group selected tests into per-runner filters.
+    pub fn build(targets: &[TestTarget]) -> Self {
+        let mut filters = TestFilters::default();
+        let mut go_packages: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+        let mut jest_files: BTreeSet<String> = BTreeSet::new();
+        let mut jest_titles: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+
+        for target in targets {
+            match target.runner {
+                TestRunner::Pytest => filters.pytest.push(format!(
+                    "{}::{}",
+                    target.file,
+                    target.name.replace('.', "::")
+                )),
+                TestRunner::Cargo => filters
+                    .cargo
+                    .push(index::short_name(&target.name).to_string()),
+                TestRunner::Go => {
+                    let package = Path::new(&target.file)
+                        .parent()
+                        .map(|p| p.to_string_lossy().to_string())
+                        .filter(|p| !p.is_empty())
+                        .map_or_else(|| ".".to_string(), |p| format!("./{p}"));
+                    go_packages
+                        .entry(package)
+                        .or_default()
+                        .insert(index::short_name(&target.name).to_string());
+                }
+                TestRunner::Jest if target.name == target.file => {
+                    jest_files.insert(target.file.clone());
+                }
+                TestRunner::Jest => {
+                    jest_titles
+                        .entry(target.file.clone())
+                        .or_default()
+                        .insert(escape_regex(&target.name));
+                }
+            }
+        }
+
+        filters.pytest.sort();
+        filters.pytest.dedup();
+        filters.cargo.sort();
+        filters.cargo.dedup();
+        filters.go = go_packages
+            .into_iter()
+            .map(|(package, names)| GoTestFilter {
+                package,
+                run: format!("^({})$", names.into_iter().collect::<Vec<_>>().join("|")),
+            })
+            .collect();
+        // Jest matches the pattern against the describe titles and the test
+        // title joined by spaces, so a title is anchored at the end only.
+        jest_titles.retain(|file, _| !jest_files.contains(file));
+        if !jest_files.is_empty() {
+            filters.jest.push(JestFilter {
+                test_paths: jest_files.into_iter().collect(),
+                test_name_pattern: None,
+            });
+        }
+        filters
+            .jest
+            .extend(jest_titles.into_iter().map(|(file, titles)| JestFilter {
+                test_paths: vec![file],
+                test_name_pattern: Some(format!(
+                    "(^| )({})$",
+                    titles.into_iter().collect::<Vec<_>>().join("|")
+                )),
+            }));
+        filters
+    }
+
+    /// Shell commands running exactly the selected tests.
+    pub fn commands(&self) -> Vec<String> {
+        let join = |args: &[String]| {
+            args.iter()
+                .map(|a| shell_quote(a))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        let mut commands = Vec::new();
+        if !self.pytest.is_empty() {
+            commands.push(format!("pytest {}", join(&self.pytest)));
+        }
+        if !self.cargo.is_empty() {
+            commands.push(format!("cargo test -- {}", join(&self.cargo)));
+        }
+        for go in &self.go {
+            commands.push(format!(
+                "go test {} -run {}",
+                shell_quote(&go.package),
+                shell_quote(&go.run)
+            ));
+        }
+        for jest in &self.jest {
+            let mut command = format!("npx jest {}", join(&jest.test_paths));
+            if let Some(pattern) = &jest.test_name_pattern {
+                command.push_str(&format!(" --testNamePattern {}", shell_quote(pattern)));
+            }
+            commands.push(command);
+        }
+        commands
+    }
+}
+
+// ==========================================
+// Selection
+// ==========================================
+
+impl CodeGraphBackend {
+    /// Select the tests that can reach the changed files and symbols.
+    pub async fn handle_select_tests(
+        &self,
+        params: SelectTestsParams,
+    ) -> Result<SelectTestsResponse> {
+        let folders = self.workspace_folders.read().await.clone();
+        let resolve_file = |file: &str| -> Option<PathBuf> {
+            if let Ok(uri) = Url::parse(file) {
+                if uri.scheme() == "file" {
+                    return uri.to_file_path().ok();
+                }
+            }
+            let path = PathBuf::from(file);
+            if path.is_absolute() {
+                return Some(path);
+            }
+            folders
+                .iter()
+                .map(|f| f.join(&path))
+                .find(|p| self.symbol_index.files().contains(p) || p.is_file())
+        };
+
+        let changed_files: Vec<PathBuf> = params
+            .files
+            .iter()
+            .filter_map(|f| resolve_file(f))
+            .collect();
+
+        // Takes the graph lock itself, so it must come first.
+        let import_findings = self.import_findings().await;
+        let graph = self.graph.read().await;
+        let imports = &import_findings.graph;
+        let file_nodes: HashMap<PathBuf, NodeId> = graph
+            .query()
+            .node_type(NodeType::CodeFile)
+            .execute()
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|id| {
+                let path = graph.get_node(id).ok()?.properties.get_string("path")?;
+                Some((PathBuf::from(path), id))
+            })
+            .collect();
+
+        // Symbols whose change is being tested.
+        let mut starts: Vec<NodeId> = Vec::new();
+        for file in &changed_files {
+            starts.extend(self.symbol_index.get_file_symbols(file));
+        }
+        let mut changed_symbols = 0;
+        for symbol in &params.symbols {
+            let restrict = symbol.uri.as_deref().and_then(&resolve_file);
+            let mut found = self.symbol_index.find_by_name(&symbol.name);
+            if found.is_empty() {
+                found = self.symbol_index.find_by_short_name(&symbol.name);
+            }
+            found.retain(|&id| {
+                restrict.is_none() || self.symbol_index.find_file_for_node(id) == restrict
+            });
+            changed_symbols += found.len();
+            starts.extend(found);
+        }
+        starts.sort_unstable();
+        starts.dedup();
+
+        let name_of = |id: NodeId| {
+            graph
+                .get_node(id)
+                .ok()
+                .and_then(|n| n.properties.get_string("name"))
+                .unwrap_or("")
+                .to_string()
+        };
+        let display = |path: &Path| {
+            folders
+                .iter()
+                .find_map(|f| path.strip_prefix(f).ok())
+                .unwrap_or(path)
+                .to_string_lossy()
+                .replace('\\', "/")
+        };
+
+        // Tests reached through calls and references.
+        let importers = |node: NodeId| -> Vec<NodeId> {
+            let Some(name) = graph
+                .get_node(node)
+                .ok()
+                .filter(|n| n.node_type != NodeType::CodeFile)
+                .and_then(|n| n.properties.get_string("name"))
+            else {
+                return Vec::new();
+            };
+            let Some(file) = self
+                .symbol_index
+                .find_file_for_node(node)
+                .and_then(|f| imports.index_of(&f))
+            else {
+                return Vec::new();
+            };
+            imports
+                .importers_of(file)
+                .filter(|edge| {
+                    edge.symbols
+                        .iter()
+                        .any(|s| index::short_name(s) == index::short_name(name))
+                })
+                .filter_map(|edge| file_nodes.get(&imports.files[edge.from]).copied())
+                .collect()
+        };
+        let reached = reverse_reachability(
+            &graph,
+            &starts,
+            params.max_depth.unwrap_or(usize::MAX),
+            usize::MAX,
+            importers,
+        );
+
+        let mut sources: HashMap<PathBuf, Option<String>> = HashMap::new();
+        let mut selected: Vec<SelectedTest> = Vec::new();
+        let mut targets: Vec<TestTarget> = Vec::new();
+        let mut seen: HashSet<NodeId> = HashSet::new();
+        // Files whose tests are all selected, with how they were reached.
+        let mut test_files: Vec<(PathBuf, Vec<String>, &str)> = Vec::new();
+
+        let mut candidates: Vec<(NodeId, Vec<String>)> =
+            starts.iter().map(|&s| (s, vec![name_of(s)])).collect();
+        for symbol in reached.reached {
+            let mut path = vec![name_of(symbol.path[0].1)];
+            path.extend(symbol.path.iter().map(|&(from, _, _)| name_of(from)));
+            candidates.push((symbol.node, path));
+        }
+        for (node_id, path) in candidates {
+            let Ok(node) = graph.get_node(node_id) else {
+                continue;
+            };
+            if node.node_type == NodeType::CodeFile {
+                if let Some(file) = node.properties.get_string("path") {
+                    test_files.push((PathBuf::from(file), path, "import"));
+                }
+                continue;
+            }
+            match self.select_test(&graph, node_id, &path, "call", &mut sources, &display) {
+                Some(test) => {
+                    if seen.insert(node_id) {
+                        targets.push(test.0);
+                        selected.push(test.1);
+                    }
+                }
+                // Jest tests are anonymous callbacks: a helper in a test file
+                // that reaches the change selects the whole file.
+                None => {
+                    if let Some(file) = self.symbol_index.find_file_for_node(node_id) {
+                        if Language::from_path(&file) == Some(Language::TypeScript)
+                            && is_test_file(TestRunner::Jest, &file)
+                        {
+                            test_files.push((file, path, "call"));
+                        }
+                    }
+                }
+            }
+        }
+
+        // Tests in files that import a changed file, directly or transitively.
+        let mut visited: HashSet<usize> = HashSet::new();
+        let mut queue: VecDeque<(usize, Vec<String>)> = changed_files
+            .iter()
+            .filter_map(|f| imports.index_of(f))
+            .map(|i| (i, vec![display(&imports.files[i])]))
+            .collect();
+        visited.extend(queue.iter().map(|(i, _)| *i));
+        while let Some((file, path)) = queue.pop_front() {
+            for edge in imports.importers_of(file) {
+                if visited.insert(edge.from) {
+                    let mut next = path.clone();
+                    next.push(display(&imports.files[edge.from]));
+                    test_files.push((imports.files[edge.from].clone(), next.clone(), "import"));
+                    queue.push_back((edge.from, next));
+                }
+            }
+        }
+        for (file, path, reason) in test_files {
+            let tests = self.file_tests(&graph, &file, &path, reason, &mut sources, &display);
+            for (target, test) in tests {
+                if !targets.contains(&target) {
+                    targets.push(target);
+                    selected.push(test);
+                }
+            }
+        }
+
+        selected.sort_by(|a, b| (&a.uri, a.range.start.line).cmp(&(&b.uri, b.range.start.line)));
+        targets.sort();
+        targets.dedup();
+        let filters = TestFilters::build(&targets);
+        let files_selected: HashSet<&str> = selected.iter().map(|t| t.uri.as_str()).collect();
+
+        Ok(SelectTestsResponse {
+            commands: filters.commands(),
+            summary: TestSelectionSummary {
+                changed_files: changed_files.len(),
+                changed_symbols,
+                tests_selected: selected.len(),
+                test_files: files_selected.len(),
+            },
+            tests: selected,
+            filters,
+        })
+    }
+
+    /// Whether a function node is a test its runner collects. Jest tests are
+    /// anonymous callbacks, so any function in a Jest test file counts.
+    pub(crate) fn is_test_symbol(
+        &self,
+        graph: &CodeGraph,
+        node_id: NodeId,
+        sources: &mut HashMap<PathBuf, Option<String>>,
+    ) -> bool {
+        let Some(node) = graph.get_node(node_id).ok() else {
+            return false;
+        };
+        let Some(name) = node.properties.get_string("name") else {
+            return false;
+        };
+        if node.node_type != NodeType::Function {
+            return false;
+        }
+        let Some(file) = self.symbol_index.find_file_for_node(node_id) else {
+            return false;
+        };
+        let Some(runner) = Language::from_path(&file).and_then(TestRunner::for_language) else {
+            return false;
+        };
+        if runner == TestRunner::Jest {
+            return is_test_file(runner, &file);
+        }
+        let Some(range) = self.symbol_index.get_node_range(&file, node_id) else {
+            return false;
+        };
+        let text = sources
+            .entry(file.clone())
+            .or_insert_with(|| self.document_text(&file));
+        let lines: Vec<&str> = text
+            .as_deref()
+            .map(|t| t.lines().collect())
+            .unwrap_or_default();
+        let definition = (range.start_line.saturating_sub(1) as usize).min(lines.len());
+        is_test_function(runner, &file, name, &lines[..definition])
+    }
+
+    /// The test a function node represents, if it is one.
+    fn select_test(
+        &self,
+        graph: &CodeGraph,
+        node_id: NodeId,
+        path: &[String],
+        reason: &str,
+        sources: &mut HashMap<PathBuf, Option<String>>,
+        display: &dyn Fn(&Path) -> String,
+    ) -> Option<(TestTarget, SelectedTest)> {
+        let node = graph.get_node(node_id).ok()?;
+        if node.node_type != NodeType::Function {
+            return None;
+        }
+        let name = node.properties.get_string("name")?;
+        let file = self.symbol_index.find_file_for_node(node_id)?;
+        let runner = TestRunner::for_language(Language::from_path(&file)?)?;
+        let range = self.symbol_index.get_node_range(&file, node_id)?;
+
+        let text = sources
+            .entry(file.clone())
+            .or_insert_with(|| self.document_text(&file));
+        let lines: Vec<&str> = text
+            .as_deref()
+            .map(|t| t.lines().collect())
+            .unwrap_or_default();
+        let definition = (range.start_line.saturating_sub(1) as usize).min(lines.len());
+        if !is_test_function(runner, &file, name, &lines[..definition]) {
+            return None;
+        }
+
+        let relative = display(&file);
+        Some((
+            TestTarget {
+                runner,
+                file: relative,
+                name: name.to_string(),
+            },
+            SelectedTest {
+                name: name.to_string(),
+                uri: Url::from_file_path(&file).ok()?.to_string(),
+                range: range.to_lsp_range(),
+                runner: Some(runner),
+                reason: reason.to_string(),
+                path: path.to_vec(),
+            },
+        ))
+    }
+
+    /// All tests defined in a file that reaches a change.
+    fn file_tests(
+        &self,
+        graph: &CodeGraph,
+        file: &Path,
+        path: &[String],
+        reason: &str,
+        sources: &mut HashMap<PathBuf, Option<String>>,
+        display: &dyn Fn(&Path) -> String,
+    ) -> Vec<(TestTarget, SelectedTest)> {
+        let Some(runner) = Language::from_path(file).and_then(TestRunner::for_language) else {
+            return Vec::new();
+        };
+        if !is_test_file(runner, file) {
+            return Vec::new();
+        }
+        let Ok(uri) = Url::from_file_path(file) else {
+            return Vec::new();
+        };
+
+        if runner == TestRunner::Jest {
+            let relative = display(file);
+            let text = sources
+                .entry(file.to_path_buf())
+                .or_insert_with(|| self.document_text(file));
+            let titles = text.as_deref().map(jest_test_titles).unwrap_or_default();
+            let entries: Vec<(String, Range)> = if titles.is_empty() {
+                vec![(relative.clone(), Range::default())]
+            } else {
+                titles
+            };
+            return entries
+                .into_iter()
+                .map(|(name, range)| {
+                    (
+                        TestTarget {
+                            runner,
+                            file: relative.clone(),
+                            name: name.clone(),
+                        },
+                        SelectedTest {
+                            name,
+                            uri: uri.to_string(),
+                            range,
+                            runner: Some(runner),
+                            reason: reason.to_string(),
+                            path: path.to_vec(),
+                        },
+                    )
+                })
+                .collect();
+        }
+
+        self.symbol_index
+            .get_file_symbols(file)
+            .into_iter()
+            .filter_map(|id| self.select_test(graph, id, path, reason, sources, display))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target(runner: TestRunner, file: &str, name: &str) -> TestTarget {
+        TestTarget {
+            runner,
+            file: file.to_string(),
+            name: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_detects_test_functions() {
+        let py = Path::new("/w/tests/test_api.py");
+        assert!(is_test_function(TestRunner::Pytest, py, "test_get", &[]));
+        assert!(is_test_function(
+            TestRunner::Pytest,
+            py,
+            "TestApi.test_get",
+            &[]
+        ));
+        assert!(!is_test_function(
+            TestRunner::Pytest,
+            py,
+            "make_client",
+            &[]
+        ));
+        assert!(!is_test_function(
+            TestRunner::Pytest,
+            Path::new("/w/api.py"),
+            "test_get",
+            &[]
+        ));
+
+        let go = Path::new("/w/pkg/api_test.go");
+        assert!(is_test_function(TestRunner::Go, go, "TestGet", &[]));
+        assert!(!is_test_function(TestRunner::Go, go, "Testify", &[]));
+        assert!(!is_test_function(TestRunner::Go, go, "helper", &[]));
+
+        let rs = Path::new("/w/src/lib.rs");
+        assert!(is_test_function(
+            TestRunner::Cargo,
+            rs,
+            "parses",
+            &["", "    #[test]"]
+        ));
+        assert!(is_test_function(
+            TestRunner::Cargo,
+            rs,
+            "serves",
+            &["#[tokio::test(flavor = \"multi_thread\")]", "// slow"]
+        ));
+        assert!(!is_test_function(
+            TestRunner::Cargo,
+            rs,
+            "helper",
+            &["#[test]", "fn other() {}"]
+        ));
+    }
+
+    #[test]
+    fn test_jest_titles() {
+        let source = "describe('api', () => {\n  it('gets a user', async () => {});\n  test(\"lists (all)\", () => {});\n  it(`skips ${name}`, () => {});\n});\n";
+        let titles: Vec<String> = jest_test_titles(source)
+            .into_iter()
+            .map(|(t, _)| t)
+            .collect();
+        assert_eq!(titles, vec!["gets a user", "lists (all)"]);
+    }
+
+    #[test]
+    fn test_runner_filters() {
+        let targets = vec![
+            target(TestRunner::Pytest, "tests/test_api.py", "TestApi.test_get"),
+            target(TestRunner::Pytest, "tests/test_api.py", "test_list"),
+            target(TestRunner::Cargo, "src/lib.rs", "tests::parses"),
+            target(TestRunner::Go, "pkg/api/api_test.go", "TestGet"),
+            target(TestRunner::Go, "pkg/api/list_test.go", "TestList"),
+            target(TestRunner::Go, "main_test.go", "TestMain"),
+            target(TestRunner::Jest, "src/api.test.ts", "lists (all)"),
+            target(TestRunner::Jest, "src/api.test.ts", "gets a user"),
+            target(TestRunner::Jest, "src/util.test.ts", "src/util.test.ts"),
+            target(TestRunner::Jest, "src/util.test.ts", "formats"),
+        ];
+        let filters = TestFilters::build(&targets);
+        assert_eq!(
+            filters.pytest,
+            vec![
+                "tests/test_api.py::TestApi::test_get",
+                "tests/test_api.py::test_list"
+            ]
+        );
+        assert_eq!(filters.cargo, vec!["parses"]);
+        assert_eq!(
+            filters.go,
+            vec![
+                GoTestFilter {
+                    package: ".".to_string(),
+                    run: "^(TestMain)$".to_string(),
+                },
+                GoTestFilter {
+                    package: "./pkg/api".to_string(),
+                    run: "^(TestGet|TestList)$".to_string(),
+                },
+            ]
+        );
+        assert_eq!(
+            filters.jest,
+            vec![
+                JestFilter {
+                    test_paths: vec!["src/util.test.ts".to_string()],
+                    test_name_pattern: None,
+                },
+                JestFilter {
+                    test_paths: vec!["src/api.test.ts".to_string()],
+                    test_name_pattern: Some("(^| )(gets a user|lists \\(all\\))$".to_string()),
+                },
+            ]
+        );
+
+        assert_eq!(
+            filters.commands(),
+            vec![
+                "pytest tests/test_api.py::TestApi::test_get tests/test_api.py::test_list",
+                "cargo test -- parses",
+                "go test . -run '^(TestMain)$'",
+                "go test ./pkg/api -run '^(TestGet|TestList)$'",
+                "npx jest src/util.test.ts",
+                "npx jest src/api.test.ts --testNamePattern '(^| )(gets a user|lists \\(all\\))$'",
+            ]
+        );
+    }
+}
@@ -0,0 +1,508 @@
+//! Unused Imports and Variables - Names bound but never read.
+//!
+//! An import is unused when none of the names it binds appears as an
+//! identifier outside the file's import statements. A module-level variable
+//! or constant is unused when no occurrence in the workspace reads it. Both
+//! are reported by `codegraph/findUnusedCode` alongside unused functions.
+
+use crate::backend::CodeGraphBackend;
+use crate::handlers::ai_context::LocationInfo;
+use crate::handlers::metrics::UnusedItem;
+use crate::import_graph::{self, normalize_rust_use};
+use crate::index;
+use crate::lexer::{self, Language, Token, TokenKind};
+use crate::occurrences::OccurrenceKind;
+use codegraph::{CodeGraph, Direction, EdgeType, NodeId, NodeType};
+use std::collections::HashSet;
+use std::path::Path;
+use tower_lsp::lsp_types::{Range, Url};
+
+/// Files that usually exist to re-export what they import.
+const REEXPORT_FILES: &[&str] = &[
+    "__init__.py",
+    "index.ts",
+    "index.tsx",
+    "index.js",
+    "mod.rs",
+    "lib.rs",
+];
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+}
+
+/// Names an import binds in the importing file.
+///
+/// Returns nothing when the bound name cannot be known from the module
+/// string alone (side-effect imports, Go packages whose name differs from
+/// their path, C includes).
+pub fn bound_names(module: &str, symbols: &[String], language: Language) -> Vec<String> {
+    let alias = |item: &str| -> String {
+        let item = item.trim().trim_end_matches(';').trim();
+        match item.rsplit_once(" as ") {
+            Some((_, alias)) => alias.trim().to_string(),
+            None => index::short_name(item).to_string(),
+        }
+    };
+
+    let names: Vec<String> = if !symbols.is_empty() {
+        symbols.iter().map(|s| alias(s)).collect()
+    } else {
+        match language {
+            Language::Python => {
+                let module = module.trim();
+                match module.rsplit_once(" as ") {
+                    Some((_, alias)) => vec![alias.trim().to_string()],
+                    None => vec![module.split('.').next().unwrap_or("").to_string()],
+                }
+            }
+            Language::Rust => {
+                let raw = module.trim_end_matches(". tree");
+                match (raw.find('{'), raw.rfind('}')) {
+                    (Some(open), Some(close)) if open < close => {
+                        let group = &raw[open + 1..close];
+                        if group.contains('{') {
+                            Vec::new()
+                        } else {
+                            group
+                                .split(',')
+                                .map(|item| {
+                                    let item =
+                                        item.split_whitespace().collect::<Vec<_>>().join(" ");
+                                    alias(&item.replace(" :: ", "::"))
+                                })
+                                .collect()
+                        }
+                    }
+                    _ => match raw.rsplit_once(" as ") {
+                        Some((_, alias)) => {
+                            vec![alias.trim().trim_end_matches(';').trim().to_string()]
+                        }
+                        None => normalize_rust_use(raw)
+                            .rsplit("::")
+                            .next()
+                            .map(|s| vec![s.to_string()])
+                            .unwrap_or_default(),
+                    },
+                }
+            }
+            Language::Go => {
+                let path = module.trim().trim_matches('"');
+                let mut segments = path.rsplit('/');
+                let mut last = segments.next().unwrap_or("");
+                // `example.com/lib/v2` is package `lib`.
+                if last.len() > 1
+                    && last.starts_with('v')
+                    && last[1..].chars().all(|c| c.is_ascii_digit())
+                {
+                    last = segments.next().unwrap_or("");
+                }
+                vec![last.to_string()]
+            }
+            Language::TypeScript | Language::C => Vec::new(),
+        }
+    };
+
+    let mut names: Vec<String> = names
+        .into_iter()
+        .filter(|n| is_identifier(n) && n != "self" && n != "super" && n != "crate")
+        .collect();
+    names.dedup();
+    names
+}
+
+/// Token index ranges `[start, end]` of the import statements in a file.
+pub fn import_statements(tokens: &[Token]) -> Vec<(usize, usize)> {
+    let starts_line = |i: usize| i == 0 || tokens[i - 1].start.line < tokens[i].start.line;
+    let mut statements = Vec::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        let token = &tokens[i];
+        let is_start = starts_line(i)
+            && (token.is("import")
+                || token.is("from")
+                || token.is("use")
+                || (token.is("pub") && tokens.get(i + 1).is_some_and(|t| t.is("use"))));
+        if !is_start {
+            i += 1;
+            continue;
+        }
+
+        let mut depth = 0i32;
+        let mut end = i;
+        while end < tokens.len() {
+            let current = &tokens[end];
+            if current.kind != TokenKind::String {
+                match current.text.as_str() {
+                    "(" | "[" | "{" => depth += 1,
+                    ")" | "]" | "}" => depth -= 1,
+                    _ => {}
+                }
+            }
+            if depth <= 0 {
+                if current.is(";") {
+                    break;
+                }
+                let continues = [",", "(", "{", "::", "."].iter().any(|t| current.is(t));
+                let next_on_new_line = tokens
+                    .get(end + 1)
+                    .is_none_or(|next| next.start.line > current.start.line);
+                if next_on_new_line && !continues {
+                    break;
+                }
+            }
+            end += 1;
+        }
+        let end = end.min(tokens.len() - 1);
+        statements.push((i, end));
+        i = end + 1;
+    }
+    statements
+}
+
+/// Bound names of `imports` that are never used outside import statements,
+/// with the range of the name in its import statement.
+pub fn unused_import_names(
+    text: &str,
+    language: Language,
+    imports: &[(String, Vec<String>)],
+) -> Vec<(String, String, Range)> {
+    let tokens = lexer::tokenize(text, language);
+    let statements = import_statements(&tokens);
+    let mut in_import = vec![false; tokens.len()];
+    for &(start, end) in &statements {
+        in_import[start..=end].fill(true);
+    }
+
+    let used: HashSet<&str> = tokens
+        .iter()
+        .enumerate()
+        .filter(|(i, t)| t.kind == TokenKind::Identifier && !in_import[*i])
+        .map(|(_, t)| t.text.as_str())
+        .collect();
+
+    let mut unused = Vec::new();
+    let mut reported = HashSet::new();
+    for (module, symbols) in imports {
+        let statement_line = import_graph::find_import_line(text, module);
+        for name in bound_names(module, symbols, language) {
+            if used.contains(name.as_str()) {
+                continue;
+            }
+            let mut occurrences = statements
+                .iter()
+                .flat_map(|&(s, e)| s..=e)
+                .map(|i| &tokens[i])
+                .filter(|t| t.text == name && t.kind != TokenKind::String);
+            let first = occurrences.next();
+            let token = statement_line
+                .and_then(|line| {
+                    first
+                        .into_iter()
+                        .chain(occurrences)
+                        .find(|t| t.start.line >= line)
+                })
+                .or(first);
+            let Some(token) = token else {
+                continue;
+            };
+            if reported.insert((name.clone(), token.start.line, token.start.character)) {
+                unused.push((name, module.clone(), token.range()));
+            }
+        }
+    }
+    unused
+}
+
+fn is_test_path(path: &Path) -> bool {
+    let path = path.to_string_lossy();
+    path.contains("/test") || path.contains("\\test")
+}
+
+impl CodeGraphBackend {
+    /// Imports in `path` whose bound names are never used in the file.
+    pub(crate) fn find_unused_imports(&self, graph: &CodeGraph, path: &Path) -> Vec<UnusedItem> {
+        let Some(language) = Language::from_path(path) else {
+            return Vec::new();
+        };
+        let Some(file_id) = graph
+            .query()
+            .node_type(NodeType::CodeFile)
+            .property("path", path.to_string_lossy().to_string())
+            .execute()
+            .unwrap_or_default()
+            .into_iter()
+            .next()
+        else {
+            return Vec::new();
+        };
+        let imports: Vec<(String, Vec<String>)> = import_graph::file_imports(graph, file_id, path)
+            .into_iter()
+            .filter(|(_, module, _)| {
+                // `pub use` re-exports are not required to be used locally.
+                !module.trim_start().starts_with("pub")
+            })
+            .map(|(_, module, symbols)| (module, symbols))
+            .collect();
+        if imports.is_empty() {
+            return Vec::new();
+        }
+        let (Some(text), Ok(uri)) = (self.document_text(path), Url::from_file_path(path)) else {
+            return Vec::new();
+        };
+
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let reexports = REEXPORT_FILES.contains(&file_name.as_str());
+
+        unused_import_names(&text, language, &imports)
+            .into_iter()
+            .map(|(name, module, range)| {
+                let whole_module = imports
+                    .iter()
+                    .any(|(m, symbols)| *m == module && symbols.is_empty());
+                let (confidence, reason) = if reexports {
+                    (0.5, "Imported but not used; the file may re-export it")
+                } else if language == Language::Go {
+                    // Go rejects unused imports, so the package name likely
+                    // differs from its path.
+                    (0.3, "Package name not referenced in the file")
+                } else if whole_module && language == Language::Python {
+                    (
+                        0.75,
+                        "Imported module never referenced; it may be imported for side effects",
+                    )
+                } else {
+                    (0.9, "Imported name never referenced in the file")
+                };
+                UnusedItem {
+                    item_type: "import".to_string(),
+                    name,
+                    location: LocationInfo {
+                        uri: uri.to_string(),
+                        range,
+                    },
+                    confidence,
+                    reason: reason.to_string(),
+                    safe_to_remove: confidence > 0.8,
+                    importance: 0.0,
+                }
+            })
+            .collect()
+    }
+
+    /// A module-level variable or constant that nothing reads.
+    pub(crate) fn find_unused_variable(
+        &self,
+        graph: &CodeGraph,
+        node_id: NodeId,
+    ) -> Option<UnusedItem> {
+        let node = graph.get_node(node_id).ok()?;
+        if node.node_type != NodeType::Variable {
+            return None;
+        }
+        let name = node.properties.get_string("name")?;
+        let short = index::short_name(name);
+        if short.starts_with("__") && short.ends_with("__") {
+            return None;
+        }
+        let file = self.symbol_index.find_file_for_node(node_id)?;
+        let range = self.symbol_index.get_node_range(&file, node_id)?;
+
+        // Only module-level bindings: not inside any function or class.
+        let nested = self
+            .symbol_index
+            .get_file_symbols(&file)
+            .into_iter()
+            .filter(|&id| id != node_id)
+            .filter(|&id| {
+                graph
+                    .get_node(id)
+                    .is_ok_and(|n| matches!(n.node_type, NodeType::Function | NodeType::Class))
+            })
+            .filter_map(|id| self.symbol_index.get_node_range(&file, id))
+            .any(|r| r.start_line <= range.start_line && range.end_line <= r.end_line);
+        if nested {
+            return None;
+        }
+
+        let has_edge_readers = graph
+            .get_neighbors(node_id, Direction::Incoming)
+            .unwrap_or_default()
+            .into_iter()
+            .any(|source| {
+                graph
+                    .get_edges_between(source, node_id)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter_map(|e| graph.get_edge(e).ok())
+                    .any(|e| {
+                        matches!(
+                            e.edge_type,
+                            EdgeType::References | EdgeType::Uses | EdgeType::Calls
+                        )
+                    })
+            });
+        if has_edge_readers {
+            return None;
+        }
+        let occurrences = self.find_target_occurrences(graph, node_id);
+        if occurrences.iter().any(|o| {
+            matches!(
+                o.occurrence.kind,
+                OccurrenceKind::Read | OccurrenceKind::Call
+            )
+        }) {
+            return None;
+        }
+        let written = occurrences
+            .iter()
+            .filter(|o| o.occurrence.kind == OccurrenceKind::Write)
+            .count()
+            > 1;
+
+        let definition_line = self.document_text(&file).and_then(|text| {
+            text.lines()
+                .nth(range.start_line.saturating_sub(1) as usize)
+                .map(|l| l.trim_start().to_string())
+        });
+        let is_exported = node.properties.get_bool("exported").unwrap_or(false)
+            || node.properties.get_string("visibility") == Some("public")
+            || definition_line
+                .as_deref()
+                .is_some_and(|l| l.starts_with("pub") || l.starts_with("export"))
+            || (Language::from_path(&file) == Some(Language::Go)
+                && short.starts_with(|c: char| c.is_uppercase()));
+
+        let (confidence, reason) = if is_exported {
+            (0.5, "Exported but never read in the workspace")
+        } else if written {
+            (0.85, "Assigned but never read")
+        } else {
+            (0.85, "Never read in the workspace")
+        };
+        Some(UnusedItem {
+            item_type: "variable".to_string(),
+            name: name.to_string(),
+            location: LocationInfo {
+                uri: Url::from_file_path(&file).ok()?.to_string(),
+                range: range.to_lsp_range(),
+            },
+            confidence,
+            reason: reason.to_string(),
+            safe_to_remove: !is_exported && confidence > 0.8,
+            importance: 0.0,
+        })
+    }
+
+    /// Unused imports and module-level variables in the given files.
+    pub(crate) fn find_unused_bindings(
+        &self,
+        graph: &CodeGraph,
+        files: &[std::path::PathBuf],
+        include_tests: bool,
+    ) -> Vec<UnusedItem> {
+        let mut items = Vec::new();
+        for file in files {
+            if !include_tests && is_test_path(file) {
+                continue;
+            }
+            items.extend(self.find_unused_imports(graph, file));
+            items.extend(
+                self.symbol_index
+                    .get_file_symbols(file)
+                    .into_iter()
+                    .filter_map(|id| self.find_unused_variable(graph, id)),
+            );
+        }
+        items
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(module: &str, symbols: &[&str], language: Language) -> Vec<String> {
+        let symbols: Vec<String> = symbols.iter().map(|s| s.to_string()).collect();
+        bound_names(module, &symbols, language)
+    }
+
+    #[test]
+    fn test_bound_names() {
+        assert_eq!(names("os.path", &[], Language::Python), vec!["os"]);
+        assert_eq!(names("numpy as np", &[], Language::Python), vec!["np"]);
+        assert_eq!(
+            names("pkg.util", &["helper", "Parser as P"], Language::Python),
+            vec!["helper", "P"]
+        );
+        assert_eq!(
+            names("use crate :: util :: helper ; . tree", &[], Language::Rust),
+            vec!["helper"]
+        );
+        assert_eq!(
+            names(
+                "use crate::net::{http, tcp as t, self};",
+                &[],
+                Language::Rust
+            ),
+            vec!["http", "t"]
+        );
+        assert_eq!(
+            names("use std::io::*;", &[], Language::Rust),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            names("\"example.com/app/util/v2\"", &[], Language::Go),
+            vec!["util"]
+        );
+        assert!(names("./polyfill", &[], Language::TypeScript).is_empty());
+        assert_eq!(
+            names("./b", &["a", "b"], Language::TypeScript),
+            vec!["a", "b"]
+        );
+    }
+
+    #[test]
+    fn test_multiline_import_statements() {
+        let src = "from pkg import (\n    a,\n    b,\n)\nimport os\n\nprint(a)\n";
+        let tokens = lexer::tokenize(src, Language::Python);
+        let statements = import_statements(&tokens);
+        assert_eq!(statements.len(), 2);
+        assert_eq!(tokens[statements[0].1].text, ")");
+        assert_eq!(tokens[statements[1].1].text, "os");
+    }
+
+    #[test]
+    fn test_unused_import_names() {
+        let src = "import os\nfrom pkg.util import (\n    helper,\n    unused,\n)\n\ndef run():\n    return helper(os.sep)\n";
+        let imports = vec![
+            ("os".to_string(), Vec::new()),
+            (
+                "pkg.util".to_string(),
+                vec!["helper".to_string(), "unused".to_string()],
+            ),
+        ];
+        let unused = unused_import_names(src, Language::Python, &imports);
+        assert_eq!(unused.len(), 1);
+        assert_eq!(unused[0].0, "unused");
+        assert_eq!(unused[0].2.start.line, 3);
+
+        let ts = "import { a, b } from './x';\nimport type { T } from './t';\nexport const f = (x: T) => a(x);\n";
+        let imports = vec![
+            ("./x".to_string(), vec!["a".to_string(), "b".to_string()]),
+            ("./t".to_string(), vec!["T".to_string()]),
+        ];
+        let unused: Vec<String> = unused_import_names(ts, Language::TypeScript, &imports)
+            .into_iter()
+            .map(|(name, _, _)| name)
+            .collect();
+        assert_eq!(unused, vec!["b"]);
+    }
+}
@@ -0,0 +1,776 @@
+//! File-level import graph.
+//!
+//! Parsers record imports as `Imports` edges from a `CodeFile` node to a
+//! `Module` node that only carries the module name as written in the source
+//! (`pkg.b`, `./b`, `crate::util`, `"fmt"`, `stdio.h`). This module resolves
+//! those names against the files that are actually indexed, producing a
+//! file -> file graph that cycle detection and layering checks can run on.
+
+use crate::algorithms;
+use codegraph::{CodeGraph, Direction, EdgeType, NodeId, NodeType};
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+
+/// A resolved import between two indexed files.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportEdge {
+    /// Index of the importing file in [`ImportGraph::files`].
+    pub from: usize,
+    /// Index of the imported file in [`ImportGraph::files`].
+    pub to: usize,
+    /// Module name as recorded by the parser.
+    pub module: String,
+    /// Symbols imported by name; empty for whole-module imports.
+    pub symbols: Vec<String>,
+}
+
+/// Import relationships between indexed files.
+#[derive(Debug, Clone, Default)]
+pub struct ImportGraph {
+    /// All indexed files, in a stable order.
+    pub files: Vec<PathBuf>,
+    /// Resolved file -> file imports (unresolvable/external imports are dropped).
+    pub edges: Vec<ImportEdge>,
+    index: HashMap<PathBuf, usize>,
+}
+
+/// A raw import as found in the graph: importing file, module name, imported symbols.
+pub type RawImport = (PathBuf, String, Vec<String>);
+
+impl ImportGraph {
+    /// Build the import graph from every `CodeFile` node in the code graph.
+    pub fn build(graph: &CodeGraph) -> Self {
+        let file_nodes = graph
+            .query()
+            .node_type(NodeType::CodeFile)
+            .execute()
+            .unwrap_or_default();
+
+        let mut files = Vec::new();
+        let mut imports = Vec::new();
+
+        for file_id in file_nodes {
+            let Ok(node) = graph.get_node(file_id) else {
+                continue;
+            };
+            let Some(path) = node.properties.get_string("path") else {
+                continue;
+            };
+            let path = PathBuf::from(path);
+            files.push(path.clone());
+
+            imports.extend(file_imports(graph, file_id, &path));
+        }
+
+        Self::from_imports(files, &imports)
+    }
+
+    /// Build the import graph from a file list and raw imports.
+    pub fn from_imports(mut files: Vec<PathBuf>, imports: &[RawImport]) -> Self {
+        files.sort();
+        files.dedup();
+
+        let index: HashMap<PathBuf, usize> = files
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (p.clone(), i))
+            .collect();
+        let lookup = FileLookup::new(&files);
+
+        let mut edges = Vec::new();
+        let mut seen = HashSet::new();
+        for (from_path, module, symbols) in imports {
+            let Some(&from) = index.get(from_path) else {
+                continue;
+            };
+            for target in resolve_import(from_path, module, symbols, &lookup) {
+                if let Some(&to) = index.get(&target) {
+                    if to != from && seen.insert((from, to, module.clone())) {
+                        edges.push(ImportEdge {
+                            from,
+                            to,
+                            module: module.clone(),
+                            symbols: symbols.clone(),
+                        });
+                    }
+                }
+            }
+        }
+
+        Self {
+            files,
+            edges,
+            index,
+        }
+    }
+
+    /// Replace the imports of one file, resolving only those. Returns false
+    /// when `path` is not among the files, in which case the graph has to be
+    /// rebuilt.
+    pub fn update_file(&mut self, path: &Path, imports: &[RawImport]) -> bool {
+        let Some(file) = self.index_of(path) else {
+            return false;
+        };
+        let imports: Vec<RawImport> = imports
+            .iter()
+            .filter(|(from, _, _)| from == path)
+            .cloned()
+            .collect();
+        let updated = Self::from_imports(self.files.clone(), &imports);
+        self.edges.retain(|e| e.from != file);
+        self.edges.extend(updated.edges);
+        true
+    }
+
+    /// Index of a file in [`ImportGraph::files`].
+    pub fn index_of(&self, path: &Path) -> Option<usize> {
+        self.index.get(path).copied()
+    }
+
+    /// Successor lists suitable for the algorithms in [`crate::algorithms`].
+    pub fn adjacency(&self) -> Vec<Vec<usize>> {
+        let mut adjacency = vec![Vec::new(); self.files.len()];
+        for edge in &self.edges {
+            if !adjacency[edge.from].contains(&edge.to) {
+                adjacency[edge.from].push(edge.to);
+            }
+        }
+        adjacency
+    }
+
+    /// Groups of files that import each other, directly or transitively.
+    pub fn cycles(&self) -> Vec<Vec<usize>> {
+        algorithms::cyclic_components(&self.adjacency())
+    }
+
+    /// Imports made by the given file.
+    pub fn imports_of(&self, file: usize) -> impl Iterator<Item = &ImportEdge> {
+        self.edges.iter().filter(move |e| e.from == file)
+    }
+
+    /// Imports that target the given file.
+    pub fn importers_of(&self, file: usize) -> impl Iterator<Item = &ImportEdge> {
+        self.edges.iter().filter(move |e| e.to == file)
+    }
+}
+
+/// Imports recorded for one `CodeFile` node.
+pub fn file_imports(graph: &CodeGraph, file_id: NodeId, path: &Path) -> Vec<RawImport> {
+    let mut imports = Vec::new();
+    for neighbor in graph
+        .get_neighbors(file_id, Direction::Outgoing)
+        .unwrap_or_default()
+    {
+        for edge_id in graph
+            .get_edges_between(file_id, neighbor)
+            .unwrap_or_default()
+        {
+            let Ok(edge) = graph.get_edge(edge_id) else {
+                continue;
+            };
+            if edge.edge_type != EdgeType::Imports {
+                continue;
+            }
+            let Some(module) = graph
+                .get_node(neighbor)
+                .ok()
+                .and_then(|n| n.properties.get_string("name"))
+            else {
+                continue;
+            };
+            let symbols = edge
+                .properties
+                .get_string("symbols")
+                .map(|s| {
+                    s.split(',')
+                        .map(|p| p.trim().to_string())
+                        .filter(|p| !p.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default();
+            imports.push((path.to_path_buf(), module.to_string(), symbols));
+        }
+    }
+    imports
+}
+
+/// Files among `files` that `imports` resolve to, without building the
+/// import graph of the whole workspace.
+pub fn imported_files(imports: &[RawImport], files: &[PathBuf]) -> HashSet<PathBuf> {
+    let lookup = FileLookup::new(files);
+    imports
+        .iter()
+        .flat_map(|(from, module, symbols)| {
+            resolve_import(from, module, symbols, &lookup)
+                .into_iter()
+                .filter(move |target| target != from)
+        })
+        .collect()
+}
+
+/// Resolve `(from, module)` pairs to files among `files`, one result per pair.
+pub fn resolve_modules(imports: &[(PathBuf, String)], files: &[PathBuf]) -> Vec<Vec<PathBuf>> {
+    let lookup = FileLookup::new(files);
+    imports
+        .iter()
+        .map(|(from, module)| resolve_import(from, module, &[], &lookup))
+        .collect()
+}
+
+/// Fast suffix lookup over the indexed files.
+struct FileLookup {
+    all: HashSet<PathBuf>,
+    by_name: HashMap<String, Vec<PathBuf>>,
+}
+
+impl FileLookup {
+    fn new(files: &[PathBuf]) -> Self {
+        let mut by_name: HashMap<String, Vec<PathBuf>> = HashMap::new();
+        for file in files {
+            if let Some(name) = file.file_name() {
+                by_name
+                    .entry(name.to_string_lossy().to_string())
+                    .or_default()
+                    .push(file.clone());
+            }
+        }
+        Self {
+            all: files.iter().cloned().collect(),
+            by_name,
+        }
+    }
+
+    fn contains(&self, path: &Path) -> bool {
+        self.all.contains(path)
+    }
+
+    /// Files whose trailing path components equal `suffix`, closest to `near` first.
+    fn with_suffix(&self, suffix: &Path, near: &Path) -> Vec<PathBuf> {
+        let Some(name) = suffix.file_name() else {
+            return Vec::new();
+        };
+        let mut matches: Vec<PathBuf> = self
+            .by_name
+            .get(name.to_string_lossy().as_ref())
+            .map(|candidates| {
+                candidates
+                    .iter()
+                    .filter(|c| c.ends_with(suffix))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default();
+        matches.sort_by_key(|m| std::cmp::Reverse(common_prefix_len(m, near)));
+        matches
+    }
+
+    /// Files whose parent directory is `dir`.
+    fn in_directory(&self, dir: &Path) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = self
+            .all
+            .iter()
+            .filter(|f| f.parent() == Some(dir))
+            .cloned()
+            .collect();
+        files.sort();
+        files
+    }
+}
+
+fn common_prefix_len(a: &Path, b: &Path) -> usize {
+    a.components()
+        .zip(b.components())
+        .take_while(|(x, y)| x == y)
+        .count()
+}
+
+/// Lexically normalize `.` and `..` components.
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other.as_os_str()),
+        }
+    }
+    out
+}
+
+/// Resolve a module name imported by `from` to indexed files.
+///
+/// Resolution is heuristic and per language; imports of external packages
+/// resolve to nothing.
+fn resolve_import(
+    from: &Path,
+    module: &str,
+    symbols: &[String],
+    files: &FileLookup,
+) -> Vec<PathBuf> {
+    let ext = from
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    match ext.as_str() {
+        "py" | "pyw" => resolve_python(from, module, symbols, files),
+        "ts" | "tsx" | "js" | "jsx" => resolve_typescript(from, module, files),
+        "go" => resolve_go(from, module, files),
+        "c" | "h" => resolve_c(from, module, files),
+        "rs" => resolve_rust(from, module, files),
+        _ => Vec::new(),
+    }
+}
+
+fn resolve_python(
+    from: &Path,
+    module: &str,
+    symbols: &[String],
+    files: &FileLookup,
+) -> Vec<PathBuf> {
+    let dots = module.chars().take_while(|c| *c == '.').count();
+    let rest = &module[dots..];
+    let parts: Vec<&str> = rest.split('.').filter(|p| !p.is_empty()).collect();
+
+    // Candidate module paths relative to a base: the module itself, or a
+    // submodule for each `from module import symbol`.
+    let mut relative_candidates: Vec<PathBuf> = Vec::new();
+    let module_path: PathBuf = parts.iter().collect();
+    if !parts.is_empty() {
+        relative_candidates.push(module_path.with_extension("py"));
+        relative_candidates.push(module_path.join("__init__.py"));
+    }
+    for symbol in symbols {
+        relative_candidates.push(module_path.join(format!("{symbol}.py")));
+    }
+
+    let mut resolved = Vec::new();
+    if dots > 0 {
+        let mut base = from.parent().map(Path::to_path_buf).unwrap_or_default();
+        for _ in 1..dots {
+            base.pop();
+        }
+        for candidate in relative_candidates {
+            let full = base.join(candidate);
+            if files.contains(&full) && !resolved.contains(&full) {
+                resolved.push(full);
+            }
+        }
+    } else {
+        for candidate in relative_candidates {
+            if let Some(best) = files.with_suffix(&candidate, from).into_iter().next() {
+                if !resolved.contains(&best) {
+                    resolved.push(best);
+                }
+            }
+        }
+    }
+
+    // `from pkg import helper` where `helper` is a function: keep only the
+    // most specific hit so the package `__init__` is not reported as well.
+    if resolved.len() > 1 {
+        resolved.retain(|p| !p.ends_with("__init__.py"));
+    }
+    resolved
+}
+
+fn resolve_typescript(from: &Path, module: &str, files: &FileLookup) -> Vec<PathBuf> {
+    if !module.starts_with('.') {
+        return Vec::new();
+    }
+    let Some(dir) = from.parent() else {
+        return Vec::new();
+    };
+    let base = normalize(&dir.join(module));
+
+    let mut candidates = vec![base.clone()];
+    let stem = match base.extension().and_then(|e| e.to_str()) {
+        Some("js") | Some("jsx") | Some("mjs") | Some("cjs") => base.with_extension(""),
+        _ => base.clone(),
+    };
+    for ext in ["ts", "tsx", "js", "jsx"] {
+        let mut with_ext = stem.clone().into_os_string();
+        with_ext.push(format!(".{ext}"));
+        candidates.push(PathBuf::from(with_ext));
+    }
+    for ext in ["ts", "tsx", "js", "jsx"] {
+        candidates.push(base.join(format!("index.{ext}")));
+    }
+
+    candidates
+        .into_iter()
+        .find(|c| files.contains(c))
+        .into_iter()
+        .collect()
+}
+
+fn resolve_go(from: &Path, module: &str, files: &FileLookup) -> Vec<PathBuf> {
+    let import_path = module.trim_matches('"');
+    let segments: Vec<&str> = import_path.split('/').filter(|s| !s.is_empty()).collect();
+    // Single-segment imports are standard library packages.
+    if segments.len() < 2 {
+        return Vec::new();
+    }
+
+    let own_dir = from.parent();
+    let mut dirs: Vec<PathBuf> = files
+        .all
+        .iter()
+        .filter_map(|f| f.parent().map(Path::to_path_buf))
+        .filter(|d| Some(d.as_path()) != own_dir)
+        .collect();
+    dirs.sort();
+    dirs.dedup();
+
+    // Prefer the directory matching the longest tail of the import path.
+    for take in (1..=segments.len()).rev() {
+        let suffix: PathBuf = segments[segments.len() - take..].iter().collect();
+        let mut matching: Vec<&PathBuf> = dirs.iter().filter(|d| d.ends_with(&suffix)).collect();
+        if matching.is_empty() {
+            continue;
+        }
+        matching.sort_by_key(|d| std::cmp::Reverse(common_prefix_len(d, from)));
+        return files
+            .in_directory(matching[0])
+            .into_iter()
+            .filter(|f| f.extension().and_then(|e| e.to_str()) == Some("go"))
+            .collect();
+    }
+    Vec::new()
+}
+
+fn resolve_c(from: &Path, module: &str, files: &FileLookup) -> Vec<PathBuf> {
+    let header = module.trim_matches(|c| c == '"' || c == '<' || c == '>');
+    if header.is_empty() {
+        return Vec::new();
+    }
+    if let Some(dir) = from.parent() {
+        let local = normalize(&dir.join(header));
+        if files.contains(&local) {
+            return vec![local];
+        }
+    }
+    files
+        .with_suffix(Path::new(header), from)
+        .into_iter()
+        .take(1)
+        .collect()
+}
+
+/// Reduce a Rust `use` declaration as recorded by the parser to its path,
+/// e.g. `use crate :: util :: helper ; . tree` -> `crate::util::helper`.
+pub fn normalize_rust_use(module: &str) -> String {
+    let compact: String = module
+        .trim_end_matches(". tree")
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    let compact = compact.trim_end_matches(';');
+    let compact = compact.strip_prefix("pub").unwrap_or(compact);
+    let compact = compact.strip_prefix("use").unwrap_or(compact);
+    let compact = compact.split('{').next().unwrap_or(compact);
+    compact.trim_end_matches("::").to_string()
+}
+
+fn resolve_rust(from: &Path, module: &str, files: &FileLookup) -> Vec<PathBuf> {
+    let path = normalize_rust_use(module);
+    let mut parts: Vec<&str> = path.split("::").filter(|p| !p.is_empty()).collect();
+    if parts.is_empty() {
+        return Vec::new();
+    }
+
+    let self_dir = || -> Option<PathBuf> {
+        let dir = from.parent()?;
+        let stem = from.file_stem()?.to_string_lossy();
+        if matches!(stem.as_ref(), "mod" | "lib" | "main") {
+            Some(dir.to_path_buf())
+        } else {
+            Some(dir.join(stem.as_ref()))
+        }
+    };
+
+    let mut base = match parts[0] {
+        "crate" => {
+            let root = from.ancestors().find(|a| a.ends_with("src"));
+            match root {
+                Some(r) => r.to_path_buf(),
+                None => return Vec::new(),
+            }
+        }
+        "self" => match self_dir() {
+            Some(d) => d,
+            None => return Vec::new(),
+        },
+        "super" => match self_dir().and_then(|d| d.parent().map(Path::to_path_buf)) {
+            Some(d) => d,
+            None => return Vec::new(),
+        },
+        _ => return Vec::new(),
+    };
+    parts.remove(0);
+    while parts.first() == Some(&"super") {
+        base.pop();
+        parts.remove(0);
+    }
+
+    // The path may end in an item name; try the longest module prefix first.
+    for take in (1..=parts.len()).rev() {
+        let module_path: PathBuf = parts[..take].iter().collect();
+        let file = base.join(&module_path).with_extension("rs");
+        if files.contains(&file) {
+            return vec![file];
+        }
+        let mod_file = base.join(&module_path).join("mod.rs");
+        if files.contains(&mod_file) {
+            return vec![mod_file];
+        }
+    }
+    Vec::new()
+}
+
+/// Find the 0-indexed line of the import statement for `module` in `text`.
+pub fn find_import_line(text: &str, module: &str) -> Option<u32> {
+    let needle = if module.contains("::") || module.starts_with("use") {
+        normalize_rust_use(module)
+    } else {
+        module.trim_matches('"').to_string()
+    };
+    if needle.is_empty() {
+        return None;
+    }
+
+    let is_import_line = |line: &str| {
+        let trimmed = line.trim_start();
+        [
+            "import", "from", "use ", "pub use", "#include", "export", "require",
+        ]
+        .iter()
+        .any(|k| trimmed.starts_with(k))
+            || trimmed.contains("require(")
+    };
+
+    let mut quoted_match = None;
+    for (i, line) in text.lines().enumerate() {
+        let compact: String = line.chars().filter(|c| !c.is_whitespace()).collect();
+        let found = line.contains(&needle) || compact.contains(&needle);
+        if !found {
+            continue;
+        }
+        if is_import_line(line) {
+            return Some(i as u32);
+        }
+        // Go import blocks list bare quoted paths.
+        if quoted_match.is_none() && line.trim() == format!("\"{needle}\"") {
+            quoted_match = Some(i as u32);
+        }
+    }
+    quoted_match
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(files: &[&str], imports: &[(&str, &str, &[&str])]) -> ImportGraph {
+        let files = files.iter().map(PathBuf::from).collect();
+        let imports: Vec<RawImport> = imports
+            .iter()
+            .map(|(f, m, s)| {
+                (
+                    PathBuf::from(f),
+                    m.to_string(),
+                    s.iter().map(|x| x.to_string()).collect(),
+                )
+            })
+            .collect();
+        ImportGraph::from_imports(files, &imports)
+    }
+
+    fn targets(g: &ImportGraph, from: &str) -> Vec<String> {
+        let idx = g.index_of(Path::new(from)).unwrap();
+        g.imports_of(idx)
+            .map(|e| g.files[e.to].to_string_lossy().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_resolve_python_absolute_and_relative() {
+        let g = graph(
+            &[
+                "/w/pkg/a.py",
+                "/w/pkg/b.py",
+                "/w/pkg/sub/__init__.py",
+                "/w/pkg/sub/c.py",
+            ],
+            &[
+                ("/w/pkg/a.py", "pkg.b", &["helper"]),
+                ("/w/pkg/b.py", ".sub", &[]),
+                ("/w/pkg/sub/c.py", "..a", &[]),
+                ("/w/pkg/a.py", "os", &[]),
+            ],
+        );
+        assert_eq!(targets(&g, "/w/pkg/a.py"), vec!["/w/pkg/b.py"]);
+        assert_eq!(targets(&g, "/w/pkg/b.py"), vec!["/w/pkg/sub/__init__.py"]);
+        assert_eq!(targets(&g, "/w/pkg/sub/c.py"), vec!["/w/pkg/a.py"]);
+    }
+
+    #[test]
+    fn test_resolve_python_from_package_import_module() {
+        let g = graph(
+            &["/w/app.py", "/w/pkg/__init__.py", "/w/pkg/util.py"],
+            &[("/w/app.py", "pkg", &["util"])],
+        );
+        assert_eq!(targets(&g, "/w/app.py"), vec!["/w/pkg/util.py"]);
+    }
+
+    #[test]
+    fn test_resolve_typescript_relative() {
+        let g = graph(
+            &["/w/src/a.ts", "/w/src/b.ts", "/w/src/lib/index.ts"],
+            &[
+                ("/w/src/a.ts", "./b", &[]),
+                ("/w/src/b.ts", "./lib", &[]),
+                ("/w/src/lib/index.ts", "../a.js", &[]),
+                ("/w/src/a.ts", "react", &[]),
+            ],
+        );
+        assert_eq!(targets(&g, "/w/src/a.ts"), vec!["/w/src/b.ts"]);
+        assert_eq!(targets(&g, "/w/src/b.ts"), vec!["/w/src/lib/index.ts"]);
+        assert_eq!(targets(&g, "/w/src/lib/index.ts"), vec!["/w/src/a.ts"]);
+    }
+
+    #[test]
+    fn test_resolve_go_package_directory() {
+        let g = graph(
+            &[
+                "/w/main.go",
+                "/w/internal/util/a.go",
+                "/w/internal/util/b.go",
+            ],
+            &[
+                ("/w/main.go", "example.com/app/internal/util", &[]),
+                ("/w/main.go", "fmt", &[]),
+            ],
+        );
+        assert_eq!(
+            targets(&g, "/w/main.go"),
+            vec!["/w/internal/util/a.go", "/w/internal/util/b.go"]
+        );
+    }
+
+    #[test]
+    fn test_resolve_c_header() {
+        let g = graph(
+            &["/w/src/a.c", "/w/src/a.h", "/w/include/util.h"],
+            &[
+                ("/w/src/a.c", "a.h", &[]),
+                ("/w/src/a.c", "util.h", &[]),
+                ("/w/src/a.c", "stdio.h", &[]),
+            ],
+        );
+        assert_eq!(
+            targets(&g, "/w/src/a.c"),
+            vec!["/w/src/a.h", "/w/include/util.h"]
+        );
+    }
+
+    #[test]
+    fn test_resolve_rust_use_paths() {
+        let g = graph(
+            &[
+                "/w/src/lib.rs",
+                "/w/src/util.rs",
+                "/w/src/net/mod.rs",
+                "/w/src/net/http.rs",
+            ],
+            &[
+                ("/w/src/lib.rs", "use crate :: util :: helper ; . tree", &[]),
+                ("/w/src/net/http.rs", "use super :: super :: util ;", &[]),
+                ("/w/src/util.rs", "use crate::net::{http, tcp};", &[]),
+                ("/w/src/lib.rs", "use std :: fmt ;", &[]),
+            ],
+        );
+        assert_eq!(targets(&g, "/w/src/lib.rs"), vec!["/w/src/util.rs"]);
+        assert_eq!(targets(&g, "/w/src/net/http.rs"), vec!["/w/src/util.rs"]);
+        assert_eq!(targets(&g, "/w/src/util.rs"), vec!["/w/src/net/mod.rs"]);
+    }
+
+    #[test]
+    fn test_normalize_rust_use() {
+        assert_eq!(
+            normalize_rust_use("use crate :: util :: helper ; . tree"),
+            "crate::util::helper"
+        );
+        assert_eq!(normalize_rust_use("pub use self::a::{b, c};"), "self::a");
+    }
+
+    #[test]
+    fn test_import_cycles() {
+        let g = graph(
+            &["/w/a.py", "/w/b.py", "/w/c.py"],
+            &[
+                ("/w/a.py", "b", &[]),
+                ("/w/b.py", "c", &[]),
+                ("/w/c.py", "a", &[]),
+            ],
+        );
+        let cycles = g.cycles();
+        assert_eq!(cycles.len(), 1);
+        assert_eq!(cycles[0].len(), 3);
+    }
+
+    #[test]
+    fn test_update_file_resolves_only_that_file() {
+        let mut g = graph(
+            &["/w/a.py", "/w/b.py", "/w/c.py"],
+            &[
+                ("/w/a.py", "b", &[]),
+                ("/w/b.py", "c", &[]),
+                ("/w/c.py", "a", &[]),
+            ],
+        );
+        let imports = vec![(PathBuf::from("/w/c.py"), "b".to_string(), Vec::new())];
+        assert!(g.update_file(Path::new("/w/c.py"), &imports));
+        assert_eq!(targets(&g, "/w/c.py"), vec!["/w/b.py"]);
+        assert_eq!(targets(&g, "/w/a.py"), vec!["/w/b.py"]);
+        assert_eq!(g.cycles().len(), 1);
+        assert_eq!(g.cycles()[0].len(), 2);
+
+        assert!(!g.update_file(Path::new("/w/new.py"), &[]));
+    }
+
+    #[test]
+    fn test_imported_files() {
+        let files: Vec<PathBuf> = ["/w/a.ts", "/w/b.ts", "/w/lib/index.ts"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+        let imports = vec![
+            (PathBuf::from("/w/a.ts"), "./b".to_string(), Vec::new()),
+            (PathBuf::from("/w/a.ts"), "./lib".to_string(), Vec::new()),
+            (PathBuf::from("/w/a.ts"), "react".to_string(), Vec::new()),
+        ];
+        let resolved = imported_files(&imports, &files);
+        assert_eq!(resolved.len(), 2);
+        assert!(resolved.contains(Path::new("/w/b.ts")));
+        assert!(resolved.contains(Path::new("/w/lib/index.ts")));
+    }
+
+    #[test]
+    fn test_find_import_line() {
+        let py = "\"\"\"doc\"\"\"\nimport os\nfrom pkg.b import helper\n";
+        assert_eq!(find_import_line(py, "pkg.b"), Some(2));
+        assert_eq!(find_import_line(py, "missing"), None);
+
+        let go = "package main\n\nimport (\n\t\"fmt\"\n\t\"example.com/app/util\"\n)\n";
+        assert_eq!(find_import_line(go, "example.com/app/util"), Some(4));
+
+        let rs = "//! docs\nuse crate::util::helper;\n";
+        assert_eq!(
+            find_import_line(rs, "use crate :: util :: helper ; . tree"),
+            Some(1)
+        );
+    }
+}
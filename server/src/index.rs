@@ -11,6 +11,9 @@ pub struct SymbolIndex {
     /// Name -> NodeIds (for workspace symbol search).
     by_name: DashMap<String, Vec<NodeId>>,
 
+    /// Unqualified name -> NodeIds (`Class.method` and `Type::method` under `method`).
+    by_short_name: DashMap<String, Vec<NodeId>>,
+
     /// File path -> NodeIds (for file-scoped queries).
     by_file: DashMap<PathBuf, Vec<NodeId>>,
 
@@ -69,6 +72,7 @@ impl SymbolIndex {
     pub fn new() -> Self {
         Self {
             by_name: DashMap::new(),
+            by_short_name: DashMap::new(),
             by_file: DashMap::new(),
             by_type: DashMap::new(),
             by_position: DashMap::new(),
@@ -98,6 +102,10 @@ impl SymbolIndex {
                         .entry(name.to_string())
                         .or_default()
                         .push(node_id);
+                    self.by_short_name
+                        .entry(short_name(name).to_string())
+                        .or_default()
+                        .push(node_id);
                 }
 
                 // Index by type
@@ -140,6 +148,10 @@ impl SymbolIndex {
                     v.retain(|&id| id != node_id);
                     !v.is_empty()
                 });
+                self.by_short_name.retain(|_, v| {
+                    v.retain(|&id| id != node_id);
+                    !v.is_empty()
+                });
 
                 // Remove from type index
                 self.by_type.retain(|_, v| {
@@ -199,6 +211,36 @@ impl SymbolIndex {
         results
     }
 
+    /// Find symbols by their exact name as recorded in the graph.
+    pub fn find_by_name(&self, name: &str) -> Vec<NodeId> {
+        self.by_name
+            .get(name)
+            .map(|v| v.clone())
+            .unwrap_or_default()
+    }
+
+    /// Find symbols by unqualified name: `method` matches `Class.method`.
+    pub fn find_by_short_name(&self, name: &str) -> Vec<NodeId> {
+        self.by_short_name
+            .get(name)
+            .map(|v| v.clone())
+            .unwrap_or_default()
+    }
+
+    /// Find symbols whose unqualified name starts with `prefix`, ignoring case.
+    pub fn find_by_prefix(&self, prefix: &str) -> Vec<NodeId> {
+        let prefix_lower = prefix.to_lowercase();
+        let mut results = Vec::new();
+
+        for entry in self.by_short_name.iter() {
+            if entry.key().to_lowercase().starts_with(&prefix_lower) {
+                results.extend(entry.value().iter().copied());
+            }
+        }
+
+        results
+    }
+
     /// Get all symbols in a file.
     pub fn get_file_symbols(&self, path: &Path) -> Vec<NodeId> {
         self.by_file
@@ -228,6 +270,14 @@ impl SymbolIndex {
         None
     }
 
+    /// All indexed files.
+    pub fn files(&self) -> Vec<PathBuf> {
+        self.by_file
+            .iter()
+            .map(|entry| entry.key().clone())
+            .collect()
+    }
+
     /// Find the file path for a given node ID by reverse lookup.
     /// This is useful when nodes don't have a `path` property set directly.
     pub fn find_file_for_node(&self, node_id: NodeId) -> Option<PathBuf> {
@@ -237,6 +287,7 @@ impl SymbolIndex {
     /// Clear all indexes.
     pub fn clear(&self) {
         self.by_name.clear();
+        self.by_short_name.clear();
         self.by_file.clear();
         self.by_type.clear();
         self.by_position.clear();
@@ -266,6 +317,11 @@ pub struct IndexStats {
     pub unique_names: usize,
 }
 
+/// Unqualified part of a symbol name: `Class.method` / `Type::method` -> `method`.
+pub fn short_name(name: &str) -> &str {
+    name.rsplit(['.', ':']).next().unwrap_or(name)
+}
+
 /// Extract range from node properties.
 /// Note: codegraph parsers use line_start/line_end, not start_line/end_line
 fn extract_range(properties: &PropertyMap) -> Option<IndexRange> {
@@ -754,4 +810,58 @@ mod tests {
         // Non-existent node
         assert!(index.find_file_for_node(99).is_none());
     }
+
+    #[test]
+    fn test_short_name() {
+        assert_eq!(short_name("helper"), "helper");
+        assert_eq!(short_name("Parser.parse"), "parse");
+        assert_eq!(short_name("Parser::parse"), "parse");
+    }
+
+    #[test]
+    fn test_find_by_name() {
+        let index = SymbolIndex::new();
+        index.by_name.insert("Parser.parse".to_string(), vec![1]);
+
+        assert_eq!(index.find_by_name("Parser.parse"), vec![1]);
+        assert!(index.find_by_name("parse").is_empty());
+    }
+
+    #[test]
+    fn test_find_by_short_name() {
+        let index = SymbolIndex::new();
+        index.by_short_name.insert("parse".to_string(), vec![1, 2]);
+
+        assert_eq!(index.find_by_short_name("parse"), vec![1, 2]);
+        assert!(index.find_by_short_name("Parser.parse").is_empty());
+    }
+
+    #[test]
+    fn test_files() {
+        let index = SymbolIndex::new();
+        index.by_file.insert(PathBuf::from("/w/a.py"), vec![1]);
+        index.by_file.insert(PathBuf::from("/w/b.py"), vec![]);
+
+        let mut files = index.files();
+        files.sort();
+        assert_eq!(
+            files,
+            vec![PathBuf::from("/w/a.py"), PathBuf::from("/w/b.py")]
+        );
+    }
+
+    #[test]
+    fn test_find_by_prefix() {
+        let index = SymbolIndex::new();
+        index.by_short_name.insert("parse".to_string(), vec![1]);
+        index
+            .by_short_name
+            .insert("ParseError".to_string(), vec![2]);
+        index.by_short_name.insert("reparse".to_string(), vec![3]);
+
+        let mut found = index.find_by_prefix("pars");
+        found.sort();
+        assert_eq!(found, vec![1, 2]);
+        assert!(index.find_by_prefix("x").is_empty());
+    }
 }
@@ -0,0 +1,763 @@
+//! Lightweight source tokenizer.
+//!
+//! The parsers record symbols and edges but not where each identifier
+//! occurs. Features that need exact positions (call sites, references,
+//! highlights, semantic tokens, clone detection) re-lex the source with this
+//! tokenizer. It understands comments, string literals and identifiers for
+//! all supported languages; it does not build a syntax tree.
+//!
+//! Columns are UTF-16 code units, as required by the LSP.
+
+use std::ops::RangeInclusive;
+use std::path::Path;
+use tower_lsp::lsp_types::{Position, Range};
+
+/// Languages the tokenizer knows the comment/string syntax for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Language {
+    Python,
+    Rust,
+    TypeScript,
+    Go,
+    C,
+}
+
+impl Language {
+    /// Detect the language from a file extension.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_lowercase();
+        match ext.as_str() {
+            "py" | "pyw" => Some(Language::Python),
+            "rs" => Some(Language::Rust),
+            "ts" | "tsx" | "js" | "jsx" | "mjs" | "cjs" => Some(Language::TypeScript),
+            "go" => Some(Language::Go),
+            "c" | "h" => Some(Language::C),
+            _ => None,
+        }
+    }
+
+    /// Reserved words of the language.
+    pub fn keywords(self) -> &'static [&'static str] {
+        match self {
+            Language::Python => &[
+                "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class",
+                "continue", "def", "del", "elif", "else", "except", "finally", "for", "from",
+                "global", "if", "import", "in", "is", "lambda", "nonlocal", "not", "or", "pass",
+                "raise", "return", "try", "while", "with", "yield",
+            ],
+            Language::Rust => &[
+                "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else",
+                "enum", "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match",
+                "mod", "move", "mut", "pub", "ref", "return", "self", "Self", "static", "struct",
+                "super", "trait", "true", "type", "unsafe", "use", "where", "while",
+            ],
+            Language::TypeScript => &[
+                "abstract",
+                "as",
+                "async",
+                "await",
+                "break",
+                "case",
+                "catch",
+                "class",
+                "const",
+                "continue",
+                "debugger",
+                "default",
+                "delete",
+                "do",
+                "else",
+                "enum",
+                "export",
+                "extends",
+                "false",
+                "finally",
+                "for",
+                "from",
+                "function",
+                "if",
+                "implements",
+                "import",
+                "in",
+                "instanceof",
+                "interface",
+                "let",
+                "new",
+                "null",
+                "of",
+                "private",
+                "protected",
+                "public",
+                "readonly",
+                "return",
+                "static",
+                "super",
+                "switch",
+                "this",
+                "throw",
+                "true",
+                "try",
+                "type",
+                "typeof",
+                "undefined",
+                "var",
+                "void",
+                "while",
+                "with",
+                "yield",
+            ],
+            Language::Go => &[
+                "break",
+                "case",
+                "chan",
+                "const",
+                "continue",
+                "default",
+                "defer",
+                "else",
+                "fallthrough",
+                "for",
+                "func",
+                "go",
+                "goto",
+                "if",
+                "import",
+                "interface",
+                "map",
+                "package",
+                "range",
+                "return",
+                "select",
+                "struct",
+                "switch",
+                "type",
+                "var",
+                "nil",
+                "true",
+                "false",
+            ],
+            Language::C => &[
+                "auto", "break", "case", "char", "const", "continue", "default", "do", "double",
+                "else", "enum", "extern", "float", "for", "goto", "if", "inline", "int", "long",
+                "register", "restrict", "return", "short", "signed", "sizeof", "static", "struct",
+                "switch", "typedef", "union", "unsigned", "void", "volatile", "while", "NULL",
+            ],
+        }
+    }
+
+    /// Keywords that introduce a function definition.
+    fn definition_keywords(self) -> &'static [&'static str] {
+        match self {
+            Language::Python => &["def", "class"],
+            Language::Rust => &["fn", "struct", "enum", "trait", "mod"],
+            Language::TypeScript => &["function", "class", "interface"],
+            Language::Go => &["func", "type"],
+            Language::C => &[],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    Identifier,
+    Keyword,
+    Number,
+    String,
+    Operator,
+}
+
+/// A token with its position in the source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub text: String,
+    pub start: Position,
+    pub end: Position,
+}
+
+impl Token {
+    pub fn range(&self) -> Range {
+        Range {
+            start: self.start,
+            end: self.end,
+        }
+    }
+
+    pub fn is(&self, text: &str) -> bool {
+        self.kind != TokenKind::String && self.text == text
+    }
+}
+
+/// Multi-character operators, longest first so matching is greedy.
+const OPERATORS: &[&str] = &[
+    ">>>=", "...", "===", "!==", "<<=", ">>=", "**=", "//=", "&&=", "||=", "??=", "->", "=>", "::",
+    "==", "!=", "<=", ">=", "&&", "||", "++", "--", "+=", "-=", "*=", "/=", "%=", "&=", "|=", "^=",
+    "<<", ">>", "**", ":=", "?.", "??", "<-",
+];
+
+struct Cursor {
+    chars: Vec<char>,
+    pos: usize,
+    line: u32,
+    col: u32,
+}
+
+impl Cursor {
+    fn new(src: &str) -> Self {
+        Self {
+            chars: src.chars().collect(),
+            pos: 0,
+            line: 0,
+            col: 0,
+        }
+    }
+
+    fn peek(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.pos + offset).copied()
+    }
+
+    fn position(&self) -> Position {
+        Position {
+            line: self.line,
+            character: self.col,
+        }
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.chars.get(self.pos).copied()?;
+        self.pos += 1;
+        if c == '\n' {
+            self.line += 1;
+            self.col = 0;
+        } else {
+            self.col += c.len_utf16() as u32;
+        }
+        Some(c)
+    }
+
+    fn starts_with(&self, s: &str) -> bool {
+        s.chars().enumerate().all(|(i, c)| self.peek(i) == Some(c))
+    }
+
+    fn skip_line(&mut self) {
+        while let Some(c) = self.peek(0) {
+            if c == '\n' {
+                break;
+            }
+            self.bump();
+        }
+    }
+
+    /// Consume up to and including `close`.
+    fn skip_until(&mut self, close: &str) {
+        while self.peek(0).is_some() {
+            if self.starts_with(close) {
+                for _ in close.chars() {
+                    self.bump();
+                }
+                return;
+            }
+            self.bump();
+        }
+    }
+
+    /// Consume a quoted literal whose opening quote is at the cursor.
+    fn quoted(&mut self, quote: char, escapes: bool) {
+        self.bump();
+        while let Some(c) = self.bump() {
+            if escapes && c == '\\' {
+                self.bump();
+            } else if c == quote {
+                return;
+            } else if c == '\n' && quote != '`' {
+                // Unterminated single-line literal.
+                return;
+            }
+        }
+    }
+}
+
+fn is_ident_start(c: char) -> bool {
+    c == '_' || c == '$' || c.is_alphabetic()
+}
+
+fn is_ident_continue(c: char) -> bool {
+    c == '_' || c == '$' || c.is_alphanumeric()
+}
+
+/// Tokenize `src`, dropping whitespace and comments.
+pub fn tokenize(src: &str, language: Language) -> Vec<Token> {
+    let keywords = language.keywords();
+    let mut cursor = Cursor::new(src);
+    let mut tokens = Vec::new();
+
+    while let Some(c) = cursor.peek(0) {
+        if c.is_whitespace() {
+            cursor.bump();
+            continue;
+        }
+
+        // Comments
+        if language == Language::Python && c == '#' {
+            cursor.skip_line();
+            continue;
+        }
+        if language != Language::Python && cursor.starts_with("//") {
+            cursor.skip_line();
+            continue;
+        }
+        if language != Language::Python && cursor.starts_with("/*") {
+            cursor.bump();
+            cursor.bump();
+            cursor.skip_until("*/");
+            continue;
+        }
+
+        let start = cursor.position();
+        let begin = cursor.pos;
+
+        let kind = if let Some(len) = string_prefix_len(&cursor, language) {
+            lex_string(&mut cursor, language, len);
+            TokenKind::String
+        } else if c.is_ascii_digit()
+            || (c == '.' && cursor.peek(1).is_some_and(|n| n.is_ascii_digit()))
+        {
+            lex_number(&mut cursor);
+            TokenKind::Number
+        } else if is_ident_start(c) {
+            while cursor.peek(0).is_some_and(is_ident_continue) {
+                cursor.bump();
+            }
+            TokenKind::Identifier
+        } else if language == Language::Rust && c == '\'' {
+            // Lifetime or char literal.
+            let is_char = cursor.peek(1) == Some('\\') || cursor.peek(2) == Some('\'');
+            if is_char {
+                cursor.quoted('\'', true);
+                TokenKind::String
+            } else {
+                cursor.bump();
+                while cursor.peek(0).is_some_and(is_ident_continue) {
+                    cursor.bump();
+                }
+                TokenKind::Identifier
+            }
+        } else if let Some(op) = OPERATORS.iter().find(|op| cursor.starts_with(op)) {
+            for _ in op.chars() {
+                cursor.bump();
+            }
+            TokenKind::Operator
+        } else {
+            cursor.bump();
+            TokenKind::Operator
+        };
+
+        let text: String = cursor.chars[begin..cursor.pos].iter().collect();
+        let kind = if kind == TokenKind::Identifier && keywords.contains(&text.as_str()) {
+            TokenKind::Keyword
+        } else {
+            kind
+        };
+        tokens.push(Token {
+            kind,
+            text,
+            start,
+            end: cursor.position(),
+        });
+    }
+
+    tokens
+}
+
+/// If a string literal starts at the cursor, the length of its prefix
+/// (e.g. `r`, `b`, `f`, `rb`) before the opening quote.
+fn string_prefix_len(cursor: &Cursor, language: Language) -> Option<usize> {
+    let quotes: &[char] = match language {
+        Language::Python => &['"', '\''],
+        Language::Rust => &['"'],
+        Language::TypeScript => &['"', '\'', '`'],
+        Language::Go => &['"', '\'', '`'],
+        Language::C => &['"', '\''],
+    };
+    let prefixes: &[&str] = match language {
+        Language::Python => &[
+            "", "r", "b", "f", "u", "rb", "br", "fr", "rf", "R", "B", "F", "U",
+        ],
+        Language::Rust => &["", "b", "r", "br"],
+        Language::C => &["", "L", "u8", "u", "U"],
+        _ => &[""],
+    };
+    for prefix in prefixes {
+        if !cursor.starts_with(prefix) {
+            continue;
+        }
+        let mut offset = prefix.chars().count();
+        // Rust raw strings: r#"..."#
+        if language == Language::Rust && prefix.contains('r') {
+            while cursor.peek(offset) == Some('#') {
+                offset += 1;
+            }
+        }
+        if cursor.peek(offset).is_some_and(|q| quotes.contains(&q)) {
+            return Some(prefix.chars().count());
+        }
+    }
+    None
+}
+
+fn lex_string(cursor: &mut Cursor, language: Language, prefix_len: usize) {
+    let raw = language == Language::Rust
+        && cursor.chars[cursor.pos..cursor.pos + prefix_len].contains(&'r');
+    let python_raw = language == Language::Python
+        && cursor.chars[cursor.pos..cursor.pos + prefix_len]
+            .iter()
+            .any(|c| *c == 'r' || *c == 'R');
+    for _ in 0..prefix_len {
+        cursor.bump();
+    }
+
+    if raw {
+        let mut hashes = 0;
+        while cursor.peek(0) == Some('#') {
+            hashes += 1;
+            cursor.bump();
+        }
+        let close = format!("\"{}", "#".repeat(hashes));
+        cursor.bump();
+        cursor.skip_until(&close);
+        return;
+    }
+
+    let quote = cursor.peek(0).unwrap_or('"');
+    if language == Language::Python
+        && cursor.peek(1) == Some(quote)
+        && cursor.peek(2) == Some(quote)
+    {
+        let close: String = std::iter::repeat_n(quote, 3).collect();
+        for _ in 0..3 {
+            cursor.bump();
+        }
+        while cursor.peek(0).is_some() {
+            if cursor.starts_with(&close) {
+                for _ in 0..3 {
+                    cursor.bump();
+                }
+                return;
+            }
+            if cursor.peek(0) == Some('\\') && !python_raw {
+                cursor.bump();
+            }
+            cursor.bump();
+        }
+        return;
+    }
+
+    let escapes = !(quote == '`' && language == Language::Go);
+    cursor.quoted(quote, escapes);
+}
+
+fn lex_number(cursor: &mut Cursor) {
+    let begin = cursor.pos;
+    let hex = cursor.starts_with("0x") || cursor.starts_with("0X");
+    while let Some(c) = cursor.peek(0) {
+        let exponent_sign = !hex
+            && (c == '+' || c == '-')
+            && cursor.pos > begin
+            && matches!(cursor.chars[cursor.pos - 1], 'e' | 'E');
+        let fraction = c == '.' && cursor.peek(1).is_some_and(|n| n.is_ascii_digit());
+        if c.is_ascii_alphanumeric() || c == '_' || exponent_sign || fraction {
+            cursor.bump();
+        } else {
+            break;
+        }
+    }
+}
+
+/// Whether the token at `index` is followed by a call argument list.
+pub fn opens_call(tokens: &[Token], index: usize) -> bool {
+    call_paren_after(tokens, index).is_some()
+}
+
+/// Index of the next token after `index` that opens a call argument list,
+/// skipping a Rust turbofish (`name::<T>(`).
+fn call_paren_after(tokens: &[Token], index: usize) -> Option<usize> {
+    let mut next = index + 1;
+    if tokens.get(next).is_some_and(|t| t.is("::"))
+        && tokens.get(next + 1).is_some_and(|t| t.is("<"))
+    {
+        let mut depth = 0;
+        for (i, token) in tokens.iter().enumerate().skip(next + 1) {
+            if token.is("<") {
+                depth += 1;
+            } else if token.is(">") {
+                depth -= 1;
+            } else if token.is(">>") {
+                depth -= 2;
+            }
+            if depth <= 0 {
+                next = i + 1;
+                break;
+            }
+        }
+    }
+    tokens.get(next).filter(|t| t.is("(")).map(|_| next)
+}
+
+/// Index of the token closing the bracket opened at `open`.
+pub fn matching_close(tokens: &[Token], open: usize) -> Option<usize> {
+    let (open_text, close_text) = match tokens.get(open)?.text.as_str() {
+        "(" => ("(", ")"),
+        "[" => ("[", "]"),
+        "{" => ("{", "}"),
+        _ => return None,
+    };
+    let mut depth = 0usize;
+    for (i, token) in tokens.iter().enumerate().skip(open) {
+        if token.is(open_text) {
+            depth += 1;
+        } else if token.is(close_text) {
+            depth -= 1;
+            if depth == 0 {
+                return Some(i);
+            }
+        }
+    }
+    None
+}
+
+/// Index of the token opening the bracket closed at `close`.
+fn matching_open(tokens: &[Token], close: usize) -> Option<usize> {
+    let (open_text, close_text) = match tokens.get(close)?.text.as_str() {
+        ")" => ("(", ")"),
+        "]" => ("[", "]"),
+        "}" => ("{", "}"),
+        _ => return None,
+    };
+    let mut depth = 0usize;
+    for i in (0..=close).rev() {
+        if tokens[i].is(close_text) {
+            depth += 1;
+        } else if tokens[i].is(open_text) {
+            depth -= 1;
+            if depth == 0 {
+                return Some(i);
+            }
+        }
+    }
+    None
+}
+
+/// Whether the identifier at `index` is the name in a definition rather
+/// than a use, e.g. `def name(`, `func (r T) name(`, `int name(void) {`.
+pub fn is_definition_name(tokens: &[Token], index: usize, language: Language) -> bool {
+    let Some(prev) = index.checked_sub(1).and_then(|p| tokens.get(p)) else {
+        return false;
+    };
+    if language.definition_keywords().contains(&prev.text.as_str()) {
+        return true;
+    }
+    // Go method receiver: `func (s *Server) Start(`
+    if language == Language::Go && prev.is(")") {
+        return matching_open(tokens, index - 1)
+            .and_then(|open| open.checked_sub(1))
+            .is_some_and(|k| tokens[k].is("func"));
+    }
+    // C functions and TypeScript methods: `name(args) {` / `name(args): T {`
+    if matches!(language, Language::C | Language::TypeScript) {
+        let Some(close) = call_paren_after(tokens, index).and_then(|o| matching_close(tokens, o))
+        else {
+            return false;
+        };
+        let after = tokens.get(close + 1);
+        if after.is_some_and(|t| t.is("{")) {
+            return true;
+        }
+        let starts_member = prev.start.line != tokens[index].start.line
+            || ["{", "}", ";"].contains(&prev.text.as_str())
+            || [
+                "public",
+                "private",
+                "protected",
+                "static",
+                "async",
+                "get",
+                "set",
+            ]
+            .contains(&prev.text.as_str());
+        return language == Language::TypeScript
+            && starts_member
+            && after.is_some_and(|t| t.is(":"));
+    }
+    false
+}
+
+/// Ranges of call expressions `name(...)` on the given lines.
+///
+/// Matches plain calls, method calls (`obj.name(`) and path calls
+/// (`module::name(`); the definition of `name` itself is not a call.
+pub fn find_calls(
+    tokens: &[Token],
+    name: &str,
+    lines: RangeInclusive<u32>,
+    language: Language,
+) -> Vec<Range> {
+    tokens
+        .iter()
+        .enumerate()
+        .filter(|(_, t)| t.kind == TokenKind::Identifier && t.text == name)
+        .filter(|(_, t)| lines.contains(&t.start.line))
+        .filter(|(i, _)| call_paren_after(tokens, *i).is_some())
+        .filter(|(i, _)| !is_definition_name(tokens, *i, language))
+        .map(|(_, t)| t.range())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(src: &str, language: Language) -> Vec<String> {
+        tokenize(src, language)
+            .into_iter()
+            .map(|t| t.text)
+            .collect()
+    }
+
+    #[test]
+    fn test_language_from_path() {
+        assert_eq!(
+            Language::from_path(Path::new("a.py")),
+            Some(Language::Python)
+        );
+        assert_eq!(
+            Language::from_path(Path::new("a.tsx")),
+            Some(Language::TypeScript)
+        );
+        assert_eq!(Language::from_path(Path::new("a.h")), Some(Language::C));
+        assert_eq!(Language::from_path(Path::new("README.md")), None);
+    }
+
+    #[test]
+    fn test_tokenize_skips_comments_and_strings() {
+        let src = "x = foo(\"bar()\")  # call baz()\n";
+        let tokens = tokenize(src, Language::Python);
+        let idents: Vec<_> = tokens
+            .iter()
+            .filter(|t| t.kind == TokenKind::Identifier)
+            .map(|t| t.text.as_str())
+            .collect();
+        assert_eq!(idents, vec!["x", "foo"]);
+        assert!(tokens
+            .iter()
+            .any(|t| t.kind == TokenKind::String && t.text == "\"bar()\""));
+    }
+
+    #[test]
+    fn test_tokenize_python_triple_quoted_string() {
+        let src = "def f():\n    \"\"\"Calls g() in docs.\"\"\"\n    return g()\n";
+        let tokens = tokenize(src, Language::Python);
+        let calls = find_calls(&tokens, "g", 0..=10, Language::Python);
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].start, Position::new(2, 11));
+    }
+
+    #[test]
+    fn test_tokenize_rust_specifics() {
+        let src = "fn f<'a>(x: &'a str) -> char { let s = r#\"g()\"#; 'x' }";
+        let tokens = tokenize(src, Language::Rust);
+        assert!(tokens
+            .iter()
+            .any(|t| t.text == "'a" && t.kind == TokenKind::Identifier));
+        assert!(tokens
+            .iter()
+            .any(|t| t.text == "r#\"g()\"#" && t.kind == TokenKind::String));
+        assert!(tokens
+            .iter()
+            .any(|t| t.text == "'x'" && t.kind == TokenKind::String));
+        assert!(tokens
+            .iter()
+            .any(|t| t.text == "->" && t.kind == TokenKind::Operator));
+        assert!(tokens
+            .iter()
+            .any(|t| t.text == "fn" && t.kind == TokenKind::Keyword));
+    }
+
+    #[test]
+    fn test_tokenize_block_comments() {
+        let src = "/* foo() */ int main() { return foo(); }";
+        assert_eq!(
+            texts(src, Language::C),
+            vec!["int", "main", "(", ")", "{", "return", "foo", "(", ")", ";", "}"]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_numbers() {
+        let src = "x = 1.5e-3 + 0x1F + .5";
+        let numbers: Vec<_> = tokenize(src, Language::TypeScript)
+            .into_iter()
+            .filter(|t| t.kind == TokenKind::Number)
+            .map(|t| t.text)
+            .collect();
+        assert_eq!(numbers, vec!["1.5e-3", "0x1F", ".5"]);
+    }
+
+    #[test]
+    fn test_columns_are_utf16() {
+        let src = "s = \"😀\"; foo()";
+        let tokens = tokenize(src, Language::TypeScript);
+        let foo = tokens.iter().find(|t| t.text == "foo").unwrap();
+        // The emoji is two UTF-16 code units.
+        assert_eq!(foo.start, Position::new(0, 10));
+        assert_eq!(foo.end, Position::new(0, 13));
+    }
+
+    #[test]
+    fn test_find_calls_excludes_definition() {
+        let src = "def helper():\n    pass\n\ndef main():\n    helper()\n    obj.helper(1)\n    x = helper\n";
+        let tokens = tokenize(src, Language::Python);
+        let calls = find_calls(&tokens, "helper", 0..=100, Language::Python);
+        assert_eq!(
+            calls.iter().map(|r| r.start.line).collect::<Vec<_>>(),
+            vec![4, 5]
+        );
+        assert!(find_calls(&tokens, "helper", 5..=5, Language::Python).len() == 1);
+    }
+
+    #[test]
+    fn test_find_calls_rust_turbofish_and_paths() {
+        let src = "fn run() {\n    let v = parse::<u32>(s);\n    util::parse(s);\n}\n";
+        let tokens = tokenize(src, Language::Rust);
+        let calls = find_calls(&tokens, "parse", 0..=10, Language::Rust);
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[1].start, Position::new(2, 10));
+        assert_eq!(calls[1].end, Position::new(2, 15));
+    }
+
+    #[test]
+    fn test_find_calls_skips_definitions() {
+        let go = "func (s *Server) Start() {}\nfunc main() {\n\ts.Start()\n}\n";
+        let tokens = tokenize(go, Language::Go);
+        let calls = find_calls(&tokens, "Start", 0..=10, Language::Go);
+        assert_eq!(
+            calls.iter().map(|r| r.start.line).collect::<Vec<_>>(),
+            vec![2]
+        );
+
+        let ts = "class A {\n  run(): void {\n    this.run();\n  }\n}\n";
+        let tokens = tokenize(ts, Language::TypeScript);
+        let calls = find_calls(&tokens, "run", 0..=10, Language::TypeScript);
+        assert_eq!(
+            calls.iter().map(|r| r.start.line).collect::<Vec<_>>(),
+            vec![2]
+        );
+
+        let c = "int helper(void) {\n  return 0;\n}\nint main() { return helper(); }\n";
+        let tokens = tokenize(c, Language::C);
+        let calls = find_calls(&tokens, "helper", 0..=10, Language::C);
+        assert_eq!(
+            calls.iter().map(|r| r.start.line).collect::<Vec<_>>(),
+            vec![3]
+        );
+    }
+}
@@ -3,13 +3,18 @@
 //! This crate implements a Language Server Protocol (LSP) server for CodeGraph,
 //! providing cross-language code intelligence through graph-based analysis.
 
+pub mod algorithms;
 pub mod backend;
 pub mod cache;
 pub mod custom_requests;
 pub mod error;
 pub mod handlers;
+pub mod import_graph;
 pub mod index;
+pub mod lexer;
+pub mod occurrences;
 pub mod parser_registry;
+pub mod query;
 pub mod watcher;
 
 pub use backend::CodeGraphBackend;
@@ -3,6 +3,7 @@
 //! This is the main entry point for the CodeGraph Language Server.
 //! It initializes the server and starts listening for LSP messages on stdio.
 
+use codegraph_lsp::custom_requests::register_custom_methods;
 use codegraph_lsp::CodeGraphBackend;
 use tower_lsp::{LspService, Server};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
@@ -23,7 +24,8 @@ async fn main() {
     let stdin = tokio::io::stdin();
     let stdout = tokio::io::stdout();
 
-    let (service, socket) = LspService::new(CodeGraphBackend::new);
+    let (service, socket) =
+        register_custom_methods(LspService::build(CodeGraphBackend::new)).finish();
 
     Server::new(stdin, stdout, socket).serve(service).await;
 }
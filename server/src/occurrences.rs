@@ -0,0 +1,391 @@
+//! Identifier occurrence index.
+//!
+//! `SymbolIndex` only knows where symbols are *defined*. This index records
+//! every identifier in a file with its exact range and how it is used, so a
+//! cursor on a call inside a function body can be mapped to the symbol being
+//! called rather than to the enclosing function.
+//!
+//! Occurrences are resolved to their target NodeId when queried, against the
+//! current symbol index and graph, so edits to other files never leave stale
+//! targets behind.
+
+use crate::index::SymbolIndex;
+use crate::lexer::{self, Language, TokenKind};
+use codegraph::{CodeGraph, EdgeType, NodeId};
+use dashmap::DashMap;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tower_lsp::lsp_types::{Position, Range};
+
+/// How an identifier is used at an occurrence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OccurrenceKind {
+    /// The name being defined (`def name`, `fn name`).
+    Definition,
+    /// A call expression (`name(...)`).
+    Call,
+    /// The target of an assignment (`name = ...`, `name += ...`).
+    Write,
+    /// Any other use.
+    Read,
+}
+
+/// A single identifier occurrence.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Occurrence {
+    pub name: Arc<str>,
+    pub range: Range,
+    pub kind: OccurrenceKind,
+}
+
+/// An occurrence together with the symbol it refers to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedOccurrence {
+    pub path: PathBuf,
+    pub occurrence: Occurrence,
+    pub target: NodeId,
+}
+
+/// Edges from a symbol to the symbols its body uses by name.
+const USE_EDGES: &[EdgeType] = &[
+    EdgeType::Calls,
+    EdgeType::Invokes,
+    EdgeType::Instantiates,
+    EdgeType::References,
+    EdgeType::Uses,
+];
+
+const ASSIGNMENT_OPERATORS: &[&str] = &[
+    "=", "+=", "-=", "*=", "/=", "%=", "&=", "|=", "^=", "<<=", ">>=", "**=", "//=", ":=", "&&=",
+    "||=", "??=",
+];
+
+/// Extract the identifier occurrences of a source file.
+pub fn collect_occurrences(text: &str, language: Language) -> Vec<Occurrence> {
+    let tokens = lexer::tokenize(text, language);
+    let mut interned: HashMap<&str, Arc<str>> = HashMap::new();
+    let mut occurrences = Vec::new();
+
+    for (i, token) in tokens.iter().enumerate() {
+        if token.kind != TokenKind::Identifier {
+            continue;
+        }
+        let kind = if lexer::is_definition_name(&tokens, i, language) {
+            OccurrenceKind::Definition
+        } else if lexer::opens_call(&tokens, i) {
+            OccurrenceKind::Call
+        } else if tokens
+            .get(i + 1)
+            .is_some_and(|next| ASSIGNMENT_OPERATORS.iter().any(|op| next.is(op)))
+        {
+            OccurrenceKind::Write
+        } else {
+            OccurrenceKind::Read
+        };
+
+        let name = interned
+            .entry(token.text.as_str())
+            .or_insert_with(|| Arc::from(token.text.as_str()))
+            .clone();
+        occurrences.push(Occurrence {
+            name,
+            range: token.range(),
+            kind,
+        });
+    }
+
+    occurrences
+}
+
+/// Per-file identifier occurrences, sorted by position.
+#[derive(Default)]
+pub struct OccurrenceIndex {
+    by_file: DashMap<PathBuf, Vec<Occurrence>>,
+}
+
+impl OccurrenceIndex {
+    /// Create a new empty index.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// (Re)index a file from its current text.
+    pub fn index_file(&self, path: &Path, text: &str) {
+        let Some(language) = Language::from_path(path) else {
+            return;
+        };
+        self.by_file
+            .insert(path.to_path_buf(), collect_occurrences(text, language));
+    }
+
+    /// Remove a file from the index.
+    pub fn remove_file(&self, path: &Path) {
+        self.by_file.remove(path);
+    }
+
+    /// Clear all files.
+    pub fn clear(&self) {
+        self.by_file.clear();
+    }
+
+    /// The occurrence under `position` (0-indexed, LSP style), if any.
+    pub fn find_at(&self, path: &Path, position: Position) -> Option<Occurrence> {
+        let occurrences = self.by_file.get(path)?;
+        let after = occurrences.partition_point(|o| o.range.start <= position);
+        let candidate = occurrences.get(after.checked_sub(1)?)?;
+        (candidate.range.end >= position).then(|| candidate.clone())
+    }
+
+    /// All occurrences in a file.
+    pub fn file_occurrences(&self, path: &Path) -> Vec<Occurrence> {
+        self.by_file
+            .get(path)
+            .map(|v| v.clone())
+            .unwrap_or_default()
+    }
+
+    /// All occurrences named `name`, across every indexed file.
+    pub fn occurrences_named(&self, name: &str) -> Vec<(PathBuf, Occurrence)> {
+        let mut found = Vec::new();
+        for entry in self.by_file.iter() {
+            for occurrence in entry.value().iter().filter(|o| &*o.name == name) {
+                found.push((entry.key().clone(), occurrence.clone()));
+            }
+        }
+        found.sort_by(|a, b| {
+            a.0.cmp(&b.0)
+                .then(a.1.range.start.line.cmp(&b.1.range.start.line))
+                .then(a.1.range.start.character.cmp(&b.1.range.start.character))
+        });
+        found
+    }
+
+    /// Number of indexed files.
+    pub fn file_count(&self) -> usize {
+        self.by_file.len()
+    }
+}
+
+/// Resolve an occurrence in `path` to the symbol it refers to.
+///
+/// Candidates are symbols with the same unqualified name. In order of
+/// preference: the definition at this very position, a symbol the enclosing
+/// symbol calls or references, a symbol defined in the same file, the only
+/// candidate in the workspace, and the only candidate in the same directory.
+pub fn resolve(
+    graph: &CodeGraph,
+    symbols: &SymbolIndex,
+    path: &Path,
+    occurrence: &Occurrence,
+) -> Option<NodeId> {
+    let candidates = symbols.find_by_short_name(&occurrence.name);
+    if candidates.is_empty() {
+        return None;
+    }
+
+    let line = occurrence.range.start.line + 1;
+    let in_file: Vec<NodeId> = candidates
+        .iter()
+        .copied()
+        .filter(|&id| symbols.find_file_for_node(id).as_deref() == Some(path))
+        .collect();
+
+    if let Some(&defined_here) = in_file.iter().find(|&&id| {
+        symbols
+            .get_node_range(path, id)
+            .is_some_and(|r| r.start_line == line)
+    }) {
+        return Some(defined_here);
+    }
+
+    // `requests.get()` in a file that also defines `Foo.get` is whatever the
+    // parser linked the enclosing function to, not the same-file method.
+    if let Some(enclosing) = symbols.find_at_position(path, line, occurrence.range.start.character)
+    {
+        if let Some(&connected) = candidates.iter().find(|&&id| {
+            graph.get_edges_between(enclosing, id).is_ok_and(|edges| {
+                edges.into_iter().any(|edge_id| {
+                    graph
+                        .get_edge(edge_id)
+                        .is_ok_and(|e| USE_EDGES.contains(&e.edge_type))
+                })
+            })
+        }) {
+            return Some(connected);
+        }
+    }
+
+    if let Some(&first) = in_file.first() {
+        return Some(first);
+    }
+
+    if candidates.len() == 1 {
+        return Some(candidates[0]);
+    }
+
+    let dir = path.parent();
+    let mut same_dir = candidates.iter().filter(|&&id| {
+        symbols
+            .find_file_for_node(id)
+            .is_some_and(|p| p.parent() == dir)
+    });
+    match (same_dir.next(), same_dir.next()) {
+        (Some(&only), None) => Some(only),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(src: &str, language: Language, name: &str) -> Vec<(u32, OccurrenceKind)> {
+        collect_occurrences(src, language)
+            .into_iter()
+            .filter(|o| &*o.name == name)
+            .map(|o| (o.range.start.line, o.kind))
+            .collect()
+    }
+
+    #[test]
+    fn test_occurrence_kinds_python() {
+        let src =
+            "def helper():\n    pass\n\ndef main():\n    helper()\n    f = helper\n    f = 2\n";
+        assert_eq!(
+            kinds(src, Language::Python, "helper"),
+            vec![
+                (0, OccurrenceKind::Definition),
+                (4, OccurrenceKind::Call),
+                (5, OccurrenceKind::Read),
+            ]
+        );
+        assert_eq!(
+            kinds(src, Language::Python, "f"),
+            vec![(5, OccurrenceKind::Write), (6, OccurrenceKind::Write)]
+        );
+    }
+
+    #[test]
+    fn test_occurrence_kinds_rust() {
+        let src = "fn run() {\n    let mut n = 0;\n    n += step(n);\n}\n";
+        assert_eq!(
+            kinds(src, Language::Rust, "n"),
+            vec![
+                (1, OccurrenceKind::Write),
+                (2, OccurrenceKind::Write),
+                (2, OccurrenceKind::Read),
+            ]
+        );
+        assert_eq!(
+            kinds(src, Language::Rust, "step"),
+            vec![(2, OccurrenceKind::Call)]
+        );
+        // Keywords are not occurrences.
+        assert!(kinds(src, Language::Rust, "let").is_empty());
+    }
+
+    #[test]
+    fn test_find_at_position() {
+        let index = OccurrenceIndex::new();
+        let path = PathBuf::from("/w/a.py");
+        index.index_file(&path, "def main():\n    helper(x)\n");
+
+        let at = |line, character| {
+            index
+                .find_at(&path, Position { line, character })
+                .map(|o| o.name.to_string())
+        };
+        assert_eq!(at(1, 4).as_deref(), Some("helper"));
+        assert_eq!(at(1, 10).as_deref(), Some("helper"));
+        assert_eq!(at(1, 11).as_deref(), Some("x"));
+        assert_eq!(at(1, 2), None);
+        assert_eq!(at(5, 0), None);
+    }
+
+    #[test]
+    fn test_occurrences_named_and_remove() {
+        let index = OccurrenceIndex::new();
+        index.index_file(Path::new("/w/a.py"), "helper()\n");
+        index.index_file(Path::new("/w/b.py"), "x = helper\nhelper()\n");
+        index.index_file(Path::new("/w/README.md"), "helper()\n");
+
+        assert_eq!(index.file_count(), 2);
+        assert_eq!(index.occurrences_named("helper").len(), 3);
+
+        index.remove_file(Path::new("/w/b.py"));
+        assert_eq!(index.occurrences_named("helper").len(), 1);
+
+        index.clear();
+        assert_eq!(index.file_count(), 0);
+    }
+
+    #[test]
+    fn test_resolve_prefers_linked_symbol_over_same_file_name() {
+        use codegraph::PropertyMap;
+        use codegraph_parser_api::FileInfo;
+
+        let mut graph = CodeGraph::in_memory().unwrap();
+        let mut function = |name: &str, start: i64, end: i64| {
+            let properties = PropertyMap::new()
+                .with("name", name)
+                .with("line_start", start)
+                .with("line_end", end);
+            graph
+                .add_node(codegraph::NodeType::Function, properties)
+                .unwrap()
+        };
+        let method = function("Foo.get", 1, 2);
+        let fetch = function("fetch", 4, 5);
+        let library = function("get", 1, 3);
+        graph
+            .add_edge(fetch, library, EdgeType::Calls, PropertyMap::new())
+            .unwrap();
+
+        let symbols = SymbolIndex::new();
+        let file_info = |path: &str, functions: Vec<NodeId>| FileInfo {
+            file_path: PathBuf::from(path),
+            file_id: 0,
+            functions,
+            classes: Vec::new(),
+            traits: Vec::new(),
+            imports: Vec::new(),
+            parse_time: std::time::Duration::ZERO,
+            line_count: 0,
+            byte_count: 0,
+        };
+        let app = Path::new("/w/app.py");
+        symbols.add_file(
+            app.to_path_buf(),
+            &file_info("/w/app.py", vec![method, fetch]),
+            &graph,
+        );
+        symbols.add_file(
+            PathBuf::from("/w/lib/requests.py"),
+            &file_info("/w/lib/requests.py", vec![library]),
+            &graph,
+        );
+
+        let occurrence = |line: u32| Occurrence {
+            name: Arc::from("get"),
+            range: Range {
+                start: Position {
+                    line,
+                    character: 13,
+                },
+                end: Position {
+                    line,
+                    character: 16,
+                },
+            },
+            kind: OccurrenceKind::Call,
+        };
+        // `requests.get()` inside `fetch`
+        assert_eq!(
+            resolve(&graph, &symbols, app, &occurrence(4)),
+            Some(library)
+        );
+        // `self.get()` outside any symbol linked to the library
+        assert_eq!(resolve(&graph, &symbols, app, &occurrence(6)), Some(method));
+    }
+}
@@ -0,0 +1,1156 @@
+//! A small Cypher-like query language over the code graph.
+//!
+//! ```text
+//! MATCH (f:Function)-[:Calls*1..3]->(g:Function)
+//! WHERE f.file STARTS WITH 'api/' AND g.file STARTS WITH 'db/'
+//!   AND NOT (f)<-[:Calls]-(:Function {isTest: true})
+//! RETURN DISTINCT f, g.name AS callee
+//! LIMIT 50
+//! ```
+//!
+//! Supported: comma-separated patterns sharing variables, node labels
+//! (`NodeType` names, `|` for alternatives), inline property maps, directed
+//! or undirected relationships with `EdgeType` names and `*min..max` hop
+//! ranges, `WHERE` with `AND`/`OR`/`NOT`, comparisons, `STARTS WITH`,
+//! `ENDS WITH`, `CONTAINS`, `IN [...]`, `IS [NOT] NULL` and pattern
+//! predicates, `RETURN [DISTINCT]` of variables and properties with `AS`,
+//! and `LIMIT`. Keywords are case-insensitive.
+//!
+//! Matching is a backtracking search that stops at a row limit or deadline.
+//! Property values come from a caller-supplied lookup so handlers can add
+//! computed properties such as the containing file.
+
+use codegraph::{CodeGraph, Direction, EdgeType, NodeId, NodeType, PropertyValue};
+use serde_json::Value;
+use std::collections::{HashSet, VecDeque};
+use std::time::Instant;
+
+/// Upper bound for `*` and open-ended hop ranges.
+pub const MAX_HOPS: usize = 10;
+
+/// The deadline is checked every this many search steps.
+const DEADLINE_CHECK_INTERVAL: u32 = 1024;
+
+const NODE_TYPES: &[NodeType] = &[
+    NodeType::CodeFile,
+    NodeType::Function,
+    NodeType::Class,
+    NodeType::Module,
+    NodeType::Variable,
+    NodeType::Type,
+    NodeType::Interface,
+    NodeType::Generic,
+];
+
+const EDGE_TYPES: &[EdgeType] = &[
+    EdgeType::Imports,
+    EdgeType::ImportsFrom,
+    EdgeType::Contains,
+    EdgeType::Calls,
+    EdgeType::Invokes,
+    EdgeType::Instantiates,
+    EdgeType::Extends,
+    EdgeType::Implements,
+    EdgeType::Uses,
+    EdgeType::Defines,
+    EdgeType::References,
+];
+
+// ==========================================
+// Syntax Tree
+// ==========================================
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    pub patterns: Vec<Pattern>,
+    pub filter: Option<Expr>,
+    pub distinct: bool,
+    pub returns: Vec<ReturnItem>,
+    pub limit: Option<usize>,
+}
+
+/// `(a)-[..]->(b)<-[..]-(c)`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pattern {
+    pub start: NodePattern,
+    pub steps: Vec<(RelPattern, NodePattern)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodePattern {
+    pub var: Option<String>,
+    /// Any of these types; empty matches every node
+    pub labels: Vec<NodeType>,
+    pub properties: Vec<(String, Value)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RelPattern {
+    /// Any of these types; empty follows every edge
+    pub types: Vec<EdgeType>,
+    pub direction: Direction,
+    pub min_hops: usize,
+    pub max_hops: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Compare(Operand, CompareOp, Operand),
+    IsNull(Operand, bool),
+    /// A bare operand, true when it is `true`
+    Truthy(Operand),
+    /// True when the pattern matches with the current bindings
+    Exists(Pattern),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    StartsWith,
+    EndsWith,
+    Contains,
+    In,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operand {
+    Property(String, String),
+    Literal(Value),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReturnItem {
+    pub var: String,
+    /// `None` returns the node itself
+    pub property: Option<String>,
+    pub alias: String,
+}
+
+// ==========================================
+// Lexer
+// ==========================================
+
+#[derive(Debug, Clone, PartialEq)]
+enum Tok {
+    Ident(String),
+    Str(String),
+    Num(f64),
+    Sym(&'static str),
+}
+
+/// Words that cannot start an operand.
+const KEYWORDS: &[&str] = &[
+    "MATCH", "WHERE", "RETURN", "DISTINCT", "LIMIT", "AND", "OR", "NOT", "STARTS", "ENDS", "WITH",
+    "CONTAINS", "IN", "IS", "AS",
+];
+
+/// Symbols, longest first so matching is greedy.
+const SYMBOLS: &[&str] = &[
+    "->", "<-", "..", "<>", "!=", "<=", ">=", "(", ")", "[", "]", "{", "}", ":", ",", ".", "-",
+    "*", "=", "<", ">", "|",
+];
+
+fn lex(src: &str) -> Result<Vec<(Tok, usize)>, String> {
+    let chars: Vec<(usize, char)> = src.char_indices().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let (offset, c) = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].1.is_alphanumeric() || chars[i].1 == '_') {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().map(|(_, c)| c).collect();
+            tokens.push((Tok::Ident(text), offset));
+        } else if c.is_ascii_digit() {
+            let start = i;
+            while i < chars.len() && chars[i].1.is_ascii_digit() {
+                i += 1;
+            }
+            // A fraction, but not the `..` of a hop range.
+            if i + 1 < chars.len() && chars[i].1 == '.' && chars[i + 1].1.is_ascii_digit() {
+                i += 1;
+                while i < chars.len() && chars[i].1.is_ascii_digit() {
+                    i += 1;
+                }
+            }
+            let text: String = chars[start..i].iter().map(|(_, c)| c).collect();
+            let number = text
+                .parse()
+                .map_err(|_| format!("invalid number '{text}' at offset {offset}"))?;
+            tokens.push((Tok::Num(number), offset));
+        } else if c == '\'' || c == '"' {
+            let mut text = String::new();
+            i += 1;
+            loop {
+                match chars.get(i) {
+                    None => return Err(format!("unterminated string at offset {offset}")),
+                    Some(&(_, q)) if q == c => break,
+                    Some(&(_, '\\')) => {
+                        if let Some(&(_, escaped)) = chars.get(i + 1) {
+                            text.push(escaped);
+                        }
+                        i += 2;
+                    }
+                    Some(&(_, ch)) => {
+                        text.push(ch);
+                        i += 1;
+                    }
+                }
+            }
+            i += 1;
+            tokens.push((Tok::Str(text), offset));
+        } else {
+            let rest = &src[offset..];
+            let Some(symbol) = SYMBOLS.iter().find(|s| rest.starts_with(**s)) else {
+                return Err(format!("unexpected character '{c}' at offset {offset}"));
+            };
+            i += symbol.chars().count();
+            tokens.push((Tok::Sym(symbol), offset));
+        }
+    }
+    Ok(tokens)
+}
+
+// ==========================================
+// Parser
+// ==========================================
+
+struct Parser {
+    tokens: Vec<(Tok, usize)>,
+    pos: usize,
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Tok> {
+        self.tokens.get(self.pos).map(|(t, _)| t)
+    }
+
+    fn offset(&self) -> usize {
+        self.tokens.get(self.pos).map_or(self.end, |(_, o)| *o)
+    }
+
+    fn error<T>(&self, expected: &str) -> Result<T, String> {
+        let found = match self.peek() {
+            Some(Tok::Ident(s)) => format!("'{s}'"),
+            Some(Tok::Str(s)) => format!("string '{s}'"),
+            Some(Tok::Num(n)) => format!("number {n}"),
+            Some(Tok::Sym(s)) => format!("'{s}'"),
+            None => "end of query".to_string(),
+        };
+        Err(format!(
+            "expected {expected} at offset {}, found {found}",
+            self.offset()
+        ))
+    }
+
+    fn is_sym(&self, symbol: &str) -> bool {
+        matches!(self.peek(), Some(Tok::Sym(s)) if *s == symbol)
+    }
+
+    fn eat_sym(&mut self, symbol: &str) -> bool {
+        let found = self.is_sym(symbol);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect_sym(&mut self, symbol: &str) -> Result<(), String> {
+        if self.eat_sym(symbol) {
+            Ok(())
+        } else {
+            self.error(&format!("'{symbol}'"))
+        }
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Tok::Ident(s)) if s.eq_ignore_ascii_case(keyword))
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let found = self.is_keyword(keyword);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), String> {
+        if self.eat_keyword(keyword) {
+            Ok(())
+        } else {
+            self.error(keyword)
+        }
+    }
+
+    fn ident(&mut self, what: &str) -> Result<String, String> {
+        match self.peek() {
+            Some(Tok::Ident(s)) => {
+                let s = s.clone();
+                self.pos += 1;
+                Ok(s)
+            }
+            _ => self.error(what),
+        }
+    }
+
+    fn query(&mut self) -> Result<Query, String> {
+        self.expect_keyword("MATCH")?;
+        let mut patterns = vec![self.pattern()?];
+        while self.eat_sym(",") {
+            patterns.push(self.pattern()?);
+        }
+
+        let filter = if self.eat_keyword("WHERE") {
+            Some(self.or()?)
+        } else {
+            None
+        };
+
+        self.expect_keyword("RETURN")?;
+        let distinct = self.eat_keyword("DISTINCT");
+        let mut returns = vec![self.return_item()?];
+        while self.eat_sym(",") {
+            returns.push(self.return_item()?);
+        }
+
+        let limit = if self.eat_keyword("LIMIT") {
+            match self.peek() {
+                Some(Tok::Num(n)) if *n >= 0.0 && n.fract() == 0.0 => {
+                    let n = *n as usize;
+                    self.pos += 1;
+                    Some(n)
+                }
+                _ => return self.error("a row count"),
+            }
+        } else {
+            None
+        };
+
+        if self.peek().is_some() {
+            return self.error("end of query");
+        }
+        Ok(Query {
+            patterns,
+            filter,
+            distinct,
+            returns,
+            limit,
+        })
+    }
+
+    fn pattern(&mut self) -> Result<Pattern, String> {
+        let start = self.node()?;
+        let mut steps = Vec::new();
+        while self.is_sym("-") || self.is_sym("<-") {
+            let rel = self.relationship()?;
+            steps.push((rel, self.node()?));
+        }
+        Ok(Pattern { start, steps })
+    }
+
+    fn node(&mut self) -> Result<NodePattern, String> {
+        self.expect_sym("(")?;
+        let var = match self.peek() {
+            Some(Tok::Ident(_)) => Some(self.ident("a variable")?),
+            _ => None,
+        };
+        let mut labels = Vec::new();
+        if self.eat_sym(":") {
+            loop {
+                let offset = self.offset();
+                let name = self.ident("a node type")?;
+                labels.push(node_type(&name).ok_or_else(|| {
+                    format!("unknown node type '{name}' at offset {offset}; expected one of {NODE_TYPES:?}")
+                })?);
+                if !self.eat_sym("|") {
+                    break;
+                }
+            }
+        }
+        let mut properties = Vec::new();
+        if self.eat_sym("{") {
+            loop {
+                let key = self.ident("a property name")?;
+                self.expect_sym(":")?;
+                properties.push((key, self.literal()?));
+                if !self.eat_sym(",") {
+                    break;
+                }
+            }
+            self.expect_sym("}")?;
+        }
+        self.expect_sym(")")?;
+        Ok(NodePattern {
+            var,
+            labels,
+            properties,
+        })
+    }
+
+    fn relationship(&mut self) -> Result<RelPattern, String> {
+        let incoming = self.eat_sym("<-");
+        if !incoming {
+            self.expect_sym("-")?;
+        }
+
+        let mut types = Vec::new();
+        let (mut min_hops, mut max_hops) = (1, 1);
+        if self.eat_sym("[") {
+            if self.eat_sym(":") {
+                loop {
+                    let offset = self.offset();
+                    let name = self.ident("an edge type")?;
+                    types.push(edge_type(&name).ok_or_else(|| {
+                        format!("unknown edge type '{name}' at offset {offset}; expected one of {EDGE_TYPES:?}")
+                    })?);
+                    if !self.eat_sym("|") {
+                        break;
+                    }
+                }
+            }
+            if self.eat_sym("*") {
+                let low = self.hop_count();
+                if self.eat_sym("..") {
+                    min_hops = low.unwrap_or(1);
+                    max_hops = self.hop_count().unwrap_or(MAX_HOPS);
+                } else {
+                    min_hops = low.unwrap_or(1);
+                    max_hops = low.unwrap_or(MAX_HOPS);
+                }
+                if min_hops > max_hops || max_hops > MAX_HOPS {
+                    return Err(format!(
+                        "hop range must be within 0..{MAX_HOPS}, got {min_hops}..{max_hops}"
+                    ));
+                }
+            }
+            self.expect_sym("]")?;
+        }
+
+        let direction = if incoming {
+            self.expect_sym("-")?;
+            Direction::Incoming
+        } else if self.eat_sym("->") {
+            Direction::Outgoing
+        } else {
+            self.expect_sym("-")?;
+            Direction::Both
+        };
+        Ok(RelPattern {
+            types,
+            direction,
+            min_hops,
+            max_hops,
+        })
+    }
+
+    fn hop_count(&mut self) -> Option<usize> {
+        match self.peek() {
+            Some(Tok::Num(n)) if *n >= 0.0 && n.fract() == 0.0 => {
+                let n = *n as usize;
+                self.pos += 1;
+                Some(n)
+            }
+            _ => None,
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut expr = self.and()?;
+        while self.eat_keyword("OR") {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut expr = self.not()?;
+        while self.eat_keyword("AND") {
+            expr = Expr::And(Box::new(expr), Box::new(self.not()?));
+        }
+        Ok(expr)
+    }
+
+    fn not(&mut self) -> Result<Expr, String> {
+        if self.eat_keyword("NOT") {
+            Ok(Expr::Not(Box::new(self.not()?)))
+        } else {
+            self.primary()
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        if self.is_sym("(") {
+            // A pattern predicate, or a parenthesized expression.
+            let start = self.pos;
+            if let Ok(pattern) = self.pattern() {
+                if !pattern.steps.is_empty() {
+                    return Ok(Expr::Exists(pattern));
+                }
+            }
+            self.pos = start + 1;
+            let expr = self.or()?;
+            self.expect_sym(")")?;
+            return Ok(expr);
+        }
+
+        let left = self.operand()?;
+        if self.eat_keyword("IS") {
+            let negated = self.eat_keyword("NOT");
+            self.expect_keyword("NULL")?;
+            return Ok(Expr::IsNull(left, !negated));
+        }
+        let op = if self.eat_keyword("STARTS") {
+            self.expect_keyword("WITH")?;
+            CompareOp::StartsWith
+        } else if self.eat_keyword("ENDS") {
+            self.expect_keyword("WITH")?;
+            CompareOp::EndsWith
+        } else if self.eat_keyword("CONTAINS") {
+            CompareOp::Contains
+        } else if self.eat_keyword("IN") {
+            CompareOp::In
+        } else if self.eat_sym("=") {
+            CompareOp::Eq
+        } else if self.eat_sym("<>") || self.eat_sym("!=") {
+            CompareOp::Ne
+        } else if self.eat_sym("<=") {
+            CompareOp::Le
+        } else if self.eat_sym(">=") {
+            CompareOp::Ge
+        } else if self.eat_sym("<") {
+            CompareOp::Lt
+        } else if self.eat_sym(">") {
+            CompareOp::Gt
+        } else {
+            return Ok(Expr::Truthy(left));
+        };
+        Ok(Expr::Compare(left, op, self.operand()?))
+    }
+
+    fn operand(&mut self) -> Result<Operand, String> {
+        match self.peek() {
+            Some(Tok::Ident(s)) if KEYWORDS.iter().any(|k| s.eq_ignore_ascii_case(k)) => {
+                self.error("a value")
+            }
+            Some(Tok::Ident(s))
+                if !["true", "false", "null"]
+                    .iter()
+                    .any(|k| s.eq_ignore_ascii_case(k)) =>
+            {
+                let var = self.ident("a variable")?;
+                self.expect_sym(".")?;
+                Ok(Operand::Property(var, self.ident("a property name")?))
+            }
+            _ => Ok(Operand::Literal(self.literal()?)),
+        }
+    }
+
+    fn literal(&mut self) -> Result<Value, String> {
+        let negative = self.eat_sym("-");
+        let value = match self.peek() {
+            Some(Tok::Num(n)) => {
+                let n = if negative { -*n } else { *n };
+                if n.fract() == 0.0 && n.abs() < i64::MAX as f64 {
+                    Value::from(n as i64)
+                } else {
+                    Value::from(n)
+                }
+            }
+            _ if negative => return self.error("a number"),
+            Some(Tok::Str(s)) => Value::String(s.clone()),
+            Some(Tok::Ident(s)) if s.eq_ignore_ascii_case("true") => Value::Bool(true),
+            Some(Tok::Ident(s)) if s.eq_ignore_ascii_case("false") => Value::Bool(false),
+            Some(Tok::Ident(s)) if s.eq_ignore_ascii_case("null") => Value::Null,
+            Some(Tok::Sym("[")) => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                if !self.eat_sym("]") {
+                    loop {
+                        items.push(self.literal()?);
+                        if !self.eat_sym(",") {
+                            break;
+                        }
+                    }
+                    self.expect_sym("]")?;
+                }
+                return Ok(Value::Array(items));
+            }
+            _ => return self.error("a value"),
+        };
+        self.pos += 1;
+        Ok(value)
+    }
+
+    fn return_item(&mut self) -> Result<ReturnItem, String> {
+        let var = self.ident("a variable")?;
+        let property = if self.eat_sym(".") {
+            Some(self.ident("a property name")?)
+        } else {
+            None
+        };
+        let alias = if self.eat_keyword("AS") {
+            self.ident("an alias")?
+        } else {
+            match &property {
+                Some(property) => format!("{var}.{property}"),
+                None => var.clone(),
+            }
+        };
+        Ok(ReturnItem {
+            var,
+            property,
+            alias,
+        })
+    }
+}
+
+fn node_type(name: &str) -> Option<NodeType> {
+    if name.eq_ignore_ascii_case("File") {
+        return Some(NodeType::CodeFile);
+    }
+    NODE_TYPES
+        .iter()
+        .find(|t| format!("{t:?}").eq_ignore_ascii_case(name))
+        .copied()
+}
+
+fn edge_type(name: &str) -> Option<EdgeType> {
+    EDGE_TYPES
+        .iter()
+        .find(|t| format!("{t:?}").eq_ignore_ascii_case(name))
+        .copied()
+}
+
+/// Parse a query, checking that every returned or filtered variable is bound
+/// by a `MATCH` pattern.
+pub fn parse(src: &str) -> Result<Query, String> {
+    let mut parser = Parser {
+        tokens: lex(src)?,
+        pos: 0,
+        end: src.len(),
+    };
+    let query = parser.query()?;
+
+    let bound: HashSet<&str> = query
+        .patterns
+        .iter()
+        .flat_map(pattern_vars)
+        .map(String::as_str)
+        .collect();
+    let mut used: Vec<&str> = query.returns.iter().map(|r| r.var.as_str()).collect();
+    if let Some(filter) = &query.filter {
+        expr_vars(filter, &mut used);
+    }
+    if let Some(unbound) = used.into_iter().find(|v| !bound.contains(v)) {
+        return Err(format!("variable '{unbound}' is not defined by MATCH"));
+    }
+    Ok(query)
+}
+
+fn pattern_vars(pattern: &Pattern) -> impl Iterator<Item = &String> {
+    std::iter::once(&pattern.start)
+        .chain(pattern.steps.iter().map(|(_, node)| node))
+        .filter_map(|node| node.var.as_ref())
+}
+
+/// Variables a filter reads, excluding those a pattern predicate binds itself.
+fn expr_vars<'a>(expr: &'a Expr, vars: &mut Vec<&'a str>) {
+    match expr {
+        Expr::And(a, b) | Expr::Or(a, b) => {
+            expr_vars(a, vars);
+            expr_vars(b, vars);
+        }
+        Expr::Not(e) => expr_vars(e, vars),
+        Expr::Compare(a, _, b) => {
+            for operand in [a, b] {
+                if let Operand::Property(var, _) = operand {
+                    vars.push(var);
+                }
+            }
+        }
+        Expr::IsNull(Operand::Property(var, _), _) | Expr::Truthy(Operand::Property(var, _)) => {
+            vars.push(var)
+        }
+        Expr::IsNull(..) | Expr::Truthy(_) | Expr::Exists(_) => {}
+    }
+}
+
+// ==========================================
+// Values
+// ==========================================
+
+/// JSON form of a stored property.
+pub fn property_value(value: &PropertyValue) -> Value {
+    match value {
+        PropertyValue::String(s) => Value::String(s.clone()),
+        PropertyValue::Int(i) => Value::from(*i),
+        PropertyValue::Float(f) => Value::from(*f),
+        PropertyValue::Bool(b) => Value::Bool(*b),
+        PropertyValue::StringList(list) => Value::from(list.clone()),
+        PropertyValue::IntList(list) => Value::from(list.clone()),
+        PropertyValue::Null => Value::Null,
+    }
+}
+
+/// `id`, `kind` and the node's stored properties.
+pub fn stored_property(graph: &CodeGraph, node_id: NodeId, key: &str) -> Option<Value> {
+    let node = graph.get_node(node_id).ok()?;
+    match key {
+        "id" => Some(Value::from(node_id)),
+        "kind" => Some(Value::String(format!("{:?}", node.node_type))),
+        _ => node.properties.get(key).map(property_value),
+    }
+}
+
+/// Equality that treats `true` and `"true"` alike, since parsers store
+/// flags both ways, and compares numbers by value.
+fn values_equal(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => x.as_f64() == y.as_f64(),
+        (Value::Bool(flag), Value::String(s)) | (Value::String(s), Value::Bool(flag)) => {
+            s.eq_ignore_ascii_case(&flag.to_string())
+        }
+        _ => a == b,
+    }
+}
+
+fn compare(a: &Value, op: CompareOp, b: &Value) -> bool {
+    use std::cmp::Ordering;
+    let ordering = match (a, b) {
+        (Value::Number(x), Value::Number(y)) => x.as_f64().partial_cmp(&y.as_f64()),
+        (Value::String(x), Value::String(y)) => Some(x.cmp(y)),
+        _ => None,
+    };
+    match op {
+        CompareOp::Eq => values_equal(a, b),
+        CompareOp::Ne => !a.is_null() && !b.is_null() && !values_equal(a, b),
+        CompareOp::Lt => ordering == Some(Ordering::Less),
+        CompareOp::Le => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+        CompareOp::Gt => ordering == Some(Ordering::Greater),
+        CompareOp::Ge => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+        CompareOp::StartsWith | CompareOp::EndsWith | CompareOp::Contains => match (a, b) {
+            (Value::String(x), Value::String(y)) => match op {
+                CompareOp::StartsWith => x.starts_with(y.as_str()),
+                CompareOp::EndsWith => x.ends_with(y.as_str()),
+                _ => x.contains(y.as_str()),
+            },
+            (Value::Array(items), _) if op == CompareOp::Contains => {
+                items.iter().any(|item| values_equal(item, b))
+            }
+            _ => false,
+        },
+        CompareOp::In => match b {
+            Value::Array(items) => items.iter().any(|item| values_equal(a, item)),
+            _ => false,
+        },
+    }
+}
+
+// ==========================================
+// Executor
+// ==========================================
+
+/// One cell of a result row.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cell {
+    Node(NodeId),
+    Value(Value),
+}
+
+#[derive(Debug, Default)]
+pub struct QueryResult {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<Cell>>,
+    /// More rows matched than the limit allowed
+    pub truncated: bool,
+    /// The deadline passed before the search finished
+    pub timed_out: bool,
+}
+
+type Bindings = Vec<(String, NodeId)>;
+
+/// Runs parsed queries against a graph.
+pub struct Executor<'a> {
+    graph: &'a CodeGraph,
+    property: &'a dyn Fn(NodeId, &str) -> Option<Value>,
+    deadline: Instant,
+    steps: std::cell::Cell<u32>,
+    timed_out: std::cell::Cell<bool>,
+}
+
+impl<'a> Executor<'a> {
+    /// `property` resolves `var.key`; see [`stored_property`] for the basics.
+    pub fn new(
+        graph: &'a CodeGraph,
+        property: &'a dyn Fn(NodeId, &str) -> Option<Value>,
+        deadline: Instant,
+    ) -> Self {
+        Self {
+            graph,
+            property,
+            deadline,
+            steps: std::cell::Cell::new(0),
+            timed_out: std::cell::Cell::new(false),
+        }
+    }
+
+    /// Run a query, returning at most `limit` rows.
+    pub fn execute(&self, query: &Query, limit: usize) -> QueryResult {
+        let limit = query.limit.map_or(limit, |l| l.min(limit));
+        let mut result = QueryResult {
+            columns: query.returns.iter().map(|r| r.alias.clone()).collect(),
+            ..Default::default()
+        };
+        let mut seen = HashSet::new();
+
+        self.match_patterns(&query.patterns, &mut Vec::new(), &mut |bindings| {
+            if query
+                .filter
+                .as_ref()
+                .is_some_and(|filter| !self.eval(filter, bindings))
+            {
+                return true;
+            }
+            let row: Vec<Cell> = query
+                .returns
+                .iter()
+                .map(|item| {
+                    let node = lookup(bindings, &item.var);
+                    match (&item.property, node) {
+                        (None, Some(node)) => Cell::Node(node),
+                        (Some(key), Some(node)) => {
+                            Cell::Value((self.property)(node, key).unwrap_or(Value::Null))
+                        }
+                        _ => Cell::Value(Value::Null),
+                    }
+                })
+                .collect();
+            if query.distinct && !seen.insert(format!("{row:?}")) {
+                return true;
+            }
+            if result.rows.len() == limit {
+                result.truncated = true;
+                return false;
+            }
+            result.rows.push(row);
+            true
+        });
+
+        result.timed_out = self.timed_out.get();
+        result
+    }
+
+    /// Whether the deadline passed; checked every few steps.
+    fn expired(&self) -> bool {
+        if self.timed_out.get() {
+            return true;
+        }
+        let steps = self.steps.get().wrapping_add(1);
+        self.steps.set(steps);
+        if steps.is_multiple_of(DEADLINE_CHECK_INTERVAL) && Instant::now() >= self.deadline {
+            self.timed_out.set(true);
+        }
+        self.timed_out.get()
+    }
+
+    /// Enumerate bindings matching all patterns; `on_match` returns false to
+    /// stop. Returns false when the search was stopped.
+    fn match_patterns(
+        &self,
+        patterns: &[Pattern],
+        bindings: &mut Bindings,
+        on_match: &mut dyn FnMut(&Bindings) -> bool,
+    ) -> bool {
+        let Some((pattern, rest)) = patterns.split_first() else {
+            return on_match(bindings);
+        };
+        for candidate in self.candidates(&pattern.start, bindings) {
+            if self.expired() {
+                return false;
+            }
+            let Some(pushed) = self.bind(&pattern.start, candidate, bindings) else {
+                continue;
+            };
+            let go_on = self.match_steps(&pattern.steps, candidate, rest, bindings, on_match);
+            if pushed {
+                bindings.pop();
+            }
+            if !go_on {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn match_steps(
+        &self,
+        steps: &[(RelPattern, NodePattern)],
+        current: NodeId,
+        rest: &[Pattern],
+        bindings: &mut Bindings,
+        on_match: &mut dyn FnMut(&Bindings) -> bool,
+    ) -> bool {
+        let Some(((rel, node), later)) = steps.split_first() else {
+            return self.match_patterns(rest, bindings, on_match);
+        };
+        for next in self.expand(current, rel) {
+            if self.expired() {
+                return false;
+            }
+            let Some(pushed) = self.bind(node, next, bindings) else {
+                continue;
+            };
+            let go_on = self.match_steps(later, next, rest, bindings, on_match);
+            if pushed {
+                bindings.pop();
+            }
+            if !go_on {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn candidates(&self, node: &NodePattern, bindings: &Bindings) -> Vec<NodeId> {
+        if let Some(bound) = node.var.as_deref().and_then(|v| lookup(bindings, v)) {
+            return vec![bound];
+        }
+        if node.labels.is_empty() {
+            return self.graph.query().execute().unwrap_or_default();
+        }
+        node.labels
+            .iter()
+            .flat_map(|&label| {
+                self.graph
+                    .query()
+                    .node_type(label)
+                    .execute()
+                    .unwrap_or_default()
+            })
+            .collect()
+    }
+
+    /// Check `node_id` against the pattern and bind its variable. `None` if
+    /// it does not match, otherwise whether a binding was pushed.
+    fn bind(&self, node: &NodePattern, node_id: NodeId, bindings: &mut Bindings) -> Option<bool> {
+        let bound = node.var.as_deref().and_then(|v| lookup(bindings, v));
+        if bound.is_some_and(|b| b != node_id) {
+            return None;
+        }
+        let graph_node = self.graph.get_node(node_id).ok()?;
+        if !node.labels.is_empty() && !node.labels.contains(&graph_node.node_type) {
+            return None;
+        }
+        for (key, expected) in &node.properties {
+            let actual = (self.property)(node_id, key)?;
+            if !values_equal(&actual, expected) {
+                return None;
+            }
+        }
+        match (&node.var, bound) {
+            (Some(var), None) => {
+                bindings.push((var.clone(), node_id));
+                Some(true)
+            }
+            _ => Some(false),
+        }
+    }
+
+    /// Nodes `min_hops..=max_hops` edges of the allowed types away.
+    fn expand(&self, start: NodeId, rel: &RelPattern) -> Vec<NodeId> {
+        let mut found = Vec::new();
+        let mut visited = HashSet::from([start]);
+        let mut queue = VecDeque::from([(start, 0)]);
+        if rel.min_hops == 0 {
+            found.push(start);
+        }
+        while let Some((node, depth)) = queue.pop_front() {
+            if depth == rel.max_hops {
+                continue;
+            }
+            for next in self.neighbors(node, rel) {
+                if visited.insert(next) {
+                    if depth + 1 >= rel.min_hops {
+                        found.push(next);
+                    }
+                    queue.push_back((next, depth + 1));
+                }
+            }
+        }
+        found
+    }
+
+    fn neighbors(&self, node: NodeId, rel: &RelPattern) -> Vec<NodeId> {
+        let directions: &[Direction] = match rel.direction {
+            Direction::Both => &[Direction::Outgoing, Direction::Incoming],
+            Direction::Outgoing => &[Direction::Outgoing],
+            Direction::Incoming => &[Direction::Incoming],
+        };
+        let mut neighbors = Vec::new();
+        for &direction in directions {
+            for other in self
+                .graph
+                .get_neighbors(node, direction)
+                .unwrap_or_default()
+            {
+                let (source, target) = match direction {
+                    Direction::Incoming => (other, node),
+                    _ => (node, other),
+                };
+                let allowed = rel.types.is_empty()
+                    || self
+                        .graph
+                        .get_edges_between(source, target)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .filter_map(|id| self.graph.get_edge(id).ok())
+                        .any(|edge| rel.types.contains(&edge.edge_type));
+                if allowed {
+                    neighbors.push(other);
+                }
+            }
+        }
+        neighbors.sort_unstable();
+        neighbors.dedup();
+        neighbors
+    }
+
+    fn value(&self, operand: &Operand, bindings: &Bindings) -> Value {
+        match operand {
+            Operand::Literal(value) => value.clone(),
+            Operand::Property(var, key) => lookup(bindings, var)
+                .and_then(|node| (self.property)(node, key))
+                .unwrap_or(Value::Null),
+        }
+    }
+
+    fn eval(&self, expr: &Expr, bindings: &Bindings) -> bool {
+        match expr {
+            Expr::And(a, b) => self.eval(a, bindings) && self.eval(b, bindings),
+            Expr::Or(a, b) => self.eval(a, bindings) || self.eval(b, bindings),
+            Expr::Not(e) => !self.eval(e, bindings),
+            Expr::Compare(a, op, b) => {
+                compare(&self.value(a, bindings), *op, &self.value(b, bindings))
+            }
+            Expr::IsNull(operand, is_null) => self.value(operand, bindings).is_null() == *is_null,
+            Expr::Truthy(operand) => {
+                values_equal(&self.value(operand, bindings), &Value::Bool(true))
+            }
+            Expr::Exists(pattern) => {
+                let mut found = false;
+                let mut local = bindings.clone();
+                self.match_patterns(std::slice::from_ref(pattern), &mut local, &mut |_| {
+                    found = true;
+                    false
+                });
+                found
+            }
+        }
+    }
+}
+
+fn lookup(bindings: &Bindings, var: &str) -> Option<NodeId> {
+    bindings.iter().find(|(v, _)| v == var).map(|(_, id)| *id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codegraph::PropertyMap;
+    use std::time::Duration;
+
+    #[test]
+    fn test_parse_query() {
+        let query = parse(
+            "match (f:Function)-[:Calls|Invokes*1..3]->(g), (g)<--(c:Class) \
+             WHERE f.name STARTS WITH 'get' AND NOT (f)<-[:Calls]-(:Function {isTest: true}) \
+             RETURN DISTINCT f, g.name AS callee LIMIT 5",
+        )
+        .unwrap();
+        assert_eq!(query.patterns.len(), 2);
+        let (rel, _) = &query.patterns[0].steps[0];
+        assert_eq!(rel.types, vec![EdgeType::Calls, EdgeType::Invokes]);
+        assert_eq!((rel.min_hops, rel.max_hops), (1, 3));
+        assert_eq!(query.patterns[1].steps[0].0.direction, Direction::Incoming);
+        assert!(query.distinct);
+        assert_eq!(query.returns[1].alias, "callee");
+        assert_eq!(query.limit, Some(5));
+        let Some(Expr::And(_, not)) = &query.filter else {
+            panic!("expected AND");
+        };
+        assert!(matches!(**not, Expr::Not(ref e) if matches!(**e, Expr::Exists(_))));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = parse("MATCH (f:Funktion) RETURN f").unwrap_err();
+        assert!(error.starts_with("unknown node type 'Funktion' at offset 9"));
+        let error = parse("MATCH (f) RETURN g").unwrap_err();
+        assert_eq!(error, "variable 'g' is not defined by MATCH");
+        let error = parse("MATCH (f) WHERE f.name = RETURN f").unwrap_err();
+        assert!(error.starts_with("expected a value at offset 25"));
+    }
+
+    #[test]
+    fn test_execute_query() {
+        let mut graph = CodeGraph::in_memory().unwrap();
+        let function = |graph: &mut CodeGraph, name: &str, test: bool| {
+            let properties = PropertyMap::new().with("name", name).with("is_test", test);
+            graph.add_node(NodeType::Function, properties).unwrap()
+        };
+        let handler = function(&mut graph, "api_handler", false);
+        let service = function(&mut graph, "service", false);
+        let save = function(&mut graph, "db_save", false);
+        let other = function(&mut graph, "api_other", false);
+        let test = function(&mut graph, "test_api_other", true);
+        for (from, to) in [
+            (handler, service),
+            (service, save),
+            (other, save),
+            (test, other),
+        ] {
+            graph
+                .add_edge(from, to, EdgeType::Calls, PropertyMap::new())
+                .unwrap();
+        }
+
+        let property = |node, key: &str| stored_property(&graph, node, key);
+        let executor = Executor::new(&graph, &property, Instant::now() + Duration::from_secs(5));
+        let query = parse(
+            "MATCH (f:Function)-[:Calls*1..2]->(g:Function) \
+             WHERE f.name STARTS WITH 'api_' AND g.name STARTS WITH 'db_' \
+               AND NOT (f)<-[:Calls]-(:Function {is_test: true}) \
+             RETURN f.name, g.name",
+        )
+        .unwrap();
+        let result = executor.execute(&query, 100);
+        assert_eq!(result.columns, vec!["f.name", "g.name"]);
+        assert_eq!(
+            result.rows,
+            vec![vec![
+                Cell::Value(Value::from("api_handler")),
+                Cell::Value(Value::from("db_save"))
+            ]]
+        );
+
+        let query = parse("MATCH (f)-[:Calls]->(g) RETURN DISTINCT g LIMIT 2").unwrap();
+        let result = executor.execute(&query, 100);
+        assert_eq!(result.rows.len(), 2);
+        assert!(result.truncated);
+        assert!(!result.timed_out);
+    }
+}
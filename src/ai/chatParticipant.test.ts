@@ -88,10 +88,8 @@ describe('CodeGraphChatParticipant', () => {
             expect(result.output).toContain('2');
             expect(result.output).toContain('1');
             expect(mockClient.sendRequest).toHaveBeenCalledWith(
-                'workspace/executeCommand',
-                expect.objectContaining({
-                    command: 'codegraph.getDependencyGraph',
-                }),
+                'codegraph/getDependencyGraph',
+                expect.any(Object),
                 expect.anything()
             );
         });
@@ -111,10 +109,8 @@ describe('CodeGraphChatParticipant', () => {
             await (vscode.chat as any).simulateRequest('codegraph', 'what are the imports');
 
             expect(mockClient.sendRequest).toHaveBeenCalledWith(
-                'workspace/executeCommand',
-                expect.objectContaining({
-                    command: 'codegraph.getDependencyGraph',
-                }),
+                'codegraph/getDependencyGraph',
+                expect.any(Object),
                 expect.anything()
             );
         });
@@ -147,10 +143,8 @@ describe('CodeGraphChatParticipant', () => {
             expect(result.output).toContain('Call Graph');
             expect(result.output).toContain('processData');
             expect(mockClient.sendRequest).toHaveBeenCalledWith(
-                'workspace/executeCommand',
-                expect.objectContaining({
-                    command: 'codegraph.getCallGraph',
-                }),
+                'codegraph/getCallGraph',
+                expect.any(Object),
                 expect.anything()
             );
         });
@@ -203,10 +197,8 @@ describe('CodeGraphChatParticipant', () => {
             expect(result.output).toContain('5');
             expect(result.output).toContain('2');
             expect(mockClient.sendRequest).toHaveBeenCalledWith(
-                'workspace/executeCommand',
-                expect.objectContaining({
-                    command: 'codegraph.analyzeImpact',
-                }),
+                'codegraph/analyzeImpact',
+                expect.any(Object),
                 expect.anything()
             );
         });
@@ -231,10 +223,8 @@ describe('CodeGraphChatParticipant', () => {
             await (vscode.chat as any).simulateRequest('codegraph', 'what would be affected');
 
             expect(mockClient.sendRequest).toHaveBeenCalledWith(
-                'workspace/executeCommand',
-                expect.objectContaining({
-                    command: 'codegraph.analyzeImpact',
-                }),
+                'codegraph/analyzeImpact',
+                expect.any(Object),
                 expect.anything()
             );
         });
@@ -263,13 +253,8 @@ describe('CodeGraphChatParticipant', () => {
             expect(result.output).toContain('Related Tests');
             expect(result.output).toContain('testProcessData');
             expect(mockClient.sendRequest).toHaveBeenCalledWith(
-                'workspace/executeCommand',
-                expect.objectContaining({
-                    command: 'codegraph.getAIContext',
-                    arguments: expect.arrayContaining([
-                        expect.objectContaining({ contextType: 'test' }),
-                    ]),
-                }),
+                'codegraph/getAIContext',
+                expect.objectContaining({ contextType: 'test' }),
                 expect.anything()
             );
         });
@@ -322,13 +307,8 @@ describe('CodeGraphChatParticipant', () => {
             expect(result.output).toContain('Code Context');
             expect(result.output).toContain('calculateTotal');
             expect(mockClient.sendRequest).toHaveBeenCalledWith(
-                'workspace/executeCommand',
-                expect.objectContaining({
-                    command: 'codegraph.getAIContext',
-                    arguments: expect.arrayContaining([
-                        expect.objectContaining({ contextType: 'explain' }),
-                    ]),
-                }),
+                'codegraph/getAIContext',
+                expect.objectContaining({ contextType: 'explain' }),
                 expect.anything()
             );
         });
@@ -351,12 +331,8 @@ describe('CodeGraphChatParticipant', () => {
             await (vscode.chat as any).simulateRequest('codegraph', 'help debug this error');
 
             expect(mockClient.sendRequest).toHaveBeenCalledWith(
-                'workspace/executeCommand',
-                expect.objectContaining({
-                    arguments: expect.arrayContaining([
-                        expect.objectContaining({ contextType: 'debug' }),
-                    ]),
-                }),
+                'codegraph/getAIContext',
+                expect.objectContaining({ contextType: 'debug' }),
                 expect.anything()
             );
         });
@@ -379,12 +355,8 @@ describe('CodeGraphChatParticipant', () => {
             await (vscode.chat as any).simulateRequest('codegraph', 'how to refactor this');
 
             expect(mockClient.sendRequest).toHaveBeenCalledWith(
-                'workspace/executeCommand',
-                expect.objectContaining({
-                    arguments: expect.arrayContaining([
-                        expect.objectContaining({ contextType: 'modify' }),
-                    ]),
-                }),
+                'codegraph/getAIContext',
+                expect.objectContaining({ contextType: 'modify' }),
                 expect.anything()
             );
         });
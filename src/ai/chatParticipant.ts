@@ -112,15 +112,12 @@ export class CodeGraphChatParticipant {
     ): Promise<void> {
         stream.progress('Analyzing dependencies...');
 
-        const response = await this.client.sendRequest('workspace/executeCommand', {
-            command: 'codegraph.getDependencyGraph',
-            arguments: [{
-                uri,
-                depth: 3,
-                includeExternal: false,
-                direction: 'both',
-            }]
-        }, token) as DependencyGraphResponse;
+        const response = await this.client.sendRequest<DependencyGraphResponse>('codegraph/getDependencyGraph', {
+            uri,
+            depth: 3,
+            includeExternal: false,
+            direction: 'both',
+        }, token);
 
         stream.markdown('## Dependency Graph\n\n');
         stream.markdown(`Found **${response.nodes.length}** files/modules with **${response.edges.length}** dependencies.\n\n`);
@@ -150,19 +147,16 @@ export class CodeGraphChatParticipant {
     ): Promise<void> {
         stream.progress('Analyzing call graph...');
 
-        const response = await this.client.sendRequest('workspace/executeCommand', {
-            command: 'codegraph.getCallGraph',
-            arguments: [{
-                uri,
-                position: {
-                    line: position.line,
-                    character: position.character,
-                },
-                depth: 3,
-                direction: 'both',
-                includeExternal: false,
-            }]
-        }, token) as CallGraphResponse;
+        const response = await this.client.sendRequest<CallGraphResponse>('codegraph/getCallGraph', {
+            uri,
+            position: {
+                line: position.line,
+                character: position.character,
+            },
+            depth: 3,
+            direction: 'both',
+            includeExternal: false,
+        }, token);
 
         stream.markdown('## Call Graph\n\n');
 
@@ -209,17 +203,14 @@ export class CodeGraphChatParticipant {
     ): Promise<void> {
         stream.progress('Analyzing impact...');
 
-        const response = await this.client.sendRequest('workspace/executeCommand', {
-            command: 'codegraph.analyzeImpact',
-            arguments: [{
-                uri,
-                position: {
-                    line: position.line,
-                    character: position.character,
-                },
-                analysisType: 'modify',
-            }]
-        }, token) as ImpactAnalysisResponse;
+        const response = await this.client.sendRequest<ImpactAnalysisResponse>('codegraph/analyzeImpact', {
+            uri,
+            position: {
+                line: position.line,
+                character: position.character,
+            },
+            analysisType: 'modify',
+        }, token);
 
         stream.markdown('## Impact Analysis\n\n');
         stream.markdown(`| Metric | Count |\n|--------|-------|\n`);
@@ -256,18 +247,15 @@ export class CodeGraphChatParticipant {
     ): Promise<void> {
         stream.progress('Finding related tests...');
 
-        const response = await this.client.sendRequest('workspace/executeCommand', {
-            command: 'codegraph.getAIContext',
-            arguments: [{
-                uri,
-                position: {
-                    line: position.line,
-                    character: position.character,
-                },
-                contextType: 'test',
-                maxTokens: 2000,
-            }]
-        }, token) as AIContextResponse;
+        const response = await this.client.sendRequest<AIContextResponse>('codegraph/getAIContext', {
+            uri,
+            position: {
+                line: position.line,
+                character: position.character,
+            },
+            contextType: 'test',
+            maxTokens: 2000,
+        }, token);
 
         stream.markdown('## Related Tests\n\n');
 
@@ -309,18 +297,15 @@ export class CodeGraphChatParticipant {
 
         stream.progress(`Getting ${intent} context...`);
 
-        const response = await this.client.sendRequest('workspace/executeCommand', {
-            command: 'codegraph.getAIContext',
-            arguments: [{
-                uri,
-                position: {
-                    line: position.line,
-                    character: position.character,
-                },
-                contextType: intent,
-                maxTokens: 4000,
-            }]
-        }, token) as AIContextResponse;
+        const response = await this.client.sendRequest<AIContextResponse>('codegraph/getAIContext', {
+            uri,
+            position: {
+                line: position.line,
+                character: position.character,
+            },
+            contextType: intent,
+            maxTokens: 4000,
+        }, token);
 
         // Stream the primary context
         stream.markdown('## Code Context\n\n');
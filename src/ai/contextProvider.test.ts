@@ -87,15 +87,10 @@ describe('CodeGraphAIProvider', () => {
 
       // Verify LSP request was made
       expect(mockClient.sendRequest).toHaveBeenCalledWith(
-        'workspace/executeCommand',
+        expect.objectContaining({ method: 'codegraph/getAIContext' }),
         expect.objectContaining({
-          command: 'codegraph.getAIContext',
-          arguments: expect.arrayContaining([
-            expect.objectContaining({
-              contextType: 'explain',
-              maxTokens: 4000,
-            }),
-          ]),
+          contextType: 'explain',
+          maxTokens: 4000,
         })
       );
 
@@ -138,13 +133,9 @@ describe('CodeGraphAIProvider', () => {
       await provider.provideCodeContext(doc as any, { line: 0, character: 6 } as any, 'modify');
 
       expect(mockClient.sendRequest).toHaveBeenCalledWith(
-        'workspace/executeCommand',
+        expect.objectContaining({ method: 'codegraph/getAIContext' }),
         expect.objectContaining({
-          arguments: expect.arrayContaining([
-            expect.objectContaining({
-              maxTokens: 8000,
-            }),
-          ]),
+          maxTokens: 8000,
         })
       );
     });
@@ -176,13 +167,9 @@ describe('CodeGraphAIProvider', () => {
         await provider.provideCodeContext(doc as any, { line: 0, character: 9 } as any, intent);
 
         expect(mockClient.sendRequest).toHaveBeenCalledWith(
-          'workspace/executeCommand',
+          expect.objectContaining({ method: 'codegraph/getAIContext' }),
           expect.objectContaining({
-            arguments: expect.arrayContaining([
-              expect.objectContaining({
-                contextType: intent,
-              }),
-            ]),
+            contextType: intent,
           })
         );
       }
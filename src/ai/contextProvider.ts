@@ -2,7 +2,6 @@ import * as vscode from 'vscode';
 import { LanguageClient, RequestType } from 'vscode-languageclient/node';
 import { AIContextParams, AIContextResponse } from '../types';
 
-// eslint-disable-next-line @typescript-eslint/no-unused-vars
 namespace GetAIContextRequest {
     export const type = new RequestType<AIContextParams, AIContextResponse, void>(
         'codegraph/getAIContext'
@@ -46,18 +45,15 @@ export class CodeGraphAIProvider {
         const config = vscode.workspace.getConfiguration('codegraph');
         const maxTokens = config.get<number>('ai.maxContextTokens', 4000);
 
-        const response = await this.client.sendRequest('workspace/executeCommand', {
-            command: 'codegraph.getAIContext',
-            arguments: [{
-                uri: document.uri.toString(),
-                position: {
-                    line: position.line,
-                    character: position.character,
-                },
-                contextType: intent,
-                maxTokens,
-            }]
-        }) as AIContextResponse;
+        const response = await this.client.sendRequest(GetAIContextRequest.type, {
+            uri: document.uri.toString(),
+            position: {
+                line: position.line,
+                character: position.character,
+            },
+            contextType: intent,
+            maxTokens,
+        });
 
         return this.formatForAI(response);
     }
@@ -267,7 +267,15 @@ describe('CodeGraphToolManager', () => {
             const response = {
                 directImpact: [],
                 indirectImpact: [
-                    { uri: 'file:///z.ts', path: ['a.ts', 'b.ts', 'z.ts'], severity: 'breaking' },
+                    {
+                        uri: 'file:///z.ts',
+                        range: { start: { line: 4 } },
+                        symbol: 'render',
+                        distance: 2,
+                        path: ['a.ts', 'b.ts', 'z.ts'],
+                        edgePath: [],
+                        severity: 'breaking',
+                    },
                 ],
                 affectedTests: [],
                 summary: { filesAffected: 1, breakingChanges: 1, warnings: 0 },
@@ -277,6 +285,7 @@ describe('CodeGraphToolManager', () => {
 
             expect(result).toContain('## Indirect Impact (1)');
             expect(result).toContain('a.ts → b.ts → z.ts');
+            expect(result).toContain('**render** at file:///z.ts:5 (distance 2)');
         });
 
         it('should format affected tests', async () => {
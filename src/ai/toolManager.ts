@@ -23,11 +23,11 @@ export class CodeGraphToolManager {
     constructor(private client: LanguageClient) {}
 
     /**
-     * Execute an LSP command with a small retry/backoff to smooth over transient timeouts.
+     * Send a codegraph/* request with a small retry/backoff to smooth over transient timeouts.
      */
     private async sendRequestWithRetry<T>(
-        command: string,
-        args: unknown,
+        method: string,
+        params: unknown,
         token: vscode.CancellationToken,
         options: { retries?: number; delayMs?: number; backoffFactor?: number } = {}
     ): Promise<T> {
@@ -41,11 +41,7 @@ export class CodeGraphToolManager {
             }
 
             try {
-                return await this.client.sendRequest(
-                    'workspace/executeCommand',
-                    { command, arguments: [args] },
-                    token
-                ) as T;
+                return await this.client.sendRequest<T>(method, params, token);
             } catch (error) {
                 const isLastAttempt = attempt === retries;
                 if (isLastAttempt || !this.isRetryableError(error)) {
@@ -131,7 +127,7 @@ export class CodeGraphToolManager {
 
                     try {
                         const response = await this.sendRequestWithRetry<DependencyGraphResponse>(
-                            'codegraph.getDependencyGraph',
+                            'codegraph/getDependencyGraph',
                             { uri, depth, includeExternal, direction },
                             token,
                             { retries: 1 }
@@ -166,7 +162,7 @@ export class CodeGraphToolManager {
 
                     try {
                         const response = await this.sendRequestWithRetry<CallGraphResponse>(
-                            'codegraph.getCallGraph',
+                            'codegraph/getCallGraph',
                             {
                                 uri,
                                 position: { line, character },
@@ -211,7 +207,7 @@ export class CodeGraphToolManager {
 
                     try {
                         const response = await this.sendRequestWithRetry<ImpactAnalysisResponse>(
-                            'codegraph.analyzeImpact',
+                            'codegraph/analyzeImpact',
                             {
                                 uri,
                                 position: { line, character },
@@ -253,7 +249,7 @@ export class CodeGraphToolManager {
 
                     try {
                         const response = await this.sendRequestWithRetry<AIContextResponse>(
-                            'codegraph.getAIContext',
+                            'codegraph/getAIContext',
                             {
                                 uri,
                                 position: { line, character },
@@ -324,7 +320,7 @@ export class CodeGraphToolManager {
 
                     try {
                         const response = await this.sendRequestWithRetry<RelatedTestsResponse>(
-                            'codegraph.findRelatedTests',
+                            'codegraph/findRelatedTests',
                             {
                                 uri,
                                 position: { line, character: 0 },
@@ -480,7 +476,7 @@ export class CodeGraphToolManager {
 
                     try {
                         const response = await this.sendRequestWithRetry<ComplexityResponse>(
-                            'codegraph.analyzeComplexity',
+                            'codegraph/analyzeComplexity',
                             { uri, line, threshold, includeMetrics: true },
                             token,
                             { retries: 1 }
@@ -515,7 +511,7 @@ export class CodeGraphToolManager {
 
                     try {
                         const response = await this.sendRequestWithRetry<UnusedCodeResponse>(
-                            'codegraph.findUnusedCode',
+                            'codegraph/findUnusedCode',
                             { uri, scope, includeTests, confidence },
                             token,
                             { retries: 1 }
@@ -553,7 +549,7 @@ export class CodeGraphToolManager {
 
                     try {
                         const response = await this.sendRequestWithRetry<CouplingResponse>(
-                            'codegraph.analyzeCoupling',
+                            'codegraph/analyzeCoupling',
                             { uri, includeExternal, depth },
                             token,
                             { retries: 1 }
@@ -726,7 +722,7 @@ export class CodeGraphToolManager {
             response.indirectImpact.slice(0, indirectLimit).forEach(impact => {
                 const severity = impact.severity === 'breaking' ? '🔴' :
                                 impact.severity === 'warning' ? '🟡' : '🔵';
-                output += `${severity} ${impact.uri}\n`;
+                output += `${severity} **${impact.symbol}** at ${impact.uri}:${impact.range.start.line + 1} (distance ${impact.distance})\n`;
                 output += `  Dependency path: ${impact.path.join(' → ')}\n`;
             });
             if (response.indirectImpact.length > indirectLimit) {
@@ -917,7 +913,11 @@ export class CodeGraphToolManager {
         output += `- Total Functions: ${fileSummary.totalFunctions}\n`;
         output += `- Average Complexity: ${fileSummary.averageComplexity.toFixed(1)}\n`;
         output += `- Max Complexity: ${fileSummary.maxComplexity}\n`;
-        output += `- Functions Above Threshold: ${fileSummary.functionsAboveThreshold}\n\n`;
+        output += `- Functions Above Threshold: ${fileSummary.functionsAboveThreshold}\n`;
+        if (fileSummary.averageMaintainabilityIndex !== null) {
+            output += `- Maintainability Index: ${fileSummary.averageMaintainabilityIndex.toFixed(0)} average, ${fileSummary.minMaintainabilityIndex?.toFixed(0)} lowest\n`;
+        }
+        output += `- Halstead Volume: ${fileSummary.totalHalsteadVolume.toFixed(0)}, Effort: ${fileSummary.totalHalsteadEffort.toFixed(0)}\n\n`;
 
         // Function details
         const functionLimit = shouldSummarize ? 10 : functions.length;
@@ -931,7 +931,11 @@ export class CodeGraphToolManager {
                 output += `- **Complexity**: ${func.complexity} (Grade: ${func.grade})\n`;
                 output += `- Location: ${func.location.uri}:${func.location.range.start.line + 1}\n`;
                 output += `- Branches: ${func.details.branches}, Loops: ${func.details.loops}, Conditions: ${func.details.conditions}\n`;
-                output += `- Nesting Depth: ${func.details.nestingDepth}, Lines: ${func.details.linesOfCode}\n\n`;
+                output += `- Nesting Depth: ${func.details.nestingDepth}, Lines: ${func.details.linesOfCode}\n`;
+                if (func.halstead && func.maintainabilityIndex !== null) {
+                    output += `- Maintainability Index: ${func.maintainabilityIndex.toFixed(0)}, Halstead Volume: ${func.halstead.volume.toFixed(0)}, Effort: ${func.halstead.effort.toFixed(0)}\n`;
+                }
+                output += '\n';
             });
 
             if (functions.length > functionLimit) {
@@ -994,6 +998,17 @@ export class CodeGraphToolManager {
             output += `... and ${unusedItems.length - itemLimit} more items\n`;
         }
 
+        const clusters = (response.deadClusters ?? []).filter(c => c.size > 1);
+        if (clusters.length > 0) {
+            const clusterLimit = shouldSummarize ? 5 : clusters.length;
+            output += `\n## Dead Clusters (${clusters.length})\n`;
+            output += 'Unreachable code that only calls itself; remove each cluster as a whole:\n\n';
+            clusters.slice(0, clusterLimit).forEach((cluster, i) => {
+                output += `${i + 1}. ${cluster.size} symbols, ${cluster.lines} lines: ${cluster.members.slice(0, 8).join(', ')}`;
+                output += cluster.members.length > 8 ? ', ...\n' : '\n';
+            });
+        }
+
         return output;
     }
 
@@ -1045,6 +1060,19 @@ export class CodeGraphToolManager {
         output += `- Cohesion Type: ${cohesion.cohesionType}\n`;
         output += `- Internal Reference Ratio: ${(cohesion.internalReferenceRatio * 100).toFixed(0)}%\n\n`;
 
+        const splittable = cohesion.classes.filter(c => c.lcom4 > 1);
+        if (splittable.length > 0) {
+            output += `### Class Cohesion (LCOM4)\n`;
+            splittable.forEach(c => {
+                const godClass = c.isGodClass ? ' ⚠️ god class' : '';
+                output += `- **${c.name}**: LCOM4 ${c.lcom4}, ${c.methods} methods, fan-out ${c.fanOut}${godClass}\n`;
+                c.components.forEach(group => {
+                    output += `  - ${group.methods.join(', ')}${group.fields.length > 0 ? ` (fields: ${group.fields.join(', ')})` : ''}\n`;
+                });
+            });
+            output += '\n';
+        }
+
         // Violations
         if (violations.length > 0) {
             output += `## Architecture Violations (${violations.length})\n`;
@@ -13,36 +13,31 @@ import {
 } from '../types';
 import { GraphVisualizationPanel } from '../views/graphPanel';
 
-// Define custom request types (used for LSP type inference)
-// eslint-disable-next-line @typescript-eslint/no-unused-vars
+// Custom request types registered by the server as codegraph/* methods
 namespace GetDependencyGraphRequest {
     export const type = new RequestType<DependencyGraphParams, DependencyGraphResponse, void>(
         'codegraph/getDependencyGraph'
     );
 }
 
-// eslint-disable-next-line @typescript-eslint/no-unused-vars
 namespace GetCallGraphRequest {
     export const type = new RequestType<CallGraphParams, CallGraphResponse, void>(
         'codegraph/getCallGraph'
     );
 }
 
-// eslint-disable-next-line @typescript-eslint/no-unused-vars
 namespace GetImpactAnalysisRequest {
     export const type = new RequestType<ImpactAnalysisParams, ImpactAnalysisResponse, void>(
         'codegraph/analyzeImpact'
     );
 }
 
-// eslint-disable-next-line @typescript-eslint/no-unused-vars
 namespace GetParserMetricsRequest {
     export const type = new RequestType<ParserMetricsParams, ParserMetricsResponse, void>(
         'codegraph/getParserMetrics'
     );
 }
 
-// eslint-disable-next-line @typescript-eslint/no-unused-vars
 namespace ReindexWorkspaceRequest {
     export const type = new RequestType<void, void, void>(
         'codegraph/reindexWorkspace'
@@ -75,16 +70,13 @@ export function registerCommands(
             }
 
             try {
-                const response = await client.sendRequest('workspace/executeCommand', {
-                    command: 'codegraph.getDependencyGraph',
-                    arguments: [{
-                        uri: editor.document.uri.toString(),
-                        depth: vscode.workspace.getConfiguration('codegraph')
-                            .get<number>('visualization.defaultDepth', 3),
-                        includeExternal: false,
-                        direction: 'both',
-                    }]
-                }) as DependencyGraphResponse;
+                const response = await client.sendRequest(GetDependencyGraphRequest.type, {
+                    uri: editor.document.uri.toString(),
+                    depth: vscode.workspace.getConfiguration('codegraph')
+                        .get<number>('visualization.defaultDepth', 3),
+                    includeExternal: false,
+                    direction: 'both',
+                });
 
                 GraphVisualizationPanel.createOrShow(
                     context.extensionUri,
@@ -106,20 +98,17 @@ export function registerCommands(
             }
 
             try {
-                const response = await client.sendRequest('workspace/executeCommand', {
-                    command: 'codegraph.getCallGraph',
-                    arguments: [{
-                        uri: editor.document.uri.toString(),
-                        position: {
-                            line: editor.selection.active.line,
-                            character: editor.selection.active.character,
-                        },
-                        direction: 'both',
-                        depth: vscode.workspace.getConfiguration('codegraph')
-                            .get<number>('visualization.defaultDepth', 3),
-                        includeExternal: false,
-                    }]
-                }) as CallGraphResponse;
+                const response = await client.sendRequest(GetCallGraphRequest.type, {
+                    uri: editor.document.uri.toString(),
+                    position: {
+                        line: editor.selection.active.line,
+                        character: editor.selection.active.character,
+                    },
+                    direction: 'both',
+                    depth: vscode.workspace.getConfiguration('codegraph')
+                        .get<number>('visualization.defaultDepth', 3),
+                    includeExternal: false,
+                });
 
                 GraphVisualizationPanel.createOrShow(
                     context.extensionUri,
@@ -132,6 +121,17 @@ export function registerCommands(
             }
     });
 
+    const showImpact = async (params: ImpactAnalysisParams) => {
+        try {
+            const response = await client.sendRequest(GetImpactAnalysisRequest.type, params);
+
+            // Show impact analysis results
+            showImpactAnalysisResults(response);
+        } catch (error) {
+            vscode.window.showErrorMessage(`CodeGraph: Failed to analyze impact: ${error}`);
+        }
+    };
+
     // Analyze Impact
     safeRegisterCommand('codegraph.analyzeImpact', async () => {
             const editor = vscode.window.activeTextEditor;
@@ -154,33 +154,24 @@ export function registerCommands(
                 return;
             }
 
-            try {
-                const response = await client.sendRequest('workspace/executeCommand', {
-                    command: 'codegraph.analyzeImpact',
-                    arguments: [{
-                        uri: editor.document.uri.toString(),
-                        position: {
-                            line: editor.selection.active.line,
-                            character: editor.selection.active.character,
-                        },
-                        analysisType: analysisType.value as 'modify' | 'delete' | 'rename',
-                    }]
-                }) as ImpactAnalysisResponse;
-
-                // Show impact analysis results
-                showImpactAnalysisResults(response);
-            } catch (error) {
-                vscode.window.showErrorMessage(`CodeGraph: Failed to analyze impact: ${error}`);
-            }
+            await showImpact({
+                uri: editor.document.uri.toString(),
+                position: {
+                    line: editor.selection.active.line,
+                    character: editor.selection.active.character,
+                },
+                analysisType: analysisType.value as 'modify' | 'delete' | 'rename',
+            });
     });
 
+    // Show Impact - invoked by the server's code action with the symbol and analysis type.
+    // Client-only: the server advertises codegraph.<method> names for its own requests.
+    safeRegisterCommand('codegraph.showImpact', showImpact);
+
     // Show Parser Metrics
     safeRegisterCommand('codegraph.showMetrics', async () => {
             try {
-                const response = await client.sendRequest('workspace/executeCommand', {
-                    command: 'codegraph.getParserMetrics',
-                    arguments: []
-                }) as ParserMetricsResponse;
+                const response = await client.sendRequest(GetParserMetricsRequest.type, {});
 
                 showMetricsPanel(response);
             } catch (error) {
@@ -226,10 +217,7 @@ export function registerCommands(
                         cancellable: false,
                     },
                     async () => {
-                        await client.sendRequest('workspace/executeCommand', {
-                            command: 'codegraph.reindexWorkspace',
-                            arguments: []
-                        });
+                        await client.sendRequest(ReindexWorkspaceRequest.type);
                     }
                 );
                 vscode.window.showInformationMessage('CodeGraph: Workspace reindexed successfully');
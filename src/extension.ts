@@ -46,8 +46,12 @@ export async function activate(context: vscode.ExtensionContext): Promise<void>
             { scheme: 'file', language: 'go' },
         ],
         synchronize: {
+            configurationSection: 'codegraph',
             fileEvents: vscode.workspace.createFileSystemWatcher('**/*'),
         },
+        initializationOptions: {
+            diagnostics: config.get('diagnostics'),
+        },
         outputChannel: vscode.window.createOutputChannel('CodeGraph'),
         traceOutputChannel: vscode.window.createOutputChannel('CodeGraph Trace'),
     };
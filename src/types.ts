@@ -136,18 +136,31 @@ export interface ImpactAnalysisParams {
     uri: string;
     position: Position;
     analysisType: 'modify' | 'delete' | 'rename';
+    depth?: number;
+    maxFanOut?: number;
 }
 
 export interface DirectImpact {
     uri: string;
     range: Range;
-    type: 'caller' | 'reference' | 'subclass' | 'implementation';
+    type: 'caller' | 'reference' | 'subclass' | 'implementation' | 'importer';
     severity: 'breaking' | 'warning' | 'info';
+    symbol: string;
+}
+
+export interface ImpactEdge {
+    from: string;
+    to: string;
+    edgeType: string;
 }
 
 export interface IndirectImpact {
     uri: string;
+    range: Range;
+    symbol: string;
+    distance: number;
     path: string[];
+    edgePath: ImpactEdge[];
     severity: 'breaking' | 'warning' | 'info';
 }
 
@@ -164,6 +177,7 @@ export interface ImpactAnalysisResponse {
         filesAffected: number;
         breakingChanges: number;
         warnings: number;
+        truncatedDependents: number;
     };
 }
 
@@ -262,6 +276,18 @@ export interface ComplexityDetails {
     linesOfCode: number;
 }
 
+export interface HalsteadMetrics {
+    distinctOperators: number;
+    distinctOperands: number;
+    totalOperators: number;
+    totalOperands: number;
+    vocabulary: number;
+    length: number;
+    volume: number;
+    difficulty: number;
+    effort: number;
+}
+
 export interface LocationInfo {
     uri: string;
     range: Range;
@@ -273,6 +299,8 @@ export interface FunctionComplexity {
     grade: string;
     location: LocationInfo;
     details: ComplexityDetails;
+    halstead: HalsteadMetrics | null;
+    maintainabilityIndex: number | null;
 }
 
 export interface FileSummary {
@@ -281,6 +309,10 @@ export interface FileSummary {
     maxComplexity: number;
     functionsAboveThreshold: number;
     overallGrade: string;
+    averageMaintainabilityIndex: number | null;
+    minMaintainabilityIndex: number | null;
+    totalHalsteadVolume: number;
+    totalHalsteadEffort: number;
 }
 
 export interface ComplexityResponse {
@@ -298,6 +330,8 @@ export interface UnusedCodeParams {
     scope: 'file' | 'module' | 'workspace';
     includeTests?: boolean;
     confidence?: number;
+    /** Extra symbol name globs to treat as entry points */
+    entryPoints?: string[];
 }
 
 export interface UnusedItem {
@@ -307,6 +341,8 @@ export interface UnusedItem {
     confidence: number;
     reason: string;
     safeToRemove: boolean;
+    /** Graph centrality of the symbol (0-1); 0 for imports and variables */
+    importance: number;
 }
 
 export interface UnusedByType {
@@ -323,8 +359,16 @@ export interface UnusedSummary {
     estimatedLinesRemovable: number;
 }
 
+export interface DeadCluster {
+    size: number;
+    members: string[];
+    files: string[];
+    lines: number;
+}
+
 export interface UnusedCodeResponse {
     unusedItems: UnusedItem[];
+    deadClusters: DeadCluster[];
     summary: UnusedSummary;
 }
 
@@ -346,10 +390,29 @@ export interface CouplingMetrics {
     dependencies: string[];
 }
 
+export interface MethodGroup {
+    methods: string[];
+    fields: string[];
+}
+
+export interface ClassCohesion {
+    name: string;
+    uri: string;
+    range: Range;
+    methods: number;
+    fields: number;
+    lcom4: number;
+    fanOut: number;
+    isGodClass: boolean;
+    components: MethodGroup[];
+    suggestions: string[];
+}
+
 export interface CohesionMetrics {
     score: number;
     cohesionType: string;
     internalReferenceRatio: number;
+    classes: ClassCohesion[];
 }
 
 export interface ArchViolation {
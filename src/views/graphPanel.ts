@@ -28,7 +28,6 @@ interface NodeLocation {
     };
 }
 
-// eslint-disable-next-line @typescript-eslint/no-unused-vars
 namespace GetNodeLocationRequest {
     export const type = new RequestType<{ nodeId: string }, NodeLocation | null, void>(
         'codegraph/getNodeLocation'
@@ -174,10 +173,7 @@ export class GraphVisualizationPanel {
 
     private async handleNodeClick(nodeId: string): Promise<void> {
         try {
-            const location = await this.client.sendRequest('workspace/executeCommand', {
-                command: 'codegraph.getNodeLocation',
-                arguments: [{ nodeId }]
-            }) as NodeLocation | null;
+            const location = await this.client.sendRequest(GetNodeLocationRequest.type, { nodeId });
 
             if (location) {
                 const uri = vscode.Uri.parse(location.uri);
@@ -217,10 +213,7 @@ export class GraphVisualizationPanel {
 
         try {
             // Get the location of the node to expand from
-            const location = await this.client.sendRequest('workspace/executeCommand', {
-                command: 'codegraph.getNodeLocation',
-                arguments: [{ nodeId }]
-            }) as NodeLocation | null;
+            const location = await this.client.sendRequest(GetNodeLocationRequest.type, { nodeId });
 
             if (!location) {
                 this.panel.webview.postMessage({
@@ -236,27 +229,21 @@ export class GraphVisualizationPanel {
 
             if (this.currentGraphType === 'dependency') {
                 // Fetch dependency graph centered on this node's file
-                const response = await this.client.sendRequest('workspace/executeCommand', {
-                    command: 'codegraph.getDependencyGraph',
-                    arguments: [{
-                        uri: location.uri,
-                        depth: 2,
-                        includeExternal: false,
-                        direction: 'both',
-                    }]
-                }) as DependencyGraphResponse;
+                const response = await this.client.sendRequest(GetDependencyGraphRequest.type, {
+                    uri: location.uri,
+                    depth: 2,
+                    includeExternal: false,
+                    direction: 'both',
+                });
                 expandedData = this.convertToGraphData('dependency', response);
             } else {
                 // Fetch call graph centered on this node's position
-                const response = await this.client.sendRequest('workspace/executeCommand', {
-                    command: 'codegraph.getCallGraph',
-                    arguments: [{
-                        uri: location.uri,
-                        position: location.range.start,
-                        depth: 2,
-                        includeCallers: true,
-                    }]
-                }) as CallGraphResponse;
+                const response = await this.client.sendRequest(GetCallGraphRequest.type, {
+                    uri: location.uri,
+                    position: location.range.start,
+                    depth: 2,
+                    direction: 'both',
+                });
                 expandedData = this.convertToGraphData('call', response);
             }
 